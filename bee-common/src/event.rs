@@ -0,0 +1,80 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use dashmap::DashMap;
+
+use std::any::{Any, TypeId};
+
+type Listener<'a> = dyn Fn(&dyn Any) + Send + Sync + 'a;
+
+/// An event bus for arbitrary event types.
+#[derive(Default)]
+pub struct Bus<'a> {
+    listeners: DashMap<TypeId, Vec<Box<Listener<'a>>>>,
+}
+
+impl<'a> Bus<'a> {
+    /// Creates a new event bus.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dispatches an event to all the listeners registered for its type.
+    pub fn dispatch<E: Any>(&self, event: E) {
+        if let Some(listeners) = self.listeners.get(&TypeId::of::<E>()) {
+            listeners.iter().for_each(|listener| listener(&event))
+        }
+    }
+
+    /// Adds a listener for the event type `E`.
+    pub fn add_listener<E: Any, F: Fn(&E) + Send + Sync + 'a>(&self, handler: F) {
+        self.listeners
+            .entry(TypeId::of::<E>())
+            .or_insert_with(Vec::new)
+            .push(Box::new(move |event: &dyn Any| {
+                if let Some(event) = event.downcast_ref::<E>() {
+                    handler(event)
+                }
+            }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Foo(usize);
+    struct Bar;
+
+    #[test]
+    fn dispatch_to_matching_listeners() {
+        let bus = Bus::new();
+        let foos = AtomicUsize::new(0);
+        let bars = AtomicUsize::new(0);
+
+        bus.add_listener(|foo: &Foo| {
+            foos.fetch_add(foo.0, Ordering::SeqCst);
+        });
+        bus.add_listener(|_: &Bar| {
+            bars.fetch_add(1, Ordering::SeqCst);
+        });
+
+        bus.dispatch(Foo(2));
+        bus.dispatch(Foo(3));
+        bus.dispatch(Bar);
+
+        assert_eq!(foos.load(Ordering::SeqCst), 5);
+        assert_eq!(bars.load(Ordering::SeqCst), 1);
+    }
+}
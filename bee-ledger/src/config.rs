@@ -0,0 +1,52 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use serde::Deserialize;
+
+const DEFAULT_CHANNEL_SIZE: usize = 1000;
+
+#[derive(Default, Deserialize)]
+pub struct LedgerConfigBuilder {
+    channel_size: Option<usize>,
+}
+
+impl LedgerConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn channel_size(mut self, channel_size: usize) -> Self {
+        self.channel_size.replace(channel_size);
+        self
+    }
+
+    pub fn finish(self) -> LedgerConfig {
+        LedgerConfig {
+            channel_size: self.channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LedgerConfig {
+    channel_size: usize,
+}
+
+impl LedgerConfig {
+    pub fn build() -> LedgerConfigBuilder {
+        LedgerConfigBuilder::new()
+    }
+
+    /// Number of solid milestones queued for the ledger worker before new ones are dropped.
+    pub fn channel_size(&self) -> usize {
+        self.channel_size
+    }
+}
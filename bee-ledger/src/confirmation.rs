@@ -0,0 +1,498 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use bee_crypto::ternary::Hash;
+use bee_protocol::{tangle::MsTangle, MilestoneIndex};
+use bee_transaction::{
+    bundled::{Address, Bundle, BundledTransactionField, BundledTransactions},
+    TransactionVertex,
+};
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug)]
+pub(crate) enum ConfirmationError {
+    MissingTransaction(Hash),
+}
+
+/// Reason for which a confirmed bundle has been excluded from the ledger.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// The values of the bundle don't sum up to zero.
+    InvalidValueSum(i64),
+    /// Applying the bundle would make the balance of `address` negative.
+    InsufficientBalance { address: Address, balance: u64, delta: i64 },
+    /// The transactions following the tail through the trunk don't form a whole bundle.
+    IncompleteBundle,
+}
+
+/// Transactions and ledger mutations newly confirmed by a milestone.
+#[derive(Default)]
+pub(crate) struct Confirmation {
+    /// Hashes of the newly confirmed transactions, in confirmation order.
+    pub(crate) transactions: Vec<Hash>,
+    /// Tail hashes of the newly confirmed value bundles applied to the ledger, in confirmation order.
+    pub(crate) bundles: Vec<Hash>,
+    /// Tail hashes of the newly confirmed value bundles, and of the incomplete ones, excluded from the ledger, in
+    /// confirmation order.
+    pub(crate) conflicting_bundles: Vec<(Hash, ConflictReason)>,
+    /// Hashes of all the transactions of the conflicting bundles.
+    pub(crate) conflicting_transactions: Vec<Hash>,
+    /// Combined ledger diff of the applied bundles.
+    pub(crate) diff: HashMap<Address, i64>,
+    /// Input addresses of the newly confirmed value bundles, conflicting ones included since their signatures reveal
+    /// parts of the keys all the same.
+    pub(crate) spent_addresses: HashSet<Address>,
+}
+
+fn check_bundle(
+    state: &HashMap<Address, u64>,
+    milestone_diff: &HashMap<Address, i64>,
+    diff: &HashMap<Address, i64>,
+) -> Result<(), ConflictReason> {
+    let sum: i64 = diff.values().sum();
+
+    if sum != 0 {
+        return Err(ConflictReason::InvalidValueSum(sum));
+    }
+
+    for (address, delta) in diff {
+        let balance = *state.get(address).unwrap_or(&0) as i64 + milestone_diff.get(address).unwrap_or(&0);
+
+        if balance + delta < 0 {
+            return Err(ConflictReason::InsufficientBalance {
+                address: address.clone(),
+                balance: balance as u64,
+                delta: *delta,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the bundle of `tail` along with the hashes of its transactions, in the same order, or the hashes of the
+// transactions of the bundle found before it turned out to be incomplete.
+async fn load_bundle(tangle: &MsTangle, tail: &Hash) -> Result<(Bundle, Vec<Hash>), Vec<Hash>> {
+    let mut transactions = BundledTransactions::new();
+    let mut hashes = Vec::new();
+    let mut hash = *tail;
+    let mut bundle = None;
+
+    loop {
+        let transaction = match tangle.fetch(&hash).await {
+            Some(transaction) => transaction,
+            None => return Err(hashes),
+        };
+
+        if *bundle.get_or_insert(*transaction.bundle()) != *transaction.bundle() {
+            return Err(hashes);
+        }
+
+        hashes.push(hash);
+
+        if *transaction.index().to_inner() != transactions.len() {
+            return Err(hashes);
+        }
+
+        let is_head = transaction.is_head();
+        hash = *transaction.trunk();
+
+        transactions.push((*transaction).clone());
+
+        if is_head {
+            break;
+        }
+    }
+
+    Ok((Bundle::from(transactions), hashes))
+}
+
+/// Walks the past cone of the milestone `hash` and collects the transactions and value bundles it newly confirms.
+///
+/// The cone is traversed depth-first, trunk before branch, and a transaction is only visited once both of its
+/// parents have been, which makes the confirmation order deterministic. Solid entry points and already confirmed
+/// transactions bound the walk.
+/// Value bundles are checked against `state` in confirmation order; a bundle that would make a balance negative,
+/// given the bundles applied before it, is reported as conflicting and left out of the diff.
+pub(crate) async fn confirm(
+    tangle: &MsTangle,
+    state: &HashMap<Address, u64>,
+    hash: &Hash,
+) -> Result<Confirmation, ConfirmationError> {
+    let mut confirmation = Confirmation::default();
+    let mut visited = HashSet::new();
+    let mut hashes = vec![*hash];
+
+    while let Some(hash) = hashes.last().copied() {
+        if visited.contains(&hash) {
+            hashes.pop();
+            continue;
+        }
+
+        if tangle.is_solid_entry_point(&hash)
+            || tangle
+                .get_metadata(&hash)
+                .map_or(false, |metadata| metadata.flags().is_confirmed())
+        {
+            visited.insert(hash);
+            hashes.pop();
+            continue;
+        }
+
+        let transaction = tangle
+            .fetch(&hash)
+            .await
+            .ok_or(ConfirmationError::MissingTransaction(hash))?;
+
+        if !visited.contains(transaction.trunk()) {
+            hashes.push(*transaction.trunk());
+            continue;
+        }
+
+        if !visited.contains(transaction.branch()) {
+            hashes.push(*transaction.branch());
+            continue;
+        }
+
+        visited.insert(hash);
+        hashes.pop();
+        confirmation.transactions.push(hash);
+
+        if !transaction.is_tail() {
+            continue;
+        }
+
+        // An incomplete bundle can't be checked, so it is excluded from the ledger like an invalid one.
+        let (bundle, bundle_hashes) = match load_bundle(tangle, &hash).await {
+            Ok(loaded) => loaded,
+            Err(bundle_hashes) => {
+                confirmation
+                    .conflicting_bundles
+                    .push((hash, ConflictReason::IncompleteBundle));
+                confirmation.conflicting_transactions.extend(bundle_hashes);
+                continue;
+            }
+        };
+
+        if (&bundle)
+            .into_iter()
+            .all(|transaction| *transaction.value().to_inner() == 0)
+        {
+            continue;
+        }
+
+        for transaction in &bundle {
+            if *transaction.value().to_inner() < 0 {
+                confirmation.spent_addresses.insert(transaction.address().clone());
+            }
+        }
+
+        let diff = bundle.ledger_diff();
+
+        match check_bundle(state, &confirmation.diff, &diff) {
+            Ok(()) => {
+                for (address, value) in diff {
+                    *confirmation.diff.entry(address).or_insert(0) += value;
+                }
+
+                confirmation.bundles.push(hash);
+            }
+            Err(reason) => {
+                confirmation.conflicting_bundles.push((hash, reason));
+                confirmation.conflicting_transactions.extend(bundle_hashes);
+            }
+        }
+    }
+
+    Ok(confirmation)
+}
+
+/// Reverts the confirmation of the transactions confirmed by the milestone `hash` of index `index` and returns them.
+pub(crate) fn unconfirm(tangle: &MsTangle, hash: &Hash, index: MilestoneIndex) -> Vec<Hash> {
+    let mut transactions = Vec::new();
+    let mut hashes = vec![*hash];
+
+    while let Some(hash) = hashes.pop() {
+        if !tangle.get_metadata(&hash).map_or(false, |metadata| {
+            metadata.flags().is_confirmed() && metadata.milestone_index() == index
+        }) {
+            continue;
+        }
+
+        if let Some((trunk, branch)) = tangle.get_parents(&hash) {
+            hashes.push(trunk);
+            hashes.push(branch);
+        }
+
+        tangle.update_metadata(&hash, |metadata| metadata.unconfirm());
+        transactions.push(hash);
+    }
+
+    transactions
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use super::*;
+
+    use bee_protocol::tangle::TransactionMetadata;
+    use bee_test::{
+        field::rand_trits_field,
+        transaction::{clone_tx, create_random_attached_tx},
+    };
+    use bee_transaction::bundled::{
+        BundledTransaction as Transaction, BundledTransactionBuilder as TransactionBuilder, Index, Value,
+    };
+
+    use async_std::task::block_on;
+
+    pub(crate) fn create_value_tx(
+        bundle: Hash,
+        address: Address,
+        value: i64,
+        index: usize,
+        last_index: usize,
+        trunk: Hash,
+        branch: Hash,
+    ) -> (Hash, Transaction) {
+        let (_, template) = create_random_attached_tx(branch, trunk);
+        let transaction = TransactionBuilder::new()
+            .with_payload(template.payload().clone())
+            .with_address(address)
+            .with_value(Value::from_inner_unchecked(value))
+            .with_obsolete_tag(template.obsolete_tag().clone())
+            .with_timestamp(template.timestamp().clone())
+            .with_index(Index::from_inner_unchecked(index))
+            .with_last_index(Index::from_inner_unchecked(last_index))
+            .with_tag(template.tag().clone())
+            .with_attachment_ts(template.attachment_ts().clone())
+            .with_bundle(bundle)
+            .with_trunk(trunk)
+            .with_branch(branch)
+            .with_attachment_lbts(template.attachment_lbts().clone())
+            .with_attachment_ubts(template.attachment_ubts().clone())
+            .with_nonce(template.nonce().clone())
+            .build()
+            .unwrap();
+
+        (rand_trits_field::<Hash>(), transaction)
+    }
+
+    #[test]
+    fn confirm_value_bundle() {
+        let tangle = MsTangle::new();
+
+        let sep1 = rand_trits_field::<Hash>();
+        let sep2 = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep1, MilestoneIndex(0));
+        tangle.add_solid_entry_point(sep2, MilestoneIndex(0));
+
+        let bundle = rand_trits_field::<Hash>();
+        let input = rand_trits_field::<Address>();
+        let output = rand_trits_field::<Address>();
+        let mut state = HashMap::new();
+        state.insert(input.clone(), 10);
+
+        let (head_hash, head) = create_value_tx(bundle, output.clone(), 10, 1, 1, sep1, sep2);
+        let (tail_hash, tail) = create_value_tx(bundle, input.clone(), -10, 0, 1, head_hash, sep2);
+        let (ms_hash, ms) = create_random_attached_tx(sep1, tail_hash);
+
+        tangle.insert(head, head_hash, TransactionMetadata::new());
+        tangle.insert(tail, tail_hash, TransactionMetadata::new());
+        tangle.insert(clone_tx(&ms), ms_hash, TransactionMetadata::new());
+
+        let confirmation = block_on(confirm(&tangle, &state, &ms_hash)).unwrap();
+
+        assert_eq!(confirmation.transactions, vec![head_hash, tail_hash, ms_hash]);
+        assert_eq!(confirmation.bundles, vec![tail_hash]);
+        assert!(confirmation.conflicting_bundles.is_empty());
+        assert!(confirmation.conflicting_transactions.is_empty());
+        assert_eq!(confirmation.diff.len(), 2);
+        assert_eq!(*confirmation.diff.get(&input).unwrap(), -10);
+        assert_eq!(*confirmation.diff.get(&output).unwrap(), 10);
+        assert_eq!(confirmation.spent_addresses.len(), 1);
+        assert!(confirmation.spent_addresses.contains(&input));
+    }
+
+    #[test]
+    fn confirm_double_spend() {
+        let tangle = MsTangle::new();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let input = rand_trits_field::<Address>();
+        let mut state = HashMap::new();
+        state.insert(input.clone(), 10);
+
+        let bundle1 = rand_trits_field::<Hash>();
+        let (head1_hash, head1) = create_value_tx(bundle1, rand_trits_field::<Address>(), 10, 1, 1, sep, sep);
+        let (tail1_hash, tail1) = create_value_tx(bundle1, input.clone(), -10, 0, 1, head1_hash, sep);
+
+        let bundle2 = rand_trits_field::<Hash>();
+        let (head2_hash, head2) = create_value_tx(bundle2, rand_trits_field::<Address>(), 10, 1, 1, sep, sep);
+        let (tail2_hash, tail2) = create_value_tx(bundle2, input.clone(), -10, 0, 1, head2_hash, sep);
+
+        let (ms_hash, ms) = create_random_attached_tx(tail2_hash, tail1_hash);
+
+        tangle.insert(head1, head1_hash, TransactionMetadata::new());
+        tangle.insert(tail1, tail1_hash, TransactionMetadata::new());
+        tangle.insert(head2, head2_hash, TransactionMetadata::new());
+        tangle.insert(tail2, tail2_hash, TransactionMetadata::new());
+        tangle.insert(ms, ms_hash, TransactionMetadata::new());
+
+        let confirmation = block_on(confirm(&tangle, &state, &ms_hash)).unwrap();
+
+        assert_eq!(
+            confirmation.transactions,
+            vec![head1_hash, tail1_hash, head2_hash, tail2_hash, ms_hash]
+        );
+        assert_eq!(confirmation.bundles, vec![tail1_hash]);
+        assert_eq!(
+            confirmation.conflicting_bundles,
+            vec![(
+                tail2_hash,
+                ConflictReason::InsufficientBalance {
+                    address: input.clone(),
+                    balance: 0,
+                    delta: -10
+                }
+            )]
+        );
+        assert_eq!(confirmation.conflicting_transactions, vec![tail2_hash, head2_hash]);
+        assert_eq!(*confirmation.diff.get(&input).unwrap(), -10);
+    }
+
+    #[test]
+    fn confirm_invalid_value_sum() {
+        let tangle = MsTangle::new();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let (tail_hash, tail) = create_value_tx(
+            rand_trits_field::<Hash>(),
+            rand_trits_field::<Address>(),
+            10,
+            0,
+            0,
+            sep,
+            sep,
+        );
+
+        tangle.insert(tail, tail_hash, TransactionMetadata::new());
+
+        let confirmation = block_on(confirm(&tangle, &HashMap::new(), &tail_hash)).unwrap();
+
+        assert!(confirmation.bundles.is_empty());
+        assert_eq!(
+            confirmation.conflicting_bundles,
+            vec![(tail_hash, ConflictReason::InvalidValueSum(10))]
+        );
+        assert_eq!(confirmation.conflicting_transactions, vec![tail_hash]);
+        assert!(confirmation.diff.is_empty());
+    }
+
+    #[test]
+    fn confirm_incomplete_bundle() {
+        let tangle = MsTangle::new();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let input = rand_trits_field::<Address>();
+        let mut state = HashMap::new();
+        state.insert(input.clone(), 10);
+
+        // The trunk of the tail belongs to another bundle, the head is missing.
+        let (other_hash, other) = create_random_attached_tx(sep, sep);
+        let (tail_hash, tail) = create_value_tx(rand_trits_field::<Hash>(), input, -10, 0, 1, other_hash, sep);
+
+        tangle.insert(other, other_hash, TransactionMetadata::new());
+        tangle.insert(tail, tail_hash, TransactionMetadata::new());
+
+        let confirmation = block_on(confirm(&tangle, &state, &tail_hash)).unwrap();
+
+        assert_eq!(confirmation.transactions, vec![other_hash, tail_hash]);
+        assert!(confirmation.bundles.is_empty());
+        assert_eq!(
+            confirmation.conflicting_bundles,
+            vec![(tail_hash, ConflictReason::IncompleteBundle)]
+        );
+        assert_eq!(confirmation.conflicting_transactions, vec![tail_hash]);
+        assert!(confirmation.diff.is_empty());
+        assert!(confirmation.spent_addresses.is_empty());
+    }
+
+    #[test]
+    fn confirm_skips_confirmed() {
+        let tangle = MsTangle::new();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let (a_hash, a) = create_random_attached_tx(sep, sep);
+        let (b_hash, b) = create_random_attached_tx(sep, a_hash);
+        let (c_hash, c) = create_random_attached_tx(a_hash, b_hash);
+
+        tangle.insert(a, a_hash, TransactionMetadata::new());
+        tangle.insert(b, b_hash, TransactionMetadata::new());
+        tangle.insert(c, c_hash, TransactionMetadata::new());
+        tangle.update_metadata(&a_hash, |metadata| metadata.confirm(MilestoneIndex(1)));
+
+        let confirmation = block_on(confirm(&tangle, &HashMap::new(), &c_hash)).unwrap();
+
+        assert_eq!(confirmation.transactions, vec![b_hash, c_hash]);
+        assert!(confirmation.bundles.is_empty());
+        assert!(confirmation.diff.is_empty());
+    }
+
+    #[test]
+    fn confirm_missing_transaction() {
+        let tangle = MsTangle::new();
+
+        let (a_hash, a) = create_random_attached_tx(rand_trits_field::<Hash>(), rand_trits_field::<Hash>());
+
+        tangle.insert(a, a_hash, TransactionMetadata::new());
+
+        assert!(block_on(confirm(&tangle, &HashMap::new(), &a_hash)).is_err());
+    }
+
+    #[test]
+    fn unconfirm_milestone() {
+        let tangle = MsTangle::new();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let (a_hash, a) = create_random_attached_tx(sep, sep);
+        let (b_hash, b) = create_random_attached_tx(sep, a_hash);
+        let (c_hash, c) = create_random_attached_tx(a_hash, b_hash);
+
+        tangle.insert(a, a_hash, TransactionMetadata::new());
+        tangle.insert(b, b_hash, TransactionMetadata::new());
+        tangle.insert(c, c_hash, TransactionMetadata::new());
+        tangle.update_metadata(&a_hash, |metadata| metadata.confirm(MilestoneIndex(1)));
+        tangle.update_metadata(&b_hash, |metadata| metadata.confirm(MilestoneIndex(2)));
+        tangle.update_metadata(&c_hash, |metadata| metadata.confirm(MilestoneIndex(2)));
+
+        let transactions = unconfirm(&tangle, &c_hash, MilestoneIndex(2));
+
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions.contains(&b_hash));
+        assert!(transactions.contains(&c_hash));
+        assert!(tangle.get_metadata(&a_hash).unwrap().flags().is_confirmed());
+        assert!(!tangle.get_metadata(&b_hash).unwrap().flags().is_confirmed());
+        assert!(!tangle.get_metadata(&c_hash).unwrap().flags().is_confirmed());
+    }
+}
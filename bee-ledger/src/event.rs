@@ -0,0 +1,45 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::confirmation::ConflictReason;
+
+use bee_crypto::ternary::Hash;
+use bee_protocol::MilestoneIndex;
+
+/// Dispatched when the ledger has been updated with the cone of a milestone.
+pub struct MilestoneConfirmed {
+    /// Index of the confirmed milestone.
+    pub index: MilestoneIndex,
+    /// Hash of the confirmed milestone.
+    pub hash: Hash,
+    /// Number of transactions newly confirmed by the milestone.
+    pub confirmed_transactions: usize,
+    /// Tail hashes of the value bundles applied to the ledger, in confirmation order.
+    pub applied_bundles: Vec<Hash>,
+    /// Tail hashes of the value bundles excluded from the ledger and the reason why, in confirmation order.
+    pub conflicting_bundles: Vec<(Hash, ConflictReason)>,
+}
+
+/// Dispatched when transactions get confirmed by a milestone.
+pub struct TransactionsConfirmed {
+    /// Index of the confirming milestone.
+    pub index: MilestoneIndex,
+    /// Hashes of the confirmed transactions.
+    pub transactions: Vec<Hash>,
+}
+
+/// Dispatched when transactions are no longer confirmed, their milestone having been rolled back.
+pub struct TransactionsUnconfirmed {
+    /// Index of the rolled back milestone.
+    pub index: MilestoneIndex,
+    /// Hashes of the unconfirmed transactions.
+    pub transactions: Vec<Hash>,
+}
@@ -9,8 +9,14 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
+pub mod event;
+
+mod config;
+mod confirmation;
 mod merkle;
 mod worker;
 
-pub(crate) use merkle::Merkle;
-pub use worker::{LedgerWorker, LedgerWorkerEvent};
+pub use config::{LedgerConfig, LedgerConfigBuilder};
+pub use confirmation::ConflictReason;
+pub use merkle::{Merkle, MerkleProof, MerkleRoots, MerkleSide};
+pub use worker::{LedgerRestoreError, LedgerSnapshotEntry, LedgerWorker, LedgerWorkerEvent};
@@ -11,7 +11,7 @@
 
 use bee_crypto::ternary::Hash;
 use bee_ternary::T5B1Buf;
-use bee_transaction::bundled::BundledTransactionField;
+use bee_transaction::bundled::{Address, BundledTransactionField};
 
 use std::marker::PhantomData;
 
@@ -21,13 +21,48 @@ use digest::Digest;
 const LEAF_PREFIX: u8 = 0x00;
 const NODE_PREFIX: u8 = 0x01;
 
+/// Merkle roots of the ledger after the confirmation of a milestone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleRoots {
+    /// Root of the transactions confirmed by the milestone, in confirmation order.
+    pub transactions: Vec<u8>,
+    /// Root of the non-zero balances, sorted by address.
+    pub balances: Vec<u8>,
+}
+
+/// Side of a sibling in a Merkle audit path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleSide {
+    Left,
+    Right,
+}
+
+/// Audit path proving the inclusion of a transaction hash in a Merkle tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    // Siblings from the leaf up to the root.
+    path: Vec<(MerkleSide, Vec<u8>)>,
+}
+
+impl MerkleProof {
+    /// Returns the siblings of the audit path, from the leaf up to the root.
+    pub fn path(&self) -> &[(MerkleSide, Vec<u8>)] {
+        &self.path
+    }
+}
+
+/// RFC 6962 Merkle tree over transaction hashes.
 #[derive(Default)]
-pub(crate) struct Merkle<H: Default + Digest> {
+pub struct Merkle<H: Default + Digest> {
     hasher: PhantomData<H>,
 }
 
+fn hash_bytes(hash: &Hash) -> Vec<u8> {
+    cast_slice(hash.to_inner().encode::<T5B1Buf>().as_i8_slice()).to_vec()
+}
+
 impl<H: Default + Digest> Merkle<H> {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self::default()
     }
 
@@ -35,32 +70,112 @@ impl<H: Default + Digest> Merkle<H> {
         (&H::digest(b"")).to_vec()
     }
 
-    fn leaf(&mut self, hash: Hash) -> Vec<u8> {
+    fn leaf(&mut self, leaf: &[u8]) -> Vec<u8> {
         let mut hasher = H::default();
 
         hasher.update([LEAF_PREFIX]);
-        hasher.update(cast_slice(hash.to_inner().encode::<T5B1Buf>().as_i8_slice()));
+        hasher.update(leaf);
         (&hasher.finalize_reset()).to_vec()
     }
 
-    fn node(&mut self, hashes: &[Hash]) -> Vec<u8> {
+    fn combine(&mut self, left: &[u8], right: &[u8]) -> Vec<u8> {
         let mut hasher = H::default();
-        let n = hashes.len() as u32 - 1;
-        let k = 1 << (32 - n.leading_zeros() - 1);
 
         hasher.update([NODE_PREFIX]);
-        hasher.update(self.hash(&hashes[0..k]));
-        hasher.update(self.hash(&hashes[k..]));
+        hasher.update(left);
+        hasher.update(right);
         (&hasher.finalize_reset()).to_vec()
     }
 
-    pub(crate) fn hash(&mut self, hashes: &[Hash]) -> Vec<u8> {
-        match hashes.len() {
+    // Largest power of two strictly smaller than `n`.
+    fn split(n: usize) -> usize {
+        let n = n as u32 - 1;
+
+        1 << (32 - n.leading_zeros() - 1)
+    }
+
+    fn node(&mut self, leaves: &[Vec<u8>]) -> Vec<u8> {
+        let k = Self::split(leaves.len());
+        let left = self.root(&leaves[0..k]);
+        let right = self.root(&leaves[k..]);
+
+        self.combine(&left, &right)
+    }
+
+    fn root(&mut self, leaves: &[Vec<u8>]) -> Vec<u8> {
+        match leaves.len() {
             0 => self.empty(),
-            1 => self.leaf(hashes[0]),
-            _ => self.node(hashes),
+            1 => self.leaf(&leaves[0]),
+            _ => self.node(leaves),
+        }
+    }
+
+    fn audit_path(&mut self, leaves: &[Vec<u8>], index: usize, path: &mut Vec<(MerkleSide, Vec<u8>)>) {
+        if leaves.len() < 2 {
+            return;
+        }
+
+        let k = Self::split(leaves.len());
+
+        if index < k {
+            self.audit_path(&leaves[0..k], index, path);
+            path.push((MerkleSide::Right, self.root(&leaves[k..])));
+        } else {
+            self.audit_path(&leaves[k..], index - k, path);
+            path.push((MerkleSide::Left, self.root(&leaves[0..k])));
         }
     }
+
+    /// Computes the root of the tree whose leaves are `hashes`.
+    pub fn hash(&mut self, hashes: &[Hash]) -> Vec<u8> {
+        let leaves = hashes.iter().map(hash_bytes).collect::<Vec<Vec<u8>>>();
+
+        self.root(&leaves)
+    }
+
+    /// Builds the audit path of `hash` in the tree whose leaves are `hashes`, if `hash` is one of them.
+    pub fn proof(&mut self, hashes: &[Hash], hash: &Hash) -> Option<MerkleProof> {
+        let index = hashes.iter().position(|h| h == hash)?;
+        let leaves = hashes.iter().map(hash_bytes).collect::<Vec<Vec<u8>>>();
+        let mut path = Vec::new();
+
+        self.audit_path(&leaves, index, &mut path);
+
+        Some(MerkleProof { path })
+    }
+
+    /// Checks that `proof` is a valid audit path of `hash` in the tree of root `root`.
+    pub fn verify(&mut self, root: &[u8], hash: &Hash, proof: &MerkleProof) -> bool {
+        let mut node = self.leaf(&hash_bytes(hash));
+
+        for (side, sibling) in proof.path.iter() {
+            node = match side {
+                MerkleSide::Left => self.combine(sibling, &node),
+                MerkleSide::Right => self.combine(&node, sibling),
+            };
+        }
+
+        node == root
+    }
+
+    // Leaves are the T5B1 encoded addresses followed by their little-endian balances. The balances are expected to be
+    // non-zero and sorted by encoded address, which the ledger maintains incrementally rather than for every milestone.
+    pub(crate) fn hash_balances<'a, I: IntoIterator<Item = (&'a [u8], u64)>>(&mut self, balances: I) -> Vec<u8> {
+        let leaves = balances
+            .into_iter()
+            .map(|(address, balance)| {
+                let mut leaf = address.to_vec();
+                leaf.extend_from_slice(&balance.to_le_bytes());
+                leaf
+            })
+            .collect::<Vec<Vec<u8>>>();
+
+        self.root(&leaves)
+    }
+}
+
+pub(crate) fn address_bytes(address: &Address) -> Vec<u8> {
+    cast_slice(address.to_inner().encode::<T5B1Buf>().as_i8_slice()).to_vec()
 }
 
 #[cfg(test)]
@@ -69,6 +184,7 @@ mod tests {
     use super::*;
 
     use bee_ternary::{T1B1Buf, TryteBuf};
+    use bee_test::field::rand_trits_field;
 
     use blake2::Blake2b;
 
@@ -105,6 +221,69 @@ mod tests {
         )
     }
 
+    #[test]
+    fn balances_leaf() {
+        let address = rand_trits_field::<Address>();
+        let bytes = address_bytes(&address);
+        let root = Merkle::<Blake2b>::new().hash_balances(vec![(bytes.as_slice(), 42)]);
+
+        let mut hasher = Blake2b::default();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(&bytes);
+        hasher.update(42u64.to_le_bytes());
+
+        assert_eq!(bytes.len(), 49);
+        assert_eq!(root, hasher.finalize().to_vec());
+    }
+
+    #[test]
+    fn balances_order() {
+        let mut balances = (0..10)
+            .map(|_| {
+                (
+                    address_bytes(&rand_trits_field::<Address>()),
+                    rand::random::<u32>() as u64 + 1,
+                )
+            })
+            .collect::<Vec<(Vec<u8>, u64)>>();
+
+        balances.sort();
+        let root = Merkle::<Blake2b>::new().hash_balances(balances.iter().map(|(a, b)| (a.as_slice(), *b)));
+
+        assert_ne!(
+            root,
+            Merkle::<Blake2b>::new().hash_balances(balances.iter().rev().map(|(a, b)| (a.as_slice(), *b)))
+        );
+        assert_ne!(
+            root,
+            Merkle::<Blake2b>::new().hash_balances(balances.iter().skip(1).map(|(a, b)| (a.as_slice(), *b)))
+        );
+    }
+
+    #[test]
+    fn proofs() {
+        for n in 1..20 {
+            let hashes = (0..n).map(|_| rand_trits_field::<Hash>()).collect::<Vec<Hash>>();
+            let root = Merkle::<Blake2b>::new().hash(&hashes);
+
+            for hash in hashes.iter() {
+                let proof = Merkle::<Blake2b>::new().proof(&hashes, hash).unwrap();
+
+                assert!(Merkle::<Blake2b>::new().verify(&root, hash, &proof));
+                assert!(!Merkle::<Blake2b>::new().verify(&root, &rand_trits_field::<Hash>(), &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn proof_not_found() {
+        let hashes = (0..10).map(|_| rand_trits_field::<Hash>()).collect::<Vec<Hash>>();
+
+        assert!(Merkle::<Blake2b>::new()
+            .proof(&hashes, &rand_trits_field::<Hash>())
+            .is_none());
+    }
+
     #[test]
     fn tree() {
         let mut hashes = Vec::new();
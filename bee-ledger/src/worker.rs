@@ -9,44 +9,417 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use bee_transaction::bundled::Address;
+use crate::{
+    confirmation::{confirm, unconfirm},
+    event::{MilestoneConfirmed, TransactionsConfirmed, TransactionsUnconfirmed},
+    merkle::address_bytes,
+    Merkle, MerkleRoots,
+};
+
+use bee_common::event::Bus;
+use bee_crypto::ternary::Hash;
+use bee_protocol::{tangle::tangle, Milestone, MilestoneIndex};
+use bee_storage::{StateDeltaMap, StorageBackend};
+use bee_transaction::bundled::{Address, IOTA_SUPPLY};
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
 
+use blake2::Blake2b;
 use futures::{
     channel::{mpsc, oneshot},
     future::FutureExt,
     select,
-    stream::StreamExt,
+    stream::{Stream, StreamExt},
 };
-use log::{info, warn};
+use log::{error, info, warn};
+
+// Number of most recent milestones whose Merkle roots are kept.
+const MERKLE_ROOTS_DEPTH: u32 = 1000;
+// Number of snapshot balances or spent addresses stored at once when bootstrapping the ledger.
+const BOOTSTRAP_CHUNK_SIZE: usize = 100_000;
+
+/// Ledger part of a snapshot, which the ledger is bootstrapped from.
+pub enum LedgerSnapshotEntry {
+    Balance(Address, u64),
+    SpentAddress(Address),
+}
+
+#[derive(Debug)]
+pub enum LedgerRestoreError<S, E> {
+    Storage(S),
+    Snapshot(E),
+    DuplicateAddress(Address),
+}
 
 pub enum LedgerWorkerEvent {
+    Confirm(Milestone),
     ApplyDiff(HashMap<Address, i64>),
     GetBalance(Address, oneshot::Sender<Option<u64>>),
+    GetBalances(Vec<Address>, oneshot::Sender<Vec<Option<u64>>>),
+    // Page index, page size; addresses are sorted by their T5B1 encoding.
+    GetNonZeroBalances(usize, usize, oneshot::Sender<Vec<(Address, u64)>>),
+    // Err carries the actual supply when it doesn't match IOTA_SUPPLY.
+    CheckSupply(oneshot::Sender<Result<(), u64>>),
+    GetIndex(oneshot::Sender<MilestoneIndex>),
+    GetMerkleRoots(MilestoneIndex, oneshot::Sender<Option<MerkleRoots>>),
+    /// Answers with the balances as they were right after the confirmation of the given milestone, if its diffs are
+    /// still stored.
+    GetBalancesAt(MilestoneIndex, oneshot::Sender<Option<HashMap<Address, u64>>>),
+    /// Reverts the diffs of the last N confirmed milestones and answers with the resulting ledger index.
+    Rollback(u32, oneshot::Sender<MilestoneIndex>),
+    /// Answers, for each address, whether funds have ever been spent from it, in which case it must not be reused.
+    WereAddressesSpentFrom(Vec<Address>, oneshot::Sender<Vec<bool>>),
+    GetSpentAddresses(oneshot::Sender<HashSet<Address>>),
+    /// Forgets the Merkle roots of the milestones below the given index, once pruned by a local snapshot.
+    Prune(MilestoneIndex),
 }
 
-pub struct LedgerWorker {
+pub struct LedgerWorker<S: StorageBackend> {
+    index: MilestoneIndex,
     state: HashMap<Address, u64>,
+    // Addresses with a non-zero balance, sorted by their T5B1 encoding.
+    sorted_addresses: BTreeMap<Vec<u8>, Address>,
+    spent_addresses: HashSet<Address>,
+    merkle_roots: HashMap<MilestoneIndex, MerkleRoots>,
+    storage: Arc<S>,
+    bus: Arc<Bus<'static>>,
 }
 
-impl LedgerWorker {
-    pub fn new(state: HashMap<Address, u64>) -> Self {
-        Self { state }
+impl<S: StorageBackend + Send + Sync> LedgerWorker<S> {
+    pub fn new(
+        index: MilestoneIndex,
+        state: HashMap<Address, u64>,
+        spent_addresses: HashSet<Address>,
+        storage: Arc<S>,
+        bus: Arc<Bus<'static>>,
+    ) -> Self {
+        let sorted_addresses = state
+            .iter()
+            .filter(|(_, balance)| **balance != 0)
+            .map(|(address, _)| (address_bytes(address), address.clone()))
+            .collect();
+
+        Self {
+            index,
+            state,
+            sorted_addresses,
+            spent_addresses,
+            merkle_roots: HashMap::new(),
+            storage,
+            bus,
+        }
     }
 
-    fn apply_diff(&mut self, diff: HashMap<Address, i64>) {
-        for (key, value) in diff {
-            self.state
-                .entry(key)
-                .and_modify(|balance| {
-                    if *balance as i64 + value >= 0 {
-                        *balance = (*balance as i64 + value) as u64;
-                    } else {
-                        warn!("Ignoring conflicting diff.");
+    /// Resumes the ledger from storage or, if it is missing or older than the snapshot, bootstraps it from the
+    /// snapshot entries, which are stored in chunks as they are read.
+    pub async fn restore<E, B>(
+        snapshot_index: MilestoneIndex,
+        mut snapshot: B,
+        storage: Arc<S>,
+        bus: Arc<Bus<'static>>,
+    ) -> Result<Self, LedgerRestoreError<S::StorageError, E>>
+    where
+        B: Stream<Item = Result<LedgerSnapshotEntry, E>> + Unpin,
+    {
+        match storage.load_balances().await.map_err(LedgerRestoreError::Storage)? {
+            Some((index, state)) if index >= snapshot_index => {
+                info!("Resuming ledger at milestone {}.", *index);
+                // The snapshot is still read to the end so that it gets verified.
+                while let Some(entry) = snapshot.next().await {
+                    entry.map_err(LedgerRestoreError::Snapshot)?;
+                }
+                let spent_addresses = storage
+                    .load_spent_addresses()
+                    .await
+                    .map_err(LedgerRestoreError::Storage)?;
+                Ok(Self::new(index, state, spent_addresses, storage, bus))
+            }
+            _ => {
+                info!("Bootstrapping ledger from snapshot at milestone {}.", *snapshot_index);
+                let state = Self::bootstrap(snapshot_index, snapshot, &*storage).await?;
+                // Addresses spent after the snapshot, by milestones confirmed before restarting, are kept as well.
+                let spent_addresses = storage
+                    .load_spent_addresses()
+                    .await
+                    .map_err(LedgerRestoreError::Storage)?;
+                Ok(Self::new(snapshot_index, state, spent_addresses, storage, bus))
+            }
+        }
+    }
+
+    // The ledger index is stored last, so that a bootstrap interrupted by an error or a crash starts over.
+    async fn bootstrap<E, B>(
+        snapshot_index: MilestoneIndex,
+        mut snapshot: B,
+        storage: &S,
+    ) -> Result<HashMap<Address, u64>, LedgerRestoreError<S::StorageError, E>>
+    where
+        B: Stream<Item = Result<LedgerSnapshotEntry, E>> + Unpin,
+    {
+        let mut state = HashMap::new();
+        let mut balances = HashMap::new();
+        let mut spent_addresses = HashSet::new();
+
+        storage.clear_balances().await.map_err(LedgerRestoreError::Storage)?;
+
+        while let Some(entry) = snapshot.next().await {
+            match entry.map_err(LedgerRestoreError::Snapshot)? {
+                LedgerSnapshotEntry::Balance(address, balance) => {
+                    if state.insert(address.clone(), balance).is_some() {
+                        return Err(LedgerRestoreError::DuplicateAddress(address));
                     }
-                })
-                .or_insert(value as u64);
+                    balances.insert(address, balance);
+                    if balances.len() == BOOTSTRAP_CHUNK_SIZE {
+                        storage
+                            .append_balances(std::mem::take(&mut balances))
+                            .await
+                            .map_err(LedgerRestoreError::Storage)?;
+                        info!("Stored {} balances.", state.len());
+                    }
+                }
+                LedgerSnapshotEntry::SpentAddress(address) => {
+                    spent_addresses.insert(address);
+                    if spent_addresses.len() == BOOTSTRAP_CHUNK_SIZE {
+                        storage
+                            .insert_spent_addresses(std::mem::take(&mut spent_addresses))
+                            .await
+                            .map_err(LedgerRestoreError::Storage)?;
+                    }
+                }
+            }
+        }
+
+        storage
+            .append_balances(balances)
+            .await
+            .map_err(LedgerRestoreError::Storage)?;
+        storage
+            .insert_spent_addresses(spent_addresses)
+            .await
+            .map_err(LedgerRestoreError::Storage)?;
+        storage
+            .insert_ledger_index(snapshot_index)
+            .await
+            .map_err(LedgerRestoreError::Storage)?;
+        info!("Stored {} balances.", state.len());
+
+        Ok(state)
+    }
+
+    async fn confirm(&mut self, milestone: Milestone) {
+        // Milestones are confirmed in order, including the ones whose solidification event may have been missed.
+        while self.index < milestone.index() {
+            let index = self.index + MilestoneIndex(1);
+            let hash = if index == milestone.index() {
+                *milestone.hash()
+            } else {
+                match tangle().get_milestone_hash(index) {
+                    Some(hash) => hash,
+                    None => {
+                        warn!("Milestone {} is missing, can't confirm.", *index);
+                        return;
+                    }
+                }
+            };
+
+            match confirm(tangle(), &self.state, &hash).await {
+                Ok(confirmation) => {
+                    // Spent addresses are persisted before the delta, since wrongly considering an address spent is
+                    // harmless while the opposite is not.
+                    if let Err(e) = self
+                        .storage
+                        .insert_spent_addresses(confirmation.spent_addresses.clone())
+                        .await
+                    {
+                        error!("Storing spent addresses of milestone {} failed: {:?}.", *index, e);
+                        return;
+                    }
+
+                    // The diff is validated by applying it in memory before it is persisted, and reverted if that
+                    // fails, so that the in-memory and stored ledgers never diverge.
+                    if let Err(address) = self.apply_diff(confirmation.diff.clone()) {
+                        error!(
+                            "Diff of milestone {} overspends address {:?}, can't confirm.",
+                            *index, address
+                        );
+                        return;
+                    }
+
+                    if let Err(e) = self
+                        .storage
+                        .insert_state_delta(
+                            StateDeltaMap {
+                                address_to_delta: confirmation.diff.clone(),
+                            },
+                            index,
+                        )
+                        .await
+                    {
+                        error!("Storing state delta of milestone {} failed: {:?}.", *index, e);
+                        self.revert_diff(confirmation.diff);
+                        return;
+                    }
+
+                    for hash in confirmation.transactions.iter() {
+                        tangle().update_metadata(hash, |metadata| metadata.confirm(index));
+                    }
+
+                    self.bus.dispatch(TransactionsConfirmed {
+                        index,
+                        transactions: confirmation.transactions.clone(),
+                    });
+
+                    for hash in confirmation.conflicting_transactions.iter() {
+                        tangle().update_metadata(hash, |metadata| metadata.flags_mut().set_conflicting());
+                    }
+
+                    for (hash, reason) in confirmation.conflicting_bundles.iter() {
+                        warn!(
+                            "Excluded conflicting bundle {:?} of milestone {}: {:?}.",
+                            hash, *index, reason
+                        );
+                    }
+
+                    self.spent_addresses.extend(confirmation.spent_addresses);
+                    self.index = index;
+                    self.insert_merkle_roots(index, &confirmation.transactions);
+
+                    info!(
+                        "Confirmed milestone {}: {} transaction(s), {} value bundle(s) applied, {} conflicting.",
+                        *index,
+                        confirmation.transactions.len(),
+                        confirmation.bundles.len(),
+                        confirmation.conflicting_bundles.len()
+                    );
+
+                    self.bus.dispatch(MilestoneConfirmed {
+                        index,
+                        hash,
+                        confirmed_transactions: confirmation.transactions.len(),
+                        applied_bundles: confirmation.bundles,
+                        conflicting_bundles: confirmation.conflicting_bundles,
+                    });
+                }
+                Err(e) => {
+                    error!("Confirmation of milestone {} failed: {:?}.", *index, e);
+                    return;
+                }
+            }
+        }
+    }
+
+    // Stops at the first milestone that can't be reverted, e.g. the snapshot one whose diff is not stored.
+    async fn rollback(&mut self, count: u32, sender: oneshot::Sender<MilestoneIndex>) {
+        for _ in 0..count {
+            let index = self.index;
+
+            // As for confirmations, the diff is reverted in memory first and restored if storage can't follow.
+            let diff = match self.storage.load_state_delta(index).await {
+                Ok(state_delta) => state_delta.address_to_delta,
+                Err(e) => {
+                    error!("Loading state delta of milestone {} failed: {:?}.", *index, e);
+                    break;
+                }
+            };
+
+            if let Err(address) = self.apply_diff(negate(&diff)) {
+                error!(
+                    "Reverting diff of milestone {} overspends address {:?}, ledger is corrupted.",
+                    *index, address
+                );
+                break;
+            }
+
+            if let Err(e) = self.storage.revert_state_delta().await {
+                error!("Reverting state delta of milestone {} failed: {:?}.", *index, e);
+                if self.apply_diff(diff).is_err() {
+                    error!("Restoring diff of milestone {} failed, ledger is corrupted.", *index);
+                }
+                break;
+            }
+
+            match tangle().get_milestone_hash(index) {
+                Some(hash) => {
+                    self.bus.dispatch(TransactionsUnconfirmed {
+                        index,
+                        transactions: unconfirm(tangle(), &hash, index),
+                    });
+                }
+                None => warn!("Milestone {} is missing, its transactions stay confirmed.", *index),
+            }
+
+            // Spent addresses are kept since the signatures of the reverted bundles have been published anyway.
+            self.index = MilestoneIndex(*index - 1);
+            self.merkle_roots.remove(&index);
+
+            info!("Rolled back milestone {}.", *index);
+        }
+
+        // The rolled back milestones have to be solidified again so that they are confirmed anew, in order, rather
+        // than all at once by the next solid milestone.
+        if tangle().get_solid_milestone_index() > self.index {
+            tangle().update_solid_milestone_index(self.index);
+        }
+
+        if let Err(e) = sender.send(self.index) {
+            warn!("Failed to send index: {:?}.", e);
+        }
+    }
+
+    // The diff is either entirely applied or not at all; the first address it would make negative, or overflow, is
+    // returned.
+    fn apply_diff(&mut self, diff: HashMap<Address, i64>) -> Result<(), Address> {
+        for (address, value) in diff.iter() {
+            if apply_delta(*self.state.get(address).unwrap_or(&0), *value as i128).is_none() {
+                return Err(address.clone());
+            }
+        }
+
+        for (address, value) in diff {
+            let bytes = address_bytes(&address);
+            let balance = self.state.entry(address.clone()).or_insert(0);
+            // Checked above.
+            *balance = apply_delta(*balance, value as i128).unwrap_or(*balance);
+
+            if *balance == 0 {
+                self.sorted_addresses.remove(&bytes);
+            } else {
+                self.sorted_addresses.insert(bytes, address);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert_merkle_roots(&mut self, index: MilestoneIndex, transactions: &[Hash]) {
+        let state = &self.state;
+        let balances = Merkle::<Blake2b>::new().hash_balances(
+            self.sorted_addresses
+                .iter()
+                .map(|(bytes, address)| (bytes.as_slice(), *state.get(address).unwrap_or(&0))),
+        );
+
+        self.merkle_roots.insert(
+            index,
+            MerkleRoots {
+                transactions: Merkle::<Blake2b>::new().hash(transactions),
+                balances,
+            },
+        );
+
+        if *index > MERKLE_ROOTS_DEPTH {
+            self.merkle_roots.remove(&MilestoneIndex(*index - MERKLE_ROOTS_DEPTH));
+        }
+    }
+
+    // Undoes a diff that has just been applied, which therefore can't overspend.
+    fn revert_diff(&mut self, diff: HashMap<Address, i64>) {
+        if self.apply_diff(negate(&diff)).is_err() {
+            error!("Reverting diff failed, ledger is corrupted.");
         }
     }
 
@@ -56,6 +429,125 @@ impl LedgerWorker {
         }
     }
 
+    fn get_balances(&self, addresses: Vec<Address>, sender: oneshot::Sender<Vec<Option<u64>>>) {
+        let balances = addresses
+            .iter()
+            .map(|address| self.state.get(address).cloned())
+            .collect();
+
+        if let Err(e) = sender.send(balances) {
+            warn!("Failed to send balances: {:?}.", e);
+        }
+    }
+
+    fn get_non_zero_balances(&self, page: usize, size: usize, sender: oneshot::Sender<Vec<(Address, u64)>>) {
+        let balances = self
+            .sorted_addresses
+            .values()
+            .skip(page.saturating_mul(size))
+            .take(size)
+            .map(|address| (address.clone(), *self.state.get(address).unwrap_or(&0)))
+            .collect();
+
+        if let Err(e) = sender.send(balances) {
+            warn!("Failed to send non-zero balances: {:?}.", e);
+        }
+    }
+
+    async fn get_balances_at(&self, index: MilestoneIndex, sender: oneshot::Sender<Option<HashMap<Address, u64>>>) {
+        let mut balances = if index <= self.index {
+            Some(self.state.clone())
+        } else {
+            None
+        };
+
+        // The diffs more recent than `index` are reverted from the current balances.
+        for diff_index in (*index + 1..=*self.index).rev() {
+            let state = match balances.as_mut() {
+                Some(state) => state,
+                None => break,
+            };
+
+            match self.storage.load_state_delta(MilestoneIndex(diff_index)).await {
+                Ok(state_delta) => {
+                    for (address, delta) in state_delta.address_to_delta {
+                        let balance = state.entry(address.clone()).or_insert(0);
+                        match apply_delta(*balance, -(delta as i128)) {
+                            Some(reverted) => *balance = reverted,
+                            None => {
+                                error!(
+                                    "Reverting state delta of milestone {} makes the balance of {:?} invalid, ledger \
+                                    is corrupted.",
+                                    diff_index, address
+                                );
+                                balances = None;
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Loading state delta of milestone {} failed: {:?}.", diff_index, e);
+                    balances = None;
+                }
+            }
+        }
+
+        if let Some(state) = balances.as_mut() {
+            state.retain(|_, balance| *balance != 0);
+        }
+
+        if let Err(e) = sender.send(balances) {
+            warn!("Failed to send balances: {:?}.", e);
+        }
+    }
+
+    fn were_addresses_spent_from(&self, addresses: Vec<Address>, sender: oneshot::Sender<Vec<bool>>) {
+        let spent = addresses
+            .iter()
+            .map(|address| self.spent_addresses.contains(address))
+            .collect();
+
+        if let Err(e) = sender.send(spent) {
+            warn!("Failed to send spent states: {:?}.", e);
+        }
+    }
+
+    fn get_spent_addresses(&self, sender: oneshot::Sender<HashSet<Address>>) {
+        if let Err(e) = sender.send(self.spent_addresses.clone()) {
+            warn!("Failed to send spent addresses: {:?}.", e);
+        }
+    }
+
+    fn check_supply(&self, sender: oneshot::Sender<Result<(), u64>>) {
+        let supply = self.state.values().sum::<u64>();
+        let result = if supply == IOTA_SUPPLY as u64 {
+            Ok(())
+        } else {
+            Err(supply)
+        };
+
+        if let Err(e) = sender.send(result) {
+            warn!("Failed to send supply check: {:?}.", e);
+        }
+    }
+
+    fn get_index(&self, sender: oneshot::Sender<MilestoneIndex>) {
+        if let Err(e) = sender.send(self.index) {
+            warn!("Failed to send index: {:?}.", e);
+        }
+    }
+
+    fn get_merkle_roots(&self, index: MilestoneIndex, sender: oneshot::Sender<Option<MerkleRoots>>) {
+        if let Err(e) = sender.send(self.merkle_roots.get(&index).cloned()) {
+            warn!("Failed to send Merkle roots: {:?}.", e);
+        }
+    }
+
+    fn prune(&mut self, index: MilestoneIndex) {
+        self.merkle_roots.retain(|milestone_index, _| *milestone_index >= index);
+    }
+
     pub async fn run(mut self, receiver: mpsc::Receiver<LedgerWorkerEvent>, shutdown: oneshot::Receiver<()>) {
         info!("Running.");
 
@@ -67,8 +559,29 @@ impl LedgerWorker {
                 event = receiver_fused.next() => {
                     if let Some(event) = event {
                         match event {
-                            LedgerWorkerEvent::ApplyDiff(diff) => self.apply_diff(diff),
-                            LedgerWorkerEvent::GetBalance(address, sender) => self.get_balance(address, sender)
+                            LedgerWorkerEvent::Confirm(milestone) => self.confirm(milestone).await,
+                            LedgerWorkerEvent::ApplyDiff(diff) => {
+                                if let Err(address) = self.apply_diff(diff) {
+                                    warn!("Ignoring diff overspending address {:?}.", address);
+                                }
+                            }
+                            LedgerWorkerEvent::GetBalance(address, sender) => self.get_balance(address, sender),
+                            LedgerWorkerEvent::GetBalances(addresses, sender) => self.get_balances(addresses, sender),
+                            LedgerWorkerEvent::GetNonZeroBalances(page, size, sender) => {
+                                self.get_non_zero_balances(page, size, sender)
+                            }
+                            LedgerWorkerEvent::CheckSupply(sender) => self.check_supply(sender),
+                            LedgerWorkerEvent::GetIndex(sender) => self.get_index(sender),
+                            LedgerWorkerEvent::GetMerkleRoots(index, sender) => self.get_merkle_roots(index, sender),
+                            LedgerWorkerEvent::GetBalancesAt(index, sender) => {
+                                self.get_balances_at(index, sender).await
+                            }
+                            LedgerWorkerEvent::Rollback(count, sender) => self.rollback(count, sender).await,
+                            LedgerWorkerEvent::WereAddressesSpentFrom(addresses, sender) => {
+                                self.were_addresses_spent_from(addresses, sender)
+                            }
+                            LedgerWorkerEvent::GetSpentAddresses(sender) => self.get_spent_addresses(sender),
+                            LedgerWorkerEvent::Prune(index) => self.prune(index),
                         }
                     }
                 },
@@ -82,16 +595,45 @@ impl LedgerWorker {
     }
 }
 
+// Returns the balance resulting from applying `delta` to `balance`, unless it is negative or overflows.
+fn apply_delta(balance: u64, delta: i128) -> Option<u64> {
+    u64::try_from(balance as i128 + delta).ok()
+}
+
+fn negate(diff: &HashMap<Address, i64>) -> HashMap<Address, i64> {
+    diff.iter().map(|(address, delta)| (address.clone(), -delta)).collect()
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
-    use bee_test::field::rand_trits_field;
+    use crate::confirmation::tests::create_value_tx;
+
+    use bee_protocol::tangle::{self, TransactionMetadata};
+    use bee_storage::MemoryBackendStorage;
+    use bee_ternary::T5B1Buf;
+    use bee_test::{field::rand_trits_field, transaction::create_random_attached_tx};
+    use bee_transaction::bundled::BundledTransactionField;
 
     use async_std::task::{block_on, spawn};
+    use bytemuck::cast_slice;
+    use digest::Digest;
     use futures::sink::SinkExt;
+    use futures::stream;
     use rand::Rng;
+    use serial_test::serial;
+
+    fn test_worker(index: MilestoneIndex, state: HashMap<Address, u64>) -> LedgerWorker<MemoryBackendStorage> {
+        LedgerWorker::new(
+            index,
+            state,
+            HashSet::new(),
+            Arc::new(MemoryBackendStorage::new()),
+            Arc::new(Bus::new()),
+        )
+    }
 
     #[test]
     fn get_balances() {
@@ -104,7 +646,7 @@ mod tests {
             state.insert(rand_trits_field::<Address>(), rng.gen_range(0, 100_000_000));
         }
 
-        spawn(LedgerWorker::new(state.clone()).run(rx, shutdown_rx));
+        spawn(test_worker(MilestoneIndex(0), state.clone()).run(rx, shutdown_rx));
 
         for (address, balance) in state {
             let (get_balance_tx, get_balance_rx) = oneshot::channel();
@@ -125,7 +667,7 @@ mod tests {
             state.insert(rand_trits_field::<Address>(), rng.gen_range(0, 100_000_000));
         }
 
-        spawn(LedgerWorker::new(state).run(rx, shutdown_rx));
+        spawn(test_worker(MilestoneIndex(0), state).run(rx, shutdown_rx));
 
         for _ in 0..100 {
             let (get_balance_tx, get_balance_rx) = oneshot::channel();
@@ -152,7 +694,7 @@ mod tests {
 
         block_on(tx.send(LedgerWorkerEvent::ApplyDiff(diff.clone()))).unwrap();
 
-        spawn(LedgerWorker::new(HashMap::new()).run(rx, shutdown_rx));
+        spawn(test_worker(MilestoneIndex(0), HashMap::new()).run(rx, shutdown_rx));
 
         for (address, balance) in diff {
             let (get_balance_tx, get_balance_rx) = oneshot::channel();
@@ -162,5 +704,567 @@ mod tests {
         }
     }
 
-    // TODO test LedgerWorkerEvent::ApplyDiff
+    #[test]
+    fn get_balances_batch() {
+        let mut rng = rand::thread_rng();
+        let mut state = HashMap::new();
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        for _ in 0..100 {
+            state.insert(rand_trits_field::<Address>(), rng.gen_range(0, 100_000_000));
+        }
+
+        spawn(test_worker(MilestoneIndex(0), state.clone()).run(rx, shutdown_rx));
+
+        let mut addresses = state.keys().cloned().collect::<Vec<Address>>();
+        addresses.push(rand_trits_field::<Address>());
+
+        let (get_balances_tx, get_balances_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalances(addresses.clone(), get_balances_tx))).unwrap();
+        let balances = block_on(get_balances_rx).unwrap();
+
+        assert_eq!(balances.len(), addresses.len());
+        for (address, balance) in addresses.iter().zip(balances.iter()) {
+            assert_eq!(state.get(address), balance.as_ref());
+        }
+    }
+
+    #[test]
+    fn get_non_zero_balances_paginated() {
+        let mut rng = rand::thread_rng();
+        let mut state = HashMap::new();
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        for _ in 0..95 {
+            state.insert(rand_trits_field::<Address>(), rng.gen_range(1, 100_000_000));
+        }
+        for _ in 0..5 {
+            state.insert(rand_trits_field::<Address>(), 0);
+        }
+
+        spawn(test_worker(MilestoneIndex(0), state.clone()).run(rx, shutdown_rx));
+
+        let mut balances = Vec::new();
+
+        for page in 0..10 {
+            let (get_balances_tx, get_balances_rx) = oneshot::channel();
+            block_on(tx.send(LedgerWorkerEvent::GetNonZeroBalances(page, 10, get_balances_tx))).unwrap();
+            let mut balances_page = block_on(get_balances_rx).unwrap();
+            assert!(balances_page.len() <= 10);
+            balances.append(&mut balances_page);
+        }
+
+        assert_eq!(balances.len(), 95);
+        assert!(balances
+            .windows(2)
+            .all(|pair| address_bytes(&pair[0].0) < address_bytes(&pair[1].0)));
+        for (address, balance) in balances {
+            assert_ne!(balance, 0);
+            assert_eq!(*state.get(&address).unwrap(), balance);
+            state.remove(&address);
+        }
+    }
+
+    #[test]
+    fn get_non_zero_balances_after_diff() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let from = rand_trits_field::<Address>();
+        let to = rand_trits_field::<Address>();
+        let mut state = HashMap::new();
+        state.insert(from.clone(), 10);
+
+        spawn(test_worker(MilestoneIndex(0), state).run(rx, shutdown_rx));
+
+        let mut diff = HashMap::new();
+        diff.insert(from, -10);
+        diff.insert(to.clone(), 10);
+        block_on(tx.send(LedgerWorkerEvent::ApplyDiff(diff))).unwrap();
+
+        let (get_balances_tx, get_balances_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetNonZeroBalances(0, 10, get_balances_tx))).unwrap();
+        assert_eq!(block_on(get_balances_rx).unwrap(), vec![(to, 10)]);
+    }
+
+    #[test]
+    fn check_supply() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let mut state = HashMap::new();
+        state.insert(rand_trits_field::<Address>(), IOTA_SUPPLY as u64 - 10);
+        state.insert(rand_trits_field::<Address>(), 10);
+
+        spawn(test_worker(MilestoneIndex(0), state).run(rx, shutdown_rx));
+
+        let (check_supply_tx, check_supply_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::CheckSupply(check_supply_tx))).unwrap();
+        assert!(block_on(check_supply_rx).unwrap().is_ok());
+
+        let mut diff = HashMap::new();
+        diff.insert(rand_trits_field::<Address>(), 5);
+        block_on(tx.send(LedgerWorkerEvent::ApplyDiff(diff))).unwrap();
+
+        let (check_supply_tx, check_supply_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::CheckSupply(check_supply_tx))).unwrap();
+        assert_eq!(block_on(check_supply_rx).unwrap(), Err(IOTA_SUPPLY as u64 + 5));
+    }
+
+    #[test]
+    fn get_index() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        spawn(test_worker(MilestoneIndex(42), HashMap::new()).run(rx, shutdown_rx));
+
+        let (get_index_tx, get_index_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetIndex(get_index_tx))).unwrap();
+        assert_eq!(block_on(get_index_rx).unwrap(), MilestoneIndex(42));
+    }
+
+    #[test]
+    fn get_merkle_roots_not_found() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        spawn(test_worker(MilestoneIndex(0), HashMap::new()).run(rx, shutdown_rx));
+
+        let (get_merkle_roots_tx, get_merkle_roots_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetMerkleRoots(
+            MilestoneIndex(1),
+            get_merkle_roots_tx,
+        )))
+        .unwrap();
+        assert!(block_on(get_merkle_roots_rx).unwrap().is_none());
+    }
+
+    #[test]
+    fn prune_merkle_roots() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let mut worker = test_worker(MilestoneIndex(3), HashMap::new());
+        for index in 1..=3 {
+            worker.insert_merkle_roots(MilestoneIndex(index), &[]);
+        }
+        spawn(worker.run(rx, shutdown_rx));
+
+        block_on(tx.send(LedgerWorkerEvent::Prune(MilestoneIndex(2)))).unwrap();
+
+        for (index, found) in [(1, false), (2, true), (3, true)].iter() {
+            let (get_merkle_roots_tx, get_merkle_roots_rx) = oneshot::channel();
+            block_on(tx.send(LedgerWorkerEvent::GetMerkleRoots(
+                MilestoneIndex(*index),
+                get_merkle_roots_tx,
+            )))
+            .unwrap();
+            assert_eq!(block_on(get_merkle_roots_rx).unwrap().is_some(), *found);
+        }
+    }
+
+    #[test]
+    fn apply_diff_overspending() {
+        let mut state = HashMap::new();
+        let mut diff = HashMap::new();
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let input = rand_trits_field::<Address>();
+        let output = rand_trits_field::<Address>();
+
+        state.insert(input.clone(), 10);
+        diff.insert(input.clone(), -20);
+        diff.insert(output.clone(), 20);
+
+        block_on(tx.send(LedgerWorkerEvent::ApplyDiff(diff))).unwrap();
+
+        spawn(test_worker(MilestoneIndex(0), state).run(rx, shutdown_rx));
+
+        let (get_balance_tx, get_balance_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalance(input, get_balance_tx))).unwrap();
+        assert_eq!(block_on(get_balance_rx).unwrap(), Some(10));
+
+        let (get_balance_tx, get_balance_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalance(output, get_balance_tx))).unwrap();
+        assert!(block_on(get_balance_rx).unwrap().is_none());
+    }
+
+    // The tangle is global, the tests using it run one at a time, each on a tangle of its own.
+    #[test]
+    #[serial]
+    fn rollback() {
+        tangle::init();
+
+        let (mut tx, rx) = mpsc::channel(100);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let from = rand_trits_field::<Address>();
+        let to = rand_trits_field::<Address>();
+        let storage = MemoryBackendStorage::new();
+        let mut state = HashMap::new();
+
+        state.insert(from.clone(), 10);
+        block_on(storage.insert_balances(state.clone(), MilestoneIndex(0))).unwrap();
+        for (index, value) in [(1, 4), (2, 6)].iter() {
+            let mut state_delta = StateDeltaMap {
+                address_to_delta: HashMap::new(),
+            };
+            state_delta.address_to_delta.insert(from.clone(), -value);
+            state_delta.address_to_delta.insert(to.clone(), *value);
+            block_on(storage.insert_state_delta(state_delta, MilestoneIndex(*index))).unwrap();
+        }
+        state.insert(from.clone(), 0);
+        state.insert(to.clone(), 10);
+
+        let mut metadata = TransactionMetadata::new();
+        metadata.confirm(MilestoneIndex(2));
+        let (a_hash, a) = create_random_attached_tx(rand_trits_field::<Hash>(), rand_trits_field::<Hash>());
+        let (ms_hash, ms) = create_random_attached_tx(a_hash, a_hash);
+        tangle().insert(a, a_hash, metadata);
+        tangle().insert(ms, ms_hash, metadata);
+        tangle().add_milestone(MilestoneIndex(2), ms_hash);
+        tangle().update_solid_milestone_index(MilestoneIndex(2));
+
+        let worker = spawn(
+            LedgerWorker::new(
+                MilestoneIndex(2),
+                state,
+                HashSet::new(),
+                Arc::new(storage),
+                Arc::new(Bus::new()),
+            )
+            .run(rx, shutdown_rx),
+        );
+
+        let (rollback_tx, rollback_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::Rollback(1, rollback_tx))).unwrap();
+        assert_eq!(block_on(rollback_rx).unwrap(), MilestoneIndex(1));
+        assert_eq!(tangle().get_solid_milestone_index(), MilestoneIndex(1));
+        for hash in [a_hash, ms_hash].iter() {
+            assert!(!tangle().get_metadata(hash).unwrap().flags().is_confirmed());
+        }
+
+        let (get_balance_tx, get_balance_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalance(from.clone(), get_balance_tx))).unwrap();
+        assert_eq!(block_on(get_balance_rx).unwrap(), Some(6));
+
+        // Rolling back stops at the snapshot, whose diff is not stored.
+        let (rollback_tx, rollback_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::Rollback(5, rollback_tx))).unwrap();
+        assert_eq!(block_on(rollback_rx).unwrap(), MilestoneIndex(0));
+        assert_eq!(tangle().get_solid_milestone_index(), MilestoneIndex(0));
+
+        let (get_balances_tx, get_balances_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalances(vec![from, to], get_balances_tx))).unwrap();
+        assert_eq!(block_on(get_balances_rx).unwrap(), vec![Some(10), Some(0)]);
+
+        shutdown_tx.send(()).unwrap();
+        block_on(worker);
+        tangle::drop();
+    }
+
+    #[test]
+    fn get_balances_at() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let from = rand_trits_field::<Address>();
+        let to = rand_trits_field::<Address>();
+        let storage = MemoryBackendStorage::new();
+        let mut state = HashMap::new();
+
+        state.insert(from.clone(), 10);
+        block_on(storage.insert_balances(state.clone(), MilestoneIndex(1))).unwrap();
+        state.clear();
+        let mut state_delta = StateDeltaMap {
+            address_to_delta: HashMap::new(),
+        };
+        state_delta.address_to_delta.insert(from.clone(), -10);
+        state_delta.address_to_delta.insert(to.clone(), 10);
+        block_on(storage.insert_state_delta(state_delta, MilestoneIndex(2))).unwrap();
+        state.insert(to.clone(), 10);
+
+        spawn(
+            LedgerWorker::new(
+                MilestoneIndex(2),
+                state.clone(),
+                HashSet::new(),
+                Arc::new(storage),
+                Arc::new(Bus::new()),
+            )
+            .run(rx, shutdown_rx),
+        );
+
+        let (get_balances_at_tx, get_balances_at_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalancesAt(MilestoneIndex(2), get_balances_at_tx))).unwrap();
+        assert_eq!(block_on(get_balances_at_rx).unwrap(), Some(state));
+
+        let (get_balances_at_tx, get_balances_at_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalancesAt(MilestoneIndex(1), get_balances_at_tx))).unwrap();
+        let balances = block_on(get_balances_at_rx).unwrap().unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances.get(&from), Some(&10));
+
+        // The diff of milestone 1 is not stored.
+        let (get_balances_at_tx, get_balances_at_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalancesAt(MilestoneIndex(0), get_balances_at_tx))).unwrap();
+        assert!(block_on(get_balances_at_rx).unwrap().is_none());
+    }
+
+    #[test]
+    fn get_balances_at_corrupted_delta() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let address = rand_trits_field::<Address>();
+        let storage = MemoryBackendStorage::new();
+        let mut state = HashMap::new();
+        state.insert(address.clone(), 5);
+
+        // Reverting it would make the balance negative.
+        let mut state_delta = StateDeltaMap {
+            address_to_delta: HashMap::new(),
+        };
+        state_delta.address_to_delta.insert(address, 10);
+        block_on(storage.insert_state_delta(state_delta, MilestoneIndex(2))).unwrap();
+
+        spawn(
+            LedgerWorker::new(
+                MilestoneIndex(2),
+                state,
+                HashSet::new(),
+                Arc::new(storage),
+                Arc::new(Bus::new()),
+            )
+            .run(rx, shutdown_rx),
+        );
+
+        let (get_balances_at_tx, get_balances_at_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetBalancesAt(MilestoneIndex(1), get_balances_at_tx))).unwrap();
+        assert!(block_on(get_balances_at_rx).unwrap().is_none());
+    }
+
+    #[test]
+    fn restore_spent_addresses() {
+        let (mut tx, rx) = mpsc::channel(100);
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let confirmed = rand_trits_field::<Address>();
+        let snapshot = rand_trits_field::<Address>();
+        let unspent = rand_trits_field::<Address>();
+        let storage = MemoryBackendStorage::new();
+
+        block_on(storage.insert_spent_addresses(vec![confirmed.clone()].into_iter().collect())).unwrap();
+
+        let worker = block_on(LedgerWorker::restore(
+            MilestoneIndex(0),
+            stream::iter(vec![Ok::<_, ()>(LedgerSnapshotEntry::SpentAddress(snapshot.clone()))]),
+            Arc::new(storage),
+            Arc::new(Bus::new()),
+        ))
+        .unwrap();
+        spawn(worker.run(rx, shutdown_rx));
+
+        let (spent_tx, spent_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::WereAddressesSpentFrom(
+            vec![confirmed, snapshot, unspent],
+            spent_tx,
+        )))
+        .unwrap();
+        assert_eq!(block_on(spent_rx).unwrap(), vec![true, true, false]);
+    }
+
+    #[test]
+    fn restore_bootstrap() {
+        let storage = Arc::new(MemoryBackendStorage::new());
+        let address = rand_trits_field::<Address>();
+        let spent = rand_trits_field::<Address>();
+
+        // An unreadable snapshot leaves no ledger behind.
+        let result = block_on(LedgerWorker::restore(
+            MilestoneIndex(1),
+            stream::iter(vec![Ok(LedgerSnapshotEntry::Balance(address.clone(), 10)), Err(())]),
+            storage.clone(),
+            Arc::new(Bus::new()),
+        ));
+        assert!(matches!(result, Err(LedgerRestoreError::Snapshot(()))));
+        assert!(block_on(storage.load_balances()).unwrap().is_none());
+
+        let result = block_on(LedgerWorker::restore(
+            MilestoneIndex(1),
+            stream::iter(vec![
+                Ok::<_, ()>(LedgerSnapshotEntry::Balance(address.clone(), 10)),
+                Ok(LedgerSnapshotEntry::Balance(address.clone(), 10)),
+            ]),
+            storage.clone(),
+            Arc::new(Bus::new()),
+        ));
+        assert!(matches!(result, Err(LedgerRestoreError::DuplicateAddress(_))));
+        assert!(block_on(storage.load_balances()).unwrap().is_none());
+
+        block_on(LedgerWorker::restore(
+            MilestoneIndex(1),
+            stream::iter(vec![
+                Ok::<_, ()>(LedgerSnapshotEntry::Balance(address.clone(), 10)),
+                Ok(LedgerSnapshotEntry::SpentAddress(spent.clone())),
+            ]),
+            storage.clone(),
+            Arc::new(Bus::new()),
+        ))
+        .unwrap();
+
+        let (index, balances) = block_on(storage.load_balances()).unwrap().unwrap();
+        assert_eq!(index, MilestoneIndex(1));
+        assert_eq!(balances.get(&address), Some(&10));
+        assert!(block_on(storage.load_spent_addresses()).unwrap().contains(&spent));
+    }
+
+    #[test]
+    #[serial]
+    fn confirm_flags_conflicting_bundle() {
+        tangle::init();
+
+        let (mut tx, rx) = mpsc::channel(100);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle().add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let input = rand_trits_field::<Address>();
+        let storage = MemoryBackendStorage::new();
+        let mut state = HashMap::new();
+        state.insert(input.clone(), 10);
+        block_on(storage.insert_balances(state.clone(), MilestoneIndex(0))).unwrap();
+
+        // Both bundles spend the whole balance of `input`, the second one confirmed is conflicting.
+        let bundle1 = rand_trits_field::<Hash>();
+        let (head1_hash, head1) = create_value_tx(bundle1, rand_trits_field::<Address>(), 10, 1, 1, sep, sep);
+        let (tail1_hash, tail1) = create_value_tx(bundle1, input.clone(), -10, 0, 1, head1_hash, sep);
+
+        let bundle2 = rand_trits_field::<Hash>();
+        let (head2_hash, head2) = create_value_tx(bundle2, rand_trits_field::<Address>(), 10, 1, 1, sep, sep);
+        let (tail2_hash, tail2) = create_value_tx(bundle2, input.clone(), -10, 0, 1, head2_hash, sep);
+
+        let (ms_hash, ms) = create_random_attached_tx(tail2_hash, tail1_hash);
+
+        tangle().insert(head1, head1_hash, TransactionMetadata::new());
+        tangle().insert(tail1, tail1_hash, TransactionMetadata::new());
+        tangle().insert(head2, head2_hash, TransactionMetadata::new());
+        tangle().insert(tail2, tail2_hash, TransactionMetadata::new());
+        tangle().insert(ms, ms_hash, TransactionMetadata::new());
+
+        let worker = spawn(
+            LedgerWorker::new(
+                MilestoneIndex(0),
+                state,
+                HashSet::new(),
+                Arc::new(storage),
+                Arc::new(Bus::new()),
+            )
+            .run(rx, shutdown_rx),
+        );
+
+        block_on(tx.send(LedgerWorkerEvent::Confirm(Milestone::new(ms_hash, MilestoneIndex(1))))).unwrap();
+
+        let (get_index_tx, get_index_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetIndex(get_index_tx))).unwrap();
+        assert_eq!(block_on(get_index_rx).unwrap(), MilestoneIndex(1));
+
+        for hash in [head2_hash, tail2_hash].iter() {
+            assert!(tangle().get_metadata(hash).unwrap().flags().is_conflicting());
+        }
+        for hash in [head1_hash, tail1_hash, ms_hash].iter() {
+            assert!(!tangle().get_metadata(hash).unwrap().flags().is_conflicting());
+        }
+
+        shutdown_tx.send(()).unwrap();
+        block_on(worker);
+        tangle::drop();
+    }
+
+    #[test]
+    #[serial]
+    fn confirm_merkle_roots() {
+        tangle::init();
+
+        fn leaf(bytes: &[u8]) -> Vec<u8> {
+            let mut hasher = Blake2b::default();
+            hasher.update([0x00]);
+            hasher.update(bytes);
+            hasher.finalize().to_vec()
+        }
+
+        fn node(left: &[u8], right: &[u8]) -> Vec<u8> {
+            let mut hasher = Blake2b::default();
+            hasher.update([0x01]);
+            hasher.update(left);
+            hasher.update(right);
+            hasher.finalize().to_vec()
+        }
+
+        fn hash_leaf(hash: &Hash) -> Vec<u8> {
+            leaf(cast_slice(hash.to_inner().encode::<T5B1Buf>().as_i8_slice()))
+        }
+
+        let (mut tx, rx) = mpsc::channel(100);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle().add_solid_entry_point(sep, MilestoneIndex(0));
+
+        let input = rand_trits_field::<Address>();
+        let output = rand_trits_field::<Address>();
+        let storage = MemoryBackendStorage::new();
+        let mut state = HashMap::new();
+        state.insert(input.clone(), 10);
+        block_on(storage.insert_balances(state.clone(), MilestoneIndex(0))).unwrap();
+
+        let bundle = rand_trits_field::<Hash>();
+        let (head_hash, head) = create_value_tx(bundle, output.clone(), 10, 1, 1, sep, sep);
+        let (tail_hash, tail) = create_value_tx(bundle, input, -10, 0, 1, head_hash, sep);
+        let (ms_hash, ms) = create_random_attached_tx(sep, tail_hash);
+
+        tangle().insert(head, head_hash, TransactionMetadata::new());
+        tangle().insert(tail, tail_hash, TransactionMetadata::new());
+        tangle().insert(ms, ms_hash, TransactionMetadata::new());
+
+        let worker = spawn(
+            LedgerWorker::new(
+                MilestoneIndex(0),
+                state,
+                HashSet::new(),
+                Arc::new(storage),
+                Arc::new(Bus::new()),
+            )
+            .run(rx, shutdown_rx),
+        );
+
+        block_on(tx.send(LedgerWorkerEvent::Confirm(Milestone::new(ms_hash, MilestoneIndex(1))))).unwrap();
+
+        let (get_merkle_roots_tx, get_merkle_roots_rx) = oneshot::channel();
+        block_on(tx.send(LedgerWorkerEvent::GetMerkleRoots(
+            MilestoneIndex(1),
+            get_merkle_roots_tx,
+        )))
+        .unwrap();
+        let roots = block_on(get_merkle_roots_rx).unwrap().unwrap();
+
+        // Transactions are confirmed head first, the emptied input is left out of the balances.
+        let transactions = node(
+            &node(&hash_leaf(&head_hash), &hash_leaf(&tail_hash)),
+            &hash_leaf(&ms_hash),
+        );
+        let mut balance = cast_slice::<i8, u8>(output.to_inner().encode::<T5B1Buf>().as_i8_slice()).to_vec();
+        balance.extend_from_slice(&10u64.to_le_bytes());
+
+        assert_eq!(roots.transactions, transactions);
+        assert_eq!(roots.balances, leaf(&balance));
+
+        shutdown_tx.send(()).unwrap();
+        block_on(worker);
+        tangle::drop();
+    }
 }
@@ -16,6 +16,8 @@ use bee_common::logger::LOGGER_STDOUT_NAME;
 use log::LevelFilter;
 use structopt::StructOpt;
 
+use std::mem;
+
 #[derive(Debug, StructOpt)]
 pub struct CliArgs {
     #[structopt(
@@ -24,6 +26,11 @@ pub struct CliArgs {
         help = "Stdout log level amongst \"trace\", \"debug\", \"info\", \"warn\" and \"error\""
     )]
     log_level: Option<LevelFilter>,
+    #[structopt(
+        long = "restore",
+        help = "Backup replacing the storage, once validated, before the node starts"
+    )]
+    restore: Option<String>,
 }
 
 impl CliArgs {
@@ -34,5 +41,8 @@ impl CliArgs {
     pub fn apply_to_config(self, config: &mut NodeConfigBuilder) {
         self.log_level
             .map(|log_level| config.logger.level(LOGGER_STDOUT_NAME, log_level));
+        if let Some(restore) = self.restore {
+            config.storage = mem::take(&mut config.storage).restore_path(restore);
+        }
     }
 }
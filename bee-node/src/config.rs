@@ -10,10 +10,12 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 use bee_common::logger::{LoggerConfig, LoggerConfigBuilder};
+use bee_ledger::{LedgerConfig, LedgerConfigBuilder};
 use bee_network::{NetworkConfig, NetworkConfigBuilder};
 use bee_peering::{PeeringConfig, PeeringConfigBuilder};
 use bee_protocol::{ProtocolConfig, ProtocolConfigBuilder};
 use bee_snapshot::{SnapshotConfig, SnapshotConfigBuilder};
+use bee_storage::{StorageConfig, StorageConfigBuilder};
 
 use serde::Deserialize;
 use thiserror::Error;
@@ -32,10 +34,12 @@ pub enum Error {
 #[derive(Default, Deserialize)]
 pub struct NodeConfigBuilder {
     pub(crate) logger: LoggerConfigBuilder,
+    pub(crate) ledger: LedgerConfigBuilder,
     pub(crate) network: NetworkConfigBuilder,
     pub(crate) peering: PeeringConfigBuilder,
     pub(crate) protocol: ProtocolConfigBuilder,
     pub(crate) snapshot: SnapshotConfigBuilder,
+    pub(crate) storage: StorageConfigBuilder,
 }
 
 impl NodeConfigBuilder {
@@ -50,10 +54,12 @@ impl NodeConfigBuilder {
     pub fn finish(self) -> NodeConfig {
         NodeConfig {
             logger: self.logger.finish(),
+            ledger: self.ledger.finish(),
             network: self.network.finish(),
             peering: self.peering.finish(),
             protocol: self.protocol.finish(),
             snapshot: self.snapshot.finish(),
+            storage: self.storage.finish(),
         }
     }
 }
@@ -61,8 +67,10 @@ impl NodeConfigBuilder {
 #[derive(Clone)]
 pub struct NodeConfig {
     pub logger: LoggerConfig,
+    pub ledger: LedgerConfig,
     pub network: NetworkConfig,
     pub peering: PeeringConfig,
     pub protocol: ProtocolConfig,
     pub snapshot: SnapshotConfig,
+    pub storage: StorageConfig,
 }
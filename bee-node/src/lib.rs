@@ -12,7 +12,9 @@
 mod cli;
 mod config;
 mod constants;
+mod loader;
 mod node;
+mod pruner;
 
 pub use cli::CliArgs;
 pub use config::NodeConfigBuilder;
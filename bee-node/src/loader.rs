@@ -0,0 +1,127 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use bee_crypto::ternary::Hash;
+use bee_protocol::{
+    tangle::{MsTangle, TransactionLoader, TransactionMetadata},
+    MilestoneIndex,
+};
+use bee_storage::StorageBackend;
+use bee_transaction::bundled::BundledTransaction as Transaction;
+
+use async_trait::async_trait;
+
+use std::{collections::HashSet, sync::Arc};
+
+/// Reloads the transactions evicted from a bounded tangle from storage.
+pub(crate) struct StorageTransactionLoader<S> {
+    storage: Arc<S>,
+}
+
+impl<S: StorageBackend> StorageTransactionLoader<S> {
+    pub(crate) fn new(storage: Arc<S>) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl<S: StorageBackend + Send + Sync + 'static> TransactionLoader for StorageTransactionLoader<S> {
+    async fn load(&self, hash: &Hash) -> Option<Transaction> {
+        self.storage.find_transaction(*hash).await.ok()
+    }
+}
+
+/// Outcome of populating the tangle from storage.
+#[derive(Debug, Default)]
+pub(crate) struct TangleLoadReport {
+    /// Number of transactions added to the tangle.
+    pub(crate) transactions: usize,
+    /// Number of milestones added to the tangle.
+    pub(crate) milestones: usize,
+    /// Stored transactions that could not be read back.
+    pub(crate) unreadable_transactions: HashSet<Hash>,
+    /// Parents of stored transactions that are neither stored nor solid entry points.
+    pub(crate) missing_parents: HashSet<Hash>,
+    /// Indices of stored milestones whose transaction is not stored.
+    pub(crate) missing_milestones: Vec<MilestoneIndex>,
+}
+
+/// Populates `tangle` with the transactions and milestones of `storage` and checks their referential integrity.
+///
+/// Solid entry points are expected to have been added to the tangle beforehand, as they are the only parents allowed
+/// to be missing from storage.
+pub(crate) async fn load_tangle<S: StorageBackend>(
+    storage: &S,
+    tangle: &MsTangle,
+) -> Result<TangleLoadReport, S::StorageError> {
+    let mut report = TangleLoadReport::default();
+
+    // Every stored transaction approves its trunk and branch, so it appears as an approver at least once.
+    let hashes = storage
+        .map_existing_transaction_hashes_to_approvers()
+        .await?
+        .values()
+        .flatten()
+        .copied()
+        .collect::<HashSet<Hash>>();
+
+    for hash in hashes.iter() {
+        match storage.find_transaction(*hash).await {
+            Ok(transaction) => {
+                let mut metadata = TransactionMetadata::new();
+                if transaction.is_tail() {
+                    metadata.flags_mut().set_tail();
+                }
+                // Children, tips and solid flags are kept consistent by the insertion, whatever the order.
+                if tangle.insert(transaction, *hash, metadata).is_some() {
+                    tangle.set_persisted(hash);
+                    report.transactions += 1;
+                }
+            }
+            Err(_) => {
+                report.unreadable_transactions.insert(*hash);
+            }
+        }
+    }
+
+    for milestone in storage.load_milestones().await? {
+        if tangle.contains(milestone.hash()) {
+            tangle.add_milestone(milestone.index(), *milestone.hash());
+            if milestone.index() > tangle.get_last_milestone_index() {
+                tangle.update_last_milestone_index(milestone.index());
+            }
+            report.milestones += 1;
+        } else {
+            report.missing_milestones.push(milestone.index());
+        }
+    }
+    report.missing_milestones.sort();
+
+    // Resumes the solid milestone index from the snapshot one, as far as the loaded milestones are solid.
+    let mut solid_index = tangle.get_solid_milestone_index();
+    while let Some(hash) = tangle.get_milestone_hash(solid_index + MilestoneIndex(1)) {
+        if !tangle.is_solid_transaction(&hash) {
+            break;
+        }
+        solid_index = solid_index + MilestoneIndex(1);
+    }
+    tangle.update_solid_milestone_index(solid_index);
+
+    report.missing_parents = storage
+        .map_missing_transaction_hashes_to_approvers(hashes)
+        .await?
+        .into_iter()
+        .map(|(parent, _)| parent)
+        .filter(|parent| !tangle.is_solid_entry_point(parent))
+        .collect();
+
+    Ok(report)
+}
@@ -14,15 +14,29 @@
 use crate::{
     config::NodeConfig,
     constants::{BEE_GIT_COMMIT, BEE_VERSION},
+    loader::{load_tangle, StorageTransactionLoader},
+    pruner::StoragePruner,
 };
 
-use bee_common::shutdown::Shutdown;
+use bee_common::{event::Bus, shutdown::Shutdown};
 use bee_crypto::ternary::Hash;
-use bee_ledger::{LedgerWorker, LedgerWorkerEvent};
+use bee_ledger::{
+    event::{MilestoneConfirmed, TransactionsConfirmed, TransactionsUnconfirmed},
+    LedgerRestoreError, LedgerSnapshotEntry, LedgerWorker, LedgerWorkerEvent,
+};
 use bee_network::{self, Address, Command::Connect, EndpointId, Event, EventSubscriber, Network, Origin};
 use bee_peering::{PeerManager, StaticPeerManager};
-use bee_protocol::{tangle, MilestoneIndex, Protocol};
-use bee_snapshot::{LocalSnapshot, SnapshotReadError};
+use bee_protocol::{
+    event::{LatestSolidMilestoneChanged, NewMilestone, NewTransaction, TransactionsSolidified},
+    tangle, MilestoneIndex, Protocol,
+};
+use bee_snapshot::{
+    GlobalSnapshot, GlobalSnapshotReadError, LoadType, LocalSnapshotMetadata, LocalSnapshotStream, LocalSnapshotWorker,
+    LocalSnapshotWorkerEvent, SnapshotReadError,
+};
+use bee_storage::{
+    BackupWorker, RocksDbBackendError, RocksDbBackendStorage, RocksDbBatchWriter, StorageBackend, StorageWorkerEvent,
+};
 
 use async_std::task::{block_on, spawn};
 use chrono::{offset::TimeZone, Utc};
@@ -30,13 +44,16 @@ use futures::{
     channel::{mpsc, oneshot},
     select,
     sink::SinkExt,
-    stream::{Fuse, StreamExt},
+    stream::{self, Fuse, StreamExt},
     FutureExt,
 };
 use log::{debug, error, info, warn};
 use thiserror::Error;
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
 
 /// All possible node errors.
 #[derive(Error, Debug)]
@@ -45,6 +62,18 @@ pub enum Error {
     #[error("Reading the snapshot file failed.")]
     SnapshotReadError(SnapshotReadError),
 
+    /// Occurs, when there is an error while reading the global snapshot files.
+    #[error("Reading the global snapshot files failed.")]
+    GlobalSnapshotReadError(GlobalSnapshotReadError),
+
+    /// Occurs, when an address appears twice among the snapshot balances.
+    #[error("Duplicate address in the snapshot balances.")]
+    DuplicateSnapshotAddress,
+
+    /// Occurs, when there is an error while accessing the storage.
+    #[error("Accessing the storage failed.")]
+    StorageError(RocksDbBackendError),
+
     /// Occurs, when there is an error while shutting down the node.
     #[error("Shutting down failed.")]
     ShutdownError(#[from] bee_common::shutdown::Error),
@@ -61,6 +90,7 @@ impl NodeBuilder {
         print_banner_and_version();
 
         let mut shutdown = Shutdown::new();
+        let bus = Arc::new(Bus::new());
 
         info!("Initializing network...");
         let (network, events) = bee_network::init(self.config.network, &mut shutdown);
@@ -68,60 +98,341 @@ impl NodeBuilder {
         info!("Initializing tangle...");
         tangle::init();
 
-        info!("Starting static peer manager...");
-        spawn(StaticPeerManager::new(self.config.peering.r#static.clone(), network.clone()).run());
-
         info!("Reading snapshot file...");
-        let snapshot_state = match block_on(LocalSnapshot::from_file(self.config.snapshot.local().file_path())) {
-            Ok(local_snapshot) => {
+        // Only the metadata is read here, the balances and spent addresses are streamed into the ledger.
+        let (snapshot_metadata, snapshot_ledger, balances_num, spent_addresses_num) =
+            match self.config.snapshot.load_type() {
+                LoadType::Local => match block_on(read_local_snapshot_metadata(
+                    self.config.snapshot.local().file_path(),
+                    self.config.snapshot.local().sha256(),
+                )) {
+                    Ok((metadata, stream)) => {
+                        let balances_num = stream.header().balances_num() as usize;
+                        let spent_addresses_num = stream.header().spent_addresses_num() as usize;
+                        (
+                            metadata,
+                            stream.into_ledger_stream().boxed_local(),
+                            balances_num,
+                            spent_addresses_num,
+                        )
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to read snapshot file \"{}\": {}.",
+                            self.config.snapshot.local().file_path(),
+                            e
+                        );
+                        return Err(Error::SnapshotReadError(e));
+                    }
+                },
+                LoadType::Global => match block_on(GlobalSnapshot::from_config(self.config.snapshot.global())) {
+                    Ok(global_snapshot) => {
+                        let (metadata, state) = global_snapshot.into_parts();
+                        let (balances, spent_addresses) = state.into_parts();
+                        let (balances_num, spent_addresses_num) = (balances.len(), spent_addresses.len());
+                        let entries = balances
+                            .into_iter()
+                            .map(|(address, balance)| LedgerSnapshotEntry::Balance(address, balance))
+                            .chain(spent_addresses.into_iter().map(LedgerSnapshotEntry::SpentAddress))
+                            .map(Ok::<_, SnapshotReadError>);
+                        (
+                            metadata,
+                            stream::iter(entries).boxed_local(),
+                            balances_num,
+                            spent_addresses_num,
+                        )
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to read global snapshot file \"{}\": {}.",
+                            self.config.snapshot.global().file_path(),
+                            e
+                        );
+                        return Err(Error::GlobalSnapshotReadError(e));
+                    }
+                },
+            };
+
+        info!(
+            "Read snapshot file from {} with index {}, {} solid entry points, {} seen milestones, {} balances and {} \
+            spent addresses.",
+            Utc.timestamp(snapshot_metadata.timestamp() as i64, 0).to_rfc2822(),
+            snapshot_metadata.index(),
+            snapshot_metadata.solid_entry_points().len(),
+            snapshot_metadata.seen_milestones().len(),
+            balances_num,
+            spent_addresses_num
+        );
+
+        tangle::tangle().update_solid_milestone_index(snapshot_metadata.index().into());
+
+        // TODO get from database
+        tangle::tangle().update_last_milestone_index(snapshot_metadata.index().into());
+
+        tangle::tangle().update_snapshot_milestone_index(snapshot_metadata.index().into());
+
+        // TODO index 0 ?
+        tangle::tangle().add_solid_entry_point(Hash::zeros(), MilestoneIndex(0));
+        for (hash, index) in snapshot_metadata.solid_entry_points() {
+            tangle::tangle().add_solid_entry_point(*hash, MilestoneIndex(*index));
+        }
+
+        if let Some(restore_path) = self.config.storage.restore_path() {
+            info!("Restoring storage from backup \"{}\"...", restore_path);
+            match block_on(RocksDbBackendStorage::restore(restore_path, self.config.storage.path())) {
+                Ok(last_index) => info!("Restored storage with last milestone {}.", *last_index),
+                Err(e) => {
+                    error!("Failed to restore storage from backup \"{}\": {:?}.", restore_path, e);
+                    return Err(Error::StorageError(e));
+                }
+            }
+        }
+
+        info!("Initializing storage...");
+        // Only RocksDB is wired in: the sqlx backends have no batch writer and the node is not generic over storage.
+        let mut storage = RocksDbBackendStorage::new();
+        if let Err(e) = block_on(storage.establish_connection(self.config.storage.path())) {
+            error!(
+                "Failed to initialize storage \"{}\": {:?}.",
+                self.config.storage.path(),
+                e
+            );
+            return Err(Error::StorageError(e));
+        }
+        let storage = Arc::new(storage);
+
+        if let Some(capacity) = self.config.storage.cache_capacity() {
+            info!("Bounding tangle to {} transactions in memory...", capacity);
+            tangle::tangle().enable_cache(capacity, Box::new(StorageTransactionLoader::new(storage.clone())));
+        }
+
+        info!("Loading tangle from storage...");
+        match block_on(load_tangle(&*storage, tangle::tangle())) {
+            Ok(report) => {
                 info!(
-                    "Read snapshot file from {} with index {}, {} solid entry points, {} seen milestones and \
-                    {} balances.",
-                    Utc.timestamp(local_snapshot.metadata().timestamp() as i64, 0)
-                        .to_rfc2822(),
-                    local_snapshot.metadata().index(),
-                    local_snapshot.metadata().solid_entry_points().len(),
-                    local_snapshot.metadata().seen_milestones().len(),
-                    local_snapshot.state().balances().len()
+                    "Loaded {} transactions and {} milestones, solid milestone index is {}.",
+                    report.transactions,
+                    report.milestones,
+                    *tangle::tangle().get_solid_milestone_index()
                 );
+                if !report.unreadable_transactions.is_empty() {
+                    warn!(
+                        "{} stored transactions could not be read.",
+                        report.unreadable_transactions.len()
+                    );
+                }
+                if !report.missing_milestones.is_empty() {
+                    warn!(
+                        "{} stored milestones have no stored transaction.",
+                        report.missing_milestones.len()
+                    );
+                }
+                if !report.missing_parents.is_empty() {
+                    warn!(
+                        "{} parents of stored transactions are missing.",
+                        report.missing_parents.len()
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to load the tangle from storage: {:?}.", e);
+                return Err(Error::StorageError(e));
+            }
+        }
 
-                tangle::tangle().update_solid_milestone_index(local_snapshot.metadata().index().into());
+        info!("Starting static peer manager...");
+        spawn(StaticPeerManager::new(self.config.peering.r#static.clone(), network.clone()).run());
 
-                // TODO get from database
-                tangle::tangle().update_last_milestone_index(local_snapshot.metadata().index().into());
+        let (storage_worker_tx, storage_worker_rx) = mpsc::channel(self.config.storage.channel_size());
+        let (storage_worker_shutdown_tx, storage_worker_shutdown_rx) = oneshot::channel();
 
-                tangle::tangle().update_snapshot_milestone_index(local_snapshot.metadata().index().into());
+        info!("Starting storage worker...");
+        spawn(
+            // Persisted transactions become evictable from the tangle.
+            RocksDbBatchWriter::new(storage.clone(), self.config.storage.clone())
+                .on_flush(|hashes| {
+                    for hash in hashes {
+                        tangle::tangle().set_persisted(hash);
+                    }
+                })
+                .run(storage_worker_rx, storage_worker_shutdown_rx),
+        );
+
+        let storage_worker_tx_clone = storage_worker_tx.clone();
+        let storage_clone = storage.clone();
+        bus.add_listener(move |new_transaction: &NewTransaction| {
+            send_to_storage(
+                &storage_worker_tx_clone,
+                &storage_clone,
+                StorageWorkerEvent::Transaction(new_transaction.hash, new_transaction.transaction.clone()),
+            )
+        });
 
-                // TODO index 0 ?
-                tangle::tangle().add_solid_entry_point(Hash::zeros(), MilestoneIndex(0));
-                for (hash, index) in local_snapshot.metadata().solid_entry_points() {
-                    tangle::tangle().add_solid_entry_point(*hash, MilestoneIndex(*index));
-                }
+        let storage_worker_tx_clone = storage_worker_tx.clone();
+        let storage_clone = storage.clone();
+        bus.add_listener(move |new_milestone: &NewMilestone| {
+            send_to_storage(
+                &storage_worker_tx_clone,
+                &storage_clone,
+                StorageWorkerEvent::Milestone(new_milestone.0.clone()),
+            )
+        });
 
-                for _seen_milestone in local_snapshot.metadata().seen_milestones() {
-                    // TODO request ?
-                }
+        let storage_worker_tx_clone = storage_worker_tx.clone();
+        let storage_clone = storage.clone();
+        bus.add_listener(move |transactions_solidified: &TransactionsSolidified| {
+            send_to_storage(
+                &storage_worker_tx_clone,
+                &storage_clone,
+                StorageWorkerEvent::Solid(transactions_solidified.0.clone()),
+            )
+        });
+
+        let storage_worker_tx_clone = storage_worker_tx.clone();
+        let storage_clone = storage.clone();
+        bus.add_listener(move |transactions_confirmed: &TransactionsConfirmed| {
+            send_to_storage(
+                &storage_worker_tx_clone,
+                &storage_clone,
+                StorageWorkerEvent::SnapshotIndex(
+                    transactions_confirmed.transactions.clone(),
+                    transactions_confirmed.index,
+                ),
+            )
+        });
 
-                local_snapshot.into_state()
+        let storage_worker_tx_clone = storage_worker_tx.clone();
+        let storage_clone = storage.clone();
+        bus.add_listener(move |transactions_unconfirmed: &TransactionsUnconfirmed| {
+            send_to_storage(
+                &storage_worker_tx_clone,
+                &storage_clone,
+                StorageWorkerEvent::SnapshotIndex(transactions_unconfirmed.transactions.clone(), MilestoneIndex(0)),
+            )
+        });
+
+        let backup_shutdown_tx = self.config.storage.backup_path().map(|backup_path| {
+            let (backup_worker_shutdown_tx, backup_worker_shutdown_rx) = oneshot::channel();
+
+            info!("Starting backup worker...");
+            spawn(
+                BackupWorker::new(
+                    storage.clone(),
+                    backup_path.to_string(),
+                    self.config.storage.backup_interval(),
+                    self.config.storage.backup_count(),
+                )
+                .run(backup_worker_shutdown_rx),
+            );
+
+            backup_worker_shutdown_tx
+        });
+
+        info!("Starting ledger...");
+        let ledger_worker = match block_on(LedgerWorker::restore(
+            MilestoneIndex(snapshot_metadata.index()),
+            snapshot_ledger,
+            storage.clone(),
+            bus.clone(),
+        )) {
+            Ok(ledger_worker) => ledger_worker,
+            Err(LedgerRestoreError::Storage(e)) => {
+                error!("Failed to restore the ledger: {:?}.", e);
+                return Err(Error::StorageError(e));
             }
-            Err(e) => {
+            Err(LedgerRestoreError::Snapshot(e)) => {
                 error!(
-                    "Failed to read snapshot file \"{}\": {:?}.",
+                    "Failed to read snapshot file \"{}\": {}.",
                     self.config.snapshot.local().file_path(),
                     e
                 );
                 return Err(Error::SnapshotReadError(e));
             }
+            Err(LedgerRestoreError::DuplicateAddress(address)) => {
+                error!(
+                    "Failed to restore the ledger: duplicate snapshot address {:?}.",
+                    address
+                );
+                return Err(Error::DuplicateSnapshotAddress);
+            }
         };
 
-        // TODO config
-        let (ledger_worker_tx, ledger_worker_rx) = mpsc::channel(1000);
+        let (ledger_worker_tx, ledger_worker_rx) = mpsc::channel(self.config.ledger.channel_size());
         let (ledger_worker_shutdown_tx, ledger_worker_shutdown_rx) = oneshot::channel();
 
-        info!("Starting ledger...");
-        spawn(LedgerWorker::new(snapshot_state.into_balances()).run(ledger_worker_rx, ledger_worker_shutdown_rx));
+        spawn(ledger_worker.run(ledger_worker_rx, ledger_worker_shutdown_rx));
+
+        let ledger_worker_tx_clone = ledger_worker_tx.clone();
+        bus.add_listener(move |latest_solid_milestone: &LatestSolidMilestoneChanged| {
+            if let Err(e) = ledger_worker_tx_clone
+                .clone()
+                .try_send(LedgerWorkerEvent::Confirm(latest_solid_milestone.0.clone()))
+            {
+                warn!(
+                    "Sending solid milestone {} to the ledger failed: {:?}.",
+                    *latest_solid_milestone.0.index(),
+                    e
+                );
+            }
+        });
+
+        let (local_snapshot_worker_tx, local_snapshot_worker_rx) =
+            mpsc::channel(self.config.snapshot.local().channel_size());
+        let (local_snapshot_worker_shutdown_tx, local_snapshot_worker_shutdown_rx) = oneshot::channel();
+
+        info!("Starting local snapshot worker...");
+        spawn(
+            LocalSnapshotWorker::new(
+                self.config.snapshot.local().clone(),
+                ledger_worker_tx.clone(),
+                Arc::new(StoragePruner(storage)),
+            )
+            .run(local_snapshot_worker_rx, local_snapshot_worker_shutdown_rx),
+        );
+
+        let local_snapshot_worker_tx_clone = local_snapshot_worker_tx.clone();
+        bus.add_listener(move |milestone_confirmed: &MilestoneConfirmed| {
+            if let Err(e) = local_snapshot_worker_tx_clone
+                .clone()
+                .try_send(LocalSnapshotWorkerEvent(milestone_confirmed.index))
+            {
+                warn!(
+                    "Sending confirmed milestone {} to the local snapshot worker failed: {:?}.",
+                    *milestone_confirmed.index, e
+                );
+            }
+        });
+
+        let seen_milestones = snapshot_metadata
+            .seen_milestones()
+            .iter()
+            .map(|(_, index)| MilestoneIndex(*index))
+            .filter(|index| *index > snapshot_metadata.index().into() && !tangle::tangle().contains_milestone(*index))
+            .collect::<HashSet<MilestoneIndex>>();
+        let num_seen_milestones = seen_milestones.len();
+
+        // Seen milestones are only counted once they actually arrive.
+        let pending_seen_milestones = Mutex::new(seen_milestones.clone());
+        bus.add_listener(move |new_milestone: &NewMilestone| {
+            let mut pending = pending_seen_milestones.lock().unwrap();
+            if pending.remove(&new_milestone.0.index()) {
+                if pending.is_empty() {
+                    info!("Received all {} seen milestones.", num_seen_milestones);
+                } else {
+                    debug!(
+                        "Received seen milestone {}, {} remaining.",
+                        *new_milestone.0.index(),
+                        pending.len()
+                    );
+                }
+            }
+        });
 
-        block_on(Protocol::init(self.config.protocol.clone(), network.clone()));
+        block_on(Protocol::init(self.config.protocol.clone(), network.clone(), bus));
+
+        for index in seen_milestones {
+            Protocol::request_milestone(index, None);
+        }
 
         info!("Initialized.");
 
@@ -130,7 +441,10 @@ impl NodeBuilder {
             network,
             events: events.fuse(),
             shutdown,
+            storage: (storage_worker_tx, storage_worker_shutdown_tx),
+            backup: backup_shutdown_tx,
             ledger: (ledger_worker_tx, ledger_worker_shutdown_tx),
+            local_snapshot: (local_snapshot_worker_tx, local_snapshot_worker_shutdown_tx),
             peers: HashMap::new(),
         })
     }
@@ -143,8 +457,11 @@ pub struct Node {
     network: Network,
     events: Fuse<EventSubscriber>,
     shutdown: Shutdown,
+    storage: (mpsc::Sender<StorageWorkerEvent>, oneshot::Sender<()>),
+    backup: Option<oneshot::Sender<()>>,
     // TODO design proper type `Ledger`
     ledger: (mpsc::Sender<LedgerWorkerEvent>, oneshot::Sender<()>),
+    local_snapshot: (mpsc::Sender<LocalSnapshotWorkerEvent>, oneshot::Sender<()>),
     // TODO design proper type `PeerList`
     peers: HashMap<EndpointId, (mpsc::Sender<Vec<u8>>, oneshot::Sender<()>)>,
 }
@@ -240,6 +557,67 @@ impl Node {
     }
 }
 
+// Writes that do not fit in the storage worker channel are spilled to the storage directly rather than lost.
+fn send_to_storage(
+    sender: &mpsc::Sender<StorageWorkerEvent>,
+    storage: &Arc<RocksDbBackendStorage>,
+    event: StorageWorkerEvent,
+) {
+    if let Err(e) = sender.clone().try_send(event) {
+        if !e.is_full() {
+            warn!("Sending to the storage worker failed: {:?}.", e);
+            return;
+        }
+
+        let storage = storage.clone();
+        let event = e.into_inner();
+        spawn(async move { store(&*storage, event).await });
+    }
+}
+
+async fn store(storage: &RocksDbBackendStorage, event: StorageWorkerEvent) {
+    match event {
+        StorageWorkerEvent::Transaction(hash, transaction) => {
+            match storage.insert_transaction(hash, transaction).await {
+                Ok(()) => tangle::tangle().set_persisted(&hash),
+                Err(e) => error!("Storing transaction failed: {:?}.", e),
+            }
+        }
+        StorageWorkerEvent::Milestone(milestone) => {
+            let index = milestone.index();
+            if let Err(e) = storage.insert_milestone(milestone).await {
+                error!("Storing milestone {} failed: {:?}.", *index, e);
+            }
+        }
+        StorageWorkerEvent::Solid(hashes) => {
+            if let Err(e) = storage
+                .update_transactions_set_solid(hashes.into_iter().collect())
+                .await
+            {
+                error!("Storing solid flags failed: {:?}.", e);
+            }
+        }
+        StorageWorkerEvent::SnapshotIndex(hashes, index) => {
+            if let Err(e) = storage
+                .update_transactions_set_snapshot_index(hashes.into_iter().collect(), index)
+                .await
+            {
+                error!("Storing snapshot index {} failed: {:?}.", *index, e);
+            }
+        }
+    }
+}
+
+async fn read_local_snapshot_metadata(
+    path: &str,
+    sha256: Option<&str>,
+) -> Result<(LocalSnapshotMetadata, LocalSnapshotStream), SnapshotReadError> {
+    let mut stream = LocalSnapshotStream::verify(path, sha256).await?;
+    let metadata = stream.read_metadata().await?;
+
+    Ok((metadata, stream))
+}
+
 // TODO return a Result
 fn shutdown_listener() -> oneshot::Receiver<()> {
     let (sender, receiver) = oneshot::channel();
@@ -0,0 +1,39 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use bee_crypto::ternary::Hash;
+use bee_protocol::MilestoneIndex;
+use bee_snapshot::LocalSnapshotPruner;
+use bee_storage::StorageBackend;
+
+use async_trait::async_trait;
+
+use std::{collections::HashSet, sync::Arc};
+
+// Prunes the storage below the local snapshots.
+pub(crate) struct StoragePruner<S: StorageBackend>(pub(crate) Arc<S>);
+
+#[async_trait]
+impl<S: StorageBackend + Send + Sync> LocalSnapshotPruner for StoragePruner<S> {
+    type Error = S::StorageError;
+
+    async fn prune(
+        &self,
+        transactions: &HashSet<Hash>,
+        index: MilestoneIndex,
+        solid_entry_points: &HashSet<Hash>,
+    ) -> Result<(), Self::Error> {
+        // `prune_below` only dates unconfirmed transactions against a stored milestone `index`, so the ones pruned from
+        // the tangle are deleted explicitly.
+        self.0.delete_transactions(transactions).await?;
+        self.0.prune_below(index, solid_entry_points).await
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::milestone::Milestone;
+
+use bee_crypto::ternary::Hash;
+use bee_transaction::bundled::BundledTransaction as Transaction;
+
+/// Dispatched when a new milestone becomes solid, i.e. its whole past cone is known.
+pub struct LatestSolidMilestoneChanged(pub Milestone);
+
+/// Dispatched when a transaction is added to the tangle for the first time.
+pub struct NewTransaction {
+    /// Hash of the transaction.
+    pub hash: Hash,
+    /// The transaction itself.
+    pub transaction: Transaction,
+}
+
+/// Dispatched when transactions become solid, i.e. their whole past cone is known.
+pub struct TransactionsSolidified(pub Vec<Hash>);
+
+/// Dispatched when a milestone has been validated and added to the tangle.
+pub struct NewMilestone(pub Milestone);
@@ -23,5 +23,5 @@ pub(crate) use compression::{compress_transaction_bytes, uncompress_transaction_
 pub(crate) use message::Message;
 pub(crate) use tlv::{tlv_from_bytes, tlv_into_bytes, Header, HEADER_SIZE};
 pub(crate) use v0::Handshake;
-pub(crate) use v2::{Heartbeat, MilestoneRequest, Transaction, TransactionRequest};
+pub(crate) use v2::{Heartbeat, MilestoneRequest, Transaction, TransactionRequest, LATEST_MILESTONE_INDEX};
 pub(crate) use version::{messages_supported_version, MESSAGES_VERSIONS};
@@ -18,6 +18,9 @@ use std::{convert::TryInto, ops::Range};
 const INDEX_SIZE: usize = 4;
 const CONSTANT_SIZE: usize = INDEX_SIZE;
 
+/// Index requesting the latest milestone of the peer instead of a specific one.
+pub(crate) const LATEST_MILESTONE_INDEX: u32 = 0;
+
 /// A message to request a milestone.
 #[derive(Default)]
 pub(crate) struct MilestoneRequest {
@@ -20,6 +20,6 @@ mod transaction_request;
 pub(crate) const MESSAGES_VERSION_2: u8 = 1 << 1;
 
 pub(crate) use heartbeat::Heartbeat;
-pub(crate) use milestone_request::MilestoneRequest;
+pub(crate) use milestone_request::{MilestoneRequest, LATEST_MILESTONE_INDEX};
 pub(crate) use transaction::Transaction;
 pub(crate) use transaction_request::TransactionRequest;
@@ -161,10 +161,7 @@ where
     P: PublicKey,
 {
     pub fn build(self) -> Milestone {
-        Milestone {
-            hash: self.hash,
-            index: self.index,
-        }
+        Milestone::new(self.hash, self.index)
     }
 }
 
@@ -10,7 +10,6 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 mod builder;
-mod milestone;
 
+pub use bee_tangle::{Milestone, MilestoneIndex};
 pub(crate) use builder::{MilestoneBuilder, MilestoneBuilderError};
-pub use milestone::{Milestone, MilestoneIndex};
@@ -10,7 +10,7 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 use crate::{
-    message::{Heartbeat, Transaction as TransactionMessage},
+    message::{Heartbeat, Transaction as TransactionMessage, LATEST_MILESTONE_INDEX},
     milestone::MilestoneIndex,
     protocol::Protocol,
     worker::{
@@ -36,7 +36,7 @@ impl Protocol {
     }
 
     pub fn request_last_milestone(to: Option<EndpointId>) {
-        Protocol::request_milestone(MilestoneIndex(0), to);
+        Protocol::request_milestone(MilestoneIndex(LATEST_MILESTONE_INDEX), to);
     }
 
     pub fn milestone_requester_is_empty() -> bool {
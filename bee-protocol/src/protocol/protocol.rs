@@ -24,7 +24,7 @@ use crate::{
     },
 };
 
-use bee_common::wait_priority_queue::WaitPriorityQueue;
+use bee_common::{event::Bus, wait_priority_queue::WaitPriorityQueue};
 use bee_crypto::ternary::{CurlP27, CurlP81, Hash, Kerl, SpongeType};
 use bee_network::{Address, EndpointId, Network, Origin};
 use bee_signing::ternary::WotsPublicKey;
@@ -44,6 +44,7 @@ static mut PROTOCOL: *const Protocol = ptr::null();
 pub struct Protocol {
     pub(crate) config: ProtocolConfig,
     pub(crate) network: Network,
+    pub(crate) bus: Arc<Bus<'static>>,
     pub(crate) metrics: ProtocolMetrics,
     pub(crate) transaction_worker: (mpsc::Sender<TransactionWorkerEvent>, Mutex<Option<oneshot::Sender<()>>>),
     pub(crate) transaction_responder_worker: (
@@ -82,7 +83,7 @@ pub struct Protocol {
 }
 
 impl Protocol {
-    pub async fn init(config: ProtocolConfig, network: Network) {
+    pub async fn init(config: ProtocolConfig, network: Network, bus: Arc<Bus<'static>>) {
         if unsafe { !PROTOCOL.is_null() } {
             warn!("Already initialized.");
             return;
@@ -125,6 +126,7 @@ impl Protocol {
         let protocol = Protocol {
             config,
             network: network.clone(),
+            bus,
             metrics: ProtocolMetrics::new(),
             transaction_worker: (transaction_worker_tx, Mutex::new(Some(transaction_worker_shutdown_tx))),
             transaction_responder_worker: (
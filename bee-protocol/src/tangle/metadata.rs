@@ -9,7 +9,7 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use super::Flags;
+use crate::{milestone::MilestoneIndex, tangle::flags::Flags};
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -18,6 +18,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub struct TransactionMetadata {
     pub(crate) flags: Flags,
     pub(crate) arrival_timestamp: u64,
+    pub(crate) milestone_index: MilestoneIndex,
 }
 
 impl TransactionMetadata {
@@ -30,4 +31,33 @@ impl TransactionMetadata {
             ..Self::default()
         }
     }
+
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    pub fn flags_mut(&mut self) -> &mut Flags {
+        &mut self.flags
+    }
+
+    pub fn arrival_timestamp(&self) -> u64 {
+        self.arrival_timestamp
+    }
+
+    /// Returns the index of the milestone that confirmed the transaction, if any.
+    pub fn milestone_index(&self) -> MilestoneIndex {
+        self.milestone_index
+    }
+
+    /// Confirms the transaction by the milestone of index `index`.
+    pub fn confirm(&mut self, index: MilestoneIndex) {
+        self.flags.set_confirmed();
+        self.milestone_index = index;
+    }
+
+    /// Reverts the confirmation of the transaction, e.g. when its milestone is rolled back.
+    pub fn unconfirm(&mut self) {
+        self.flags.remove(Flags::CONFIRMED | Flags::CONFLICTING);
+        self.milestone_index = MilestoneIndex(0);
+    }
 }
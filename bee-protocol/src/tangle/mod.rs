@@ -12,6 +12,7 @@
 pub mod flags;
 mod metadata;
 
+pub use bee_tangle::TransactionLoader;
 pub use metadata::TransactionMetadata;
 
 // TODO: reinstate the async worker
@@ -21,11 +22,12 @@ use crate::{milestone::MilestoneIndex, tangle::flags::Flags};
 
 use bee_crypto::ternary::Hash as TxHash;
 use bee_tangle::{Tangle, TransactionRef as TxRef};
-use bee_transaction::{bundled::BundledTransaction as Tx, TransactionVertex};
+use bee_transaction::bundled::BundledTransaction as Tx;
 
 use dashmap::DashMap;
 
 use std::{
+    collections::HashMap,
     ops::Deref,
     ptr,
     sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, Ordering},
@@ -61,17 +63,34 @@ impl MsTangle {
         }
     }
 
+    /// Bounds the number of transactions held in memory to `capacity`, evicted ones being reloaded through `loader`.
+    /// Only transactions marked persisted are evicted. Confirmed transactions are evicted first, the ones confirmed by
+    /// the oldest milestones leading, then the least recently used ones.
+    pub fn enable_cache(&self, capacity: usize, loader: Box<dyn TransactionLoader>) {
+        self.inner.set_cache(capacity, loader, eviction_priority);
+    }
+
     pub fn insert(&self, transaction: Tx, hash: TxHash, metadata: TransactionMetadata) -> Option<TxRef> {
-        if let Some(tx) = self.inner.insert(transaction, hash, metadata) {
-            self.propagate_solid_flag(hash);
-            return Some(tx);
-        }
-        None
+        self.insert_solidifying(transaction, hash, metadata).map(|(tx, _)| tx)
+    }
+
+    /// Same as `insert`, also returning the transactions that became solid, the inserted one included.
+    pub fn insert_solidifying(
+        &self,
+        transaction: Tx,
+        hash: TxHash,
+        metadata: TransactionMetadata,
+    ) -> Option<(TxRef, Vec<TxHash>)> {
+        let tx = self.inner.insert(transaction, hash, metadata)?;
+        let solidified = self.propagate_solid_flag(hash);
+
+        Some((tx, solidified))
     }
 
     // NOTE: not implemented as an async worker atm, but it makes things much easier
     #[inline]
-    fn propagate_solid_flag(&self, root: TxHash) {
+    fn propagate_solid_flag(&self, root: TxHash) -> Vec<TxHash> {
+        let mut solidified = Vec::new();
         let mut children = vec![root];
 
         while let Some(ref hash) = children.pop() {
@@ -79,9 +98,11 @@ impl MsTangle {
                 continue;
             }
 
-            if let Some(tx) = self.inner.get(&hash) {
-                if self.is_solid_transaction(tx.trunk()) && self.is_solid_transaction(tx.branch()) {
+            // Evicted transactions still have their parents known.
+            if let Some((trunk, branch)) = self.inner.get_parents(&hash) {
+                if self.is_solid_transaction(&trunk) && self.is_solid_transaction(&branch) {
                     self.inner.update_metadata(&hash, |metadata| metadata.flags.set_solid());
+                    solidified.push(*hash);
 
                     for child in self.inner.get_children(&hash) {
                         children.push(child);
@@ -89,6 +110,8 @@ impl MsTangle {
                 }
             }
         }
+
+        solidified
     }
 
     pub fn get_metadata(&self, hash: &TxHash) -> Option<TransactionMetadata> {
@@ -111,10 +134,10 @@ impl MsTangle {
     }
 
     // TODO: use combinator instead of match
-    pub fn get_milestone(&self, index: MilestoneIndex) -> Option<TxRef> {
+    pub async fn get_milestone(&self, index: MilestoneIndex) -> Option<TxRef> {
         match self.get_milestone_hash(index) {
             None => None,
-            Some(ref hash) => self.get(hash),
+            Some(ref hash) => self.fetch(hash).await,
         }
     }
 
@@ -167,6 +190,14 @@ impl MsTangle {
         self.solid_entry_points.remove(hash);
     }
 
+    /// Returns the solid entry points and the indices of the milestones that confirmed them.
+    pub fn get_solid_entry_points(&self) -> HashMap<TxHash, MilestoneIndex> {
+        self.solid_entry_points
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect()
+    }
+
     /// Returns whether the transaction associated with `hash` is a solid entry point.
     pub fn is_solid_entry_point(&self, hash: &TxHash) -> bool {
         self.solid_entry_points.contains_key(hash)
@@ -185,6 +216,15 @@ impl MsTangle {
     }
 }
 
+// Milestone indices are 32 bits, so any confirmed transaction has a lower priority than an unconfirmed one.
+fn eviction_priority(metadata: &TransactionMetadata, last_access: u64) -> u64 {
+    if metadata.flags().is_confirmed() {
+        *metadata.milestone_index() as u64
+    } else {
+        u32::MAX as u64 + 1 + last_access
+    }
+}
+
 static TANGLE: AtomicPtr<MsTangle> = AtomicPtr::new(ptr::null_mut());
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
@@ -205,6 +245,18 @@ pub fn tangle() -> &'static MsTangle {
     }
 }
 
+/// Drops the Tangle singleton, which must not be used anymore until it is initialized again.
+pub fn drop() {
+    if INITIALIZED.compare_and_swap(true, false, Ordering::Relaxed) {
+        let tangle = TANGLE.swap(ptr::null_mut(), Ordering::Relaxed);
+        if !tangle.is_null() {
+            let _ = unsafe { Box::from_raw(tangle) };
+        }
+    } else {
+        panic!("Tangle already dropped");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -10,6 +10,7 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 use crate::{
+    event::NewMilestone,
     milestone::{Milestone, MilestoneBuilder, MilestoneBuilderError},
     protocol::Protocol,
     tangle::tangle,
@@ -92,15 +93,16 @@ where
         match self.validate_milestone(tail_hash).await {
             Ok(milestone) => {
                 // TODO check multiple triggers
-                tangle().add_milestone(milestone.index.into(), milestone.hash);
-                let mut metadata = tangle().get_metadata(&milestone.hash).unwrap();
+                tangle().add_milestone(milestone.index(), *milestone.hash());
+                let mut metadata = tangle().get_metadata(milestone.hash()).unwrap();
                 metadata.flags.set_milestone();
-                tangle().set_metadata(&milestone.hash, metadata);
+                tangle().set_metadata(milestone.hash(), metadata);
+                Protocol::get().bus.dispatch(NewMilestone(milestone.clone()));
 
                 // TODO deref ? Why not .into() ?
-                if milestone.index > tangle().get_last_milestone_index() {
-                    info!("New milestone #{}.", *milestone.index);
-                    tangle().update_last_milestone_index(milestone.index.into());
+                if milestone.index() > tangle().get_last_milestone_index() {
+                    info!("New milestone #{}.", *milestone.index());
+                    tangle().update_last_milestone_index(milestone.index());
                 }
                 // TODO only trigger if index == last solid index ?
                 // TODO trigger only if requester is empty ? And unsynced ?
@@ -10,15 +10,23 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 use crate::{
-    message::MilestoneRequest, milestone::MilestoneIndex, protocol::Protocol, tangle::tangle, worker::SenderWorker,
+    message::{MilestoneRequest, LATEST_MILESTONE_INDEX},
+    milestone::MilestoneIndex,
+    protocol::Protocol,
+    tangle::tangle,
+    worker::SenderWorker,
 };
 
 use bee_network::EndpointId;
 
+use async_std::task;
 use futures::{channel::oneshot, future::FutureExt, select};
 use log::info;
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, time::Duration};
+
+// Delay before retrying a request that was made while no peer was handshaked.
+const RETRY_INTERVAL_MS: u64 = 1000;
 
 #[derive(Eq, PartialEq)]
 pub(crate) struct MilestoneRequesterWorkerEntry(pub(crate) MilestoneIndex, pub(crate) Option<EndpointId>);
@@ -44,9 +52,10 @@ impl MilestoneRequesterWorker {
         Self { counter: 0 }
     }
 
-    async fn process_request(&mut self, index: MilestoneIndex, epid: Option<EndpointId>) {
+    // Returns `false` if the request could not be processed because no peer is handshaked yet.
+    async fn process_request(&mut self, index: MilestoneIndex, epid: Option<EndpointId>) -> bool {
         if Protocol::get().peer_manager.handshaked_peers.is_empty() {
-            return;
+            return false;
         }
 
         let guard = Protocol::get().peer_manager.handshaked_peers_keys.read().await;
@@ -70,6 +79,8 @@ impl MilestoneRequesterWorker {
                 }
             }
         }
+
+        true
     }
 
     pub(crate) async fn run(mut self, shutdown: oneshot::Receiver<()>) {
@@ -81,10 +92,19 @@ impl MilestoneRequesterWorker {
             select! {
                 entry = Protocol::get().milestone_requester_worker.0.pop() => {
                     if let MilestoneRequesterWorkerEntry(index, epid) = entry {
-                        if !tangle().contains_milestone(index.into()) {
-                            self.process_request(index, epid).await;
+                        if !tangle().contains_milestone(index.into())
+                            && !self.process_request(index, epid).await
+                            && epid.is_none()
+                            && *index != LATEST_MILESTONE_INDEX
+                        {
+                            // Untargeted requests, e.g. seen milestones requested at startup, are kept until a peer
+                            // is handshaked instead of being dropped. They are queued again later, without holding
+                            // up the other requests.
+                            task::spawn(async move {
+                                task::sleep(Duration::from_millis(RETRY_INTERVAL_MS)).await;
+                                Protocol::request_milestone(index, None);
+                            });
                         }
-
                     }
                 },
                 _ = shutdown_fused => {
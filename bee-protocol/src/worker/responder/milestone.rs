@@ -10,7 +10,9 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 use crate::{
-    message::{compress_transaction_bytes, MilestoneRequest, Transaction as TransactionMessage},
+    message::{
+        compress_transaction_bytes, MilestoneRequest, Transaction as TransactionMessage, LATEST_MILESTONE_INDEX,
+    },
     tangle::tangle,
     worker::SenderWorker,
 };
@@ -42,12 +44,12 @@ impl MilestoneResponderWorker {
 
     async fn process_request(&self, epid: EndpointId, request: MilestoneRequest) {
         let index = match request.index {
-            0 => tangle().get_last_milestone_index(),
+            LATEST_MILESTONE_INDEX => tangle().get_last_milestone_index(),
             _ => request.index.into(),
         };
 
         // TODO send complete ms bundle ?
-        match tangle().get_milestone(index) {
+        match tangle().get_milestone(index).await {
             Some(transaction) => {
                 let mut trits = TritBuf::<T1B1Buf>::zeros(Transaction::trit_len());
                 transaction.into_trits_allocated(&mut trits);
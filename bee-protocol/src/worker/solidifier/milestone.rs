@@ -9,7 +9,12 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use crate::{milestone::MilestoneIndex, protocol::Protocol, tangle::tangle};
+use crate::{
+    event::LatestSolidMilestoneChanged,
+    milestone::{Milestone, MilestoneIndex},
+    protocol::Protocol,
+    tangle::tangle,
+};
 
 use futures::{
     channel::{mpsc, oneshot},
@@ -94,8 +99,10 @@ impl MilestoneSolidifierWorker {
 
         if let Some(target_hash) = tangle().get_milestone_hash(target_index.into()) {
             if tangle().is_solid_transaction(&target_hash) {
-                // TODO set confirmation index + trigger ledger
                 tangle().update_solid_milestone_index(target_index.into());
+                Protocol::get()
+                    .bus
+                    .dispatch(LatestSolidMilestoneChanged(Milestone::new(target_hash, target_index)));
                 Protocol::broadcast_heartbeat(
                     tangle().get_solid_milestone_index(),
                     tangle().get_snapshot_milestone_index(),
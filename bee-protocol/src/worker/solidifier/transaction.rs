@@ -38,6 +38,9 @@ impl TransactionSolidifierWorker {
     async fn solidify(&self, hash: Hash, index: MilestoneIndex) -> bool {
         let mut missing_hashes = HashSet::new();
 
+        // The traversal only sees the transactions held in memory.
+        tangle().fetch_cone(&hash, |metadata| !metadata.flags.is_solid()).await;
+
         traversal::visit_parents_depth_first(
             tangle(),
             hash,
@@ -10,6 +10,7 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 use crate::{
+    event::{NewTransaction, TransactionsSolidified},
     message::{uncompress_transaction_bytes, Transaction as TransactionMessage},
     protocol::Protocol,
     tangle::{tangle, TransactionMetadata},
@@ -124,8 +125,15 @@ impl TransactionWorker {
         }
 
         // store transaction
-        if let Some(transaction) = tangle().insert(transaction, hash, metadata) {
+        if let Some((transaction, solidified)) = tangle().insert_solidifying(transaction, hash, metadata) {
             Protocol::get().metrics.new_transactions_received_inc();
+            Protocol::get().bus.dispatch(NewTransaction {
+                hash,
+                transaction: (*transaction).clone(),
+            });
+            if !solidified.is_empty() {
+                Protocol::get().bus.dispatch(TransactionsSolidified(solidified));
+            }
 
             if !tangle().is_synced() && Protocol::get().requested.is_empty() {
                 Protocol::trigger_milestone_solidification().await;
@@ -188,12 +196,14 @@ mod tests {
 
     use crate::ProtocolConfig;
 
-    use bee_common::shutdown::Shutdown;
+    use bee_common::{event::Bus, shutdown::Shutdown};
     use bee_network::{NetworkConfig, Url};
 
     use async_std::task::{block_on, spawn};
     use futures::sink::SinkExt;
 
+    use std::sync::Arc;
+
     #[test]
     fn test_tx_worker_with_compressed_buffer() {
         let mut shutdown = Shutdown::new();
@@ -207,7 +217,7 @@ mod tests {
 
         // init protocol
         let protocol_config = ProtocolConfig::build().finish();
-        block_on(Protocol::init(protocol_config, network));
+        block_on(Protocol::init(protocol_config, network, Arc::new(Bus::new())));
 
         assert_eq!(tangle().len(), 0);
 
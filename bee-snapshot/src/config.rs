@@ -9,13 +9,28 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use crate::local::{LocalSnapshotConfig, LocalSnapshotConfigBuilder};
+use crate::{
+    global::{GlobalSnapshotConfig, GlobalSnapshotConfigBuilder},
+    local::{LocalSnapshotConfig, LocalSnapshotConfigBuilder},
+};
 
 use serde::Deserialize;
 
+const DEFAULT_LOAD_TYPE: LoadType = LoadType::Local;
+
+/// The kind of snapshot the node starts from.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadType {
+    Local,
+    Global,
+}
+
 #[derive(Default, Deserialize)]
 pub struct SnapshotConfigBuilder {
+    load_type: Option<LoadType>,
     local: LocalSnapshotConfigBuilder,
+    global: GlobalSnapshotConfigBuilder,
 }
 
 impl SnapshotConfigBuilder {
@@ -23,21 +38,35 @@ impl SnapshotConfigBuilder {
         Self::default()
     }
 
+    pub fn load_type(mut self, load_type: LoadType) -> Self {
+        self.load_type.replace(load_type);
+        self
+    }
+
     pub fn local_file_path(mut self, file_path: String) -> Self {
         self.local = self.local.file_path(file_path);
         self
     }
 
+    pub fn global_file_path(mut self, file_path: String) -> Self {
+        self.global = self.global.file_path(file_path);
+        self
+    }
+
     pub fn finish(self) -> SnapshotConfig {
         SnapshotConfig {
+            load_type: self.load_type.unwrap_or(DEFAULT_LOAD_TYPE),
             local: self.local.finish(),
+            global: self.global.finish(),
         }
     }
 }
 
 #[derive(Clone)]
 pub struct SnapshotConfig {
+    load_type: LoadType,
     local: LocalSnapshotConfig,
+    global: GlobalSnapshotConfig,
 }
 
 impl SnapshotConfig {
@@ -45,7 +74,15 @@ impl SnapshotConfig {
         SnapshotConfigBuilder::new()
     }
 
+    pub fn load_type(&self) -> LoadType {
+        self.load_type
+    }
+
     pub fn local(&self) -> &LocalSnapshotConfig {
         &self.local
     }
+
+    pub fn global(&self) -> &GlobalSnapshotConfig {
+        &self.global
+    }
 }
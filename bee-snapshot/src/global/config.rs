@@ -0,0 +1,92 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use serde::Deserialize;
+
+const DEFAULT_FILE_PATH: &str = "./data/snapshot.txt";
+const DEFAULT_INDEX: u32 = 0;
+
+#[derive(Default, Deserialize)]
+pub struct GlobalSnapshotConfigBuilder {
+    file_path: Option<String>,
+    meta_file_path: Option<String>,
+    spent_addresses_file_paths: Option<Vec<String>>,
+    index: Option<u32>,
+}
+
+impl GlobalSnapshotConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn file_path(mut self, file_path: String) -> Self {
+        self.file_path.replace(file_path);
+        self
+    }
+
+    pub fn meta_file_path(mut self, meta_file_path: String) -> Self {
+        self.meta_file_path.replace(meta_file_path);
+        self
+    }
+
+    pub fn spent_addresses_file_paths(mut self, spent_addresses_file_paths: Vec<String>) -> Self {
+        self.spent_addresses_file_paths.replace(spent_addresses_file_paths);
+        self
+    }
+
+    pub fn index(mut self, index: u32) -> Self {
+        self.index.replace(index);
+        self
+    }
+
+    pub fn finish(self) -> GlobalSnapshotConfig {
+        GlobalSnapshotConfig {
+            file_path: self.file_path.unwrap_or_else(|| DEFAULT_FILE_PATH.to_string()),
+            meta_file_path: self.meta_file_path,
+            spent_addresses_file_paths: self.spent_addresses_file_paths.unwrap_or_default(),
+            index: self.index.unwrap_or(DEFAULT_INDEX),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GlobalSnapshotConfig {
+    file_path: String,
+    meta_file_path: Option<String>,
+    spent_addresses_file_paths: Vec<String>,
+    index: u32,
+}
+
+impl GlobalSnapshotConfig {
+    pub fn build() -> GlobalSnapshotConfigBuilder {
+        GlobalSnapshotConfigBuilder::new()
+    }
+
+    /// Path of the `address;balance` file.
+    pub fn file_path(&self) -> &String {
+        &self.file_path
+    }
+
+    /// Path of the milestone metadata file, if any.
+    pub fn meta_file_path(&self) -> Option<&String> {
+        self.meta_file_path.as_ref()
+    }
+
+    /// Paths of the files listing the addresses spent in previous epochs.
+    pub fn spent_addresses_file_paths(&self) -> &Vec<String> {
+        &self.spent_addresses_file_paths
+    }
+
+    /// Milestone index of the snapshot when there is no milestone metadata file.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
@@ -0,0 +1,516 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::{GlobalSnapshotConfig, LocalSnapshotMetadata, SnapshotState};
+
+use bee_crypto::ternary::Hash;
+use bee_ternary::{T1B1Buf, Trits, TryteBuf, T1B1};
+use bee_transaction::bundled::{Address, BundledTransactionField, IOTA_SUPPLY};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use async_std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    prelude::*,
+};
+use log::info;
+
+/// Errors occurring while reading the files of a global snapshot; lines are numbered from 1.
+#[derive(Debug)]
+pub enum GlobalSnapshotReadError {
+    IOError(async_std::io::Error),
+    MissingLine { path: String, line: usize },
+    InvalidAddress { path: String, line: usize },
+    InvalidBalance { path: String, line: usize },
+    DuplicateAddress { path: String, line: usize },
+    InvalidHash { path: String, line: usize },
+    InvalidNumber { path: String, line: usize },
+    InvalidSupply(u64),
+}
+
+impl fmt::Display for GlobalSnapshotReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GlobalSnapshotReadError::IOError(e) => write!(f, "I/O error: {}", e),
+            GlobalSnapshotReadError::MissingLine { path, line } => write!(f, "{}:{}: missing line", path, line),
+            GlobalSnapshotReadError::InvalidAddress { path, line } => write!(f, "{}:{}: invalid address", path, line),
+            GlobalSnapshotReadError::InvalidBalance { path, line } => write!(f, "{}:{}: invalid balance", path, line),
+            GlobalSnapshotReadError::DuplicateAddress { path, line } => {
+                write!(f, "{}:{}: duplicate address", path, line)
+            }
+            GlobalSnapshotReadError::InvalidHash { path, line } => write!(f, "{}:{}: invalid hash", path, line),
+            GlobalSnapshotReadError::InvalidNumber { path, line } => write!(f, "{}:{}: invalid number", path, line),
+            GlobalSnapshotReadError::InvalidSupply(supply) => {
+                write!(f, "invalid supply {}, expected {}", supply, IOTA_SUPPLY)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GlobalSnapshotReadError {}
+
+/// A snapshot in the textual format of the legacy network.
+pub struct GlobalSnapshot {
+    metadata: LocalSnapshotMetadata,
+    state: SnapshotState,
+}
+
+// Returns the non-empty lines of a file along with their number.
+async fn read_lines(path: &str) -> Result<Vec<(usize, String)>, GlobalSnapshotReadError> {
+    let file = File::open(path).await.map_err(GlobalSnapshotReadError::IOError)?;
+    let mut lines = BufReader::new(file).lines();
+    let mut non_empty_lines = Vec::new();
+    let mut number = 0;
+
+    while let Some(line) = lines.next().await {
+        let line = line.map_err(GlobalSnapshotReadError::IOError)?;
+        number += 1;
+        if !line.trim().is_empty() {
+            non_empty_lines.push((number, line.trim().to_string()));
+        }
+    }
+
+    Ok(non_empty_lines)
+}
+
+fn parse_address(trytes: &str) -> Option<Address> {
+    TryteBuf::try_from_str(trytes)
+        .ok()
+        .and_then(|trytes| Address::try_from_inner(trytes.as_trits().encode::<T1B1Buf>()).ok())
+}
+
+fn parse_hash(trytes: &str) -> Option<Hash> {
+    TryteBuf::try_from_str(trytes)
+        .ok()
+        .and_then(|trytes| Hash::try_from_inner(trytes.as_trits().encode::<T1B1Buf>()).ok())
+}
+
+fn encode_trytes(trits: &Trits<T1B1>) -> String {
+    trits.iter_trytes().map(char::from).collect()
+}
+
+// Parses a `hash;index` line.
+fn parse_hash_index(path: &str, line: usize, content: &str) -> Result<(Hash, u32), GlobalSnapshotReadError> {
+    let mut fields = content.split(';');
+    let hash = fields
+        .next()
+        .and_then(parse_hash)
+        .ok_or_else(|| GlobalSnapshotReadError::InvalidHash {
+            path: path.to_string(),
+            line,
+        })?;
+    let index = fields
+        .next()
+        .and_then(|index| index.parse::<u32>().ok())
+        .ok_or_else(|| GlobalSnapshotReadError::InvalidNumber {
+            path: path.to_string(),
+            line,
+        })?;
+
+    Ok((hash, index))
+}
+
+impl GlobalSnapshot {
+    pub fn new(metadata: LocalSnapshotMetadata, state: SnapshotState) -> Self {
+        Self { metadata, state }
+    }
+
+    /// Reads the `address;balance` file, the spent addresses files and, if configured, the milestone metadata file.
+    /// Without metadata file, the snapshot has the configured index and neither solid entry points nor seen milestones.
+    pub async fn from_config(config: &GlobalSnapshotConfig) -> Result<GlobalSnapshot, GlobalSnapshotReadError> {
+        let metadata = match config.meta_file_path() {
+            Some(path) => Self::read_metadata(path).await?,
+            None => LocalSnapshotMetadata::new(Hash::zeros(), config.index(), 0, HashMap::new(), HashMap::new()),
+        };
+
+        let balances = Self::read_balances(config.file_path()).await?;
+
+        let mut spent_addresses = HashSet::new();
+        for path in config.spent_addresses_file_paths() {
+            for (line, content) in read_lines(path).await? {
+                spent_addresses.insert(parse_address(&content).ok_or_else(|| {
+                    GlobalSnapshotReadError::InvalidAddress {
+                        path: path.to_string(),
+                        line,
+                    }
+                })?);
+            }
+        }
+
+        info!(
+            "Read global snapshot with index {}, {} balances and {} spent addresses.",
+            metadata.index(),
+            balances.len(),
+            spent_addresses.len()
+        );
+
+        Ok(GlobalSnapshot {
+            metadata,
+            state: SnapshotState::new(balances, spent_addresses),
+        })
+    }
+
+    async fn read_balances(path: &str) -> Result<HashMap<Address, u64>, GlobalSnapshotReadError> {
+        let mut balances = HashMap::new();
+        let mut supply: u64 = 0;
+
+        for (line, content) in read_lines(path).await? {
+            let mut fields = content.split(';');
+            let address =
+                fields
+                    .next()
+                    .and_then(parse_address)
+                    .ok_or_else(|| GlobalSnapshotReadError::InvalidAddress {
+                        path: path.to_string(),
+                        line,
+                    })?;
+            let balance = fields
+                .next()
+                .and_then(|balance| balance.parse::<u64>().ok())
+                .ok_or_else(|| GlobalSnapshotReadError::InvalidBalance {
+                    path: path.to_string(),
+                    line,
+                })?;
+
+            if balances.insert(address, balance).is_some() {
+                return Err(GlobalSnapshotReadError::DuplicateAddress {
+                    path: path.to_string(),
+                    line,
+                });
+            }
+            supply = supply.saturating_add(balance);
+        }
+
+        if supply != IOTA_SUPPLY as u64 {
+            return Err(GlobalSnapshotReadError::InvalidSupply(supply));
+        }
+
+        balances.retain(|_, balance| *balance != 0);
+
+        Ok(balances)
+    }
+
+    // The metadata file holds the milestone hash, index and timestamp, the numbers of solid entry points and seen
+    // milestones, one per line, followed by the `hash;index` lines of the solid entry points and seen milestones.
+    async fn read_metadata(path: &str) -> Result<LocalSnapshotMetadata, GlobalSnapshotReadError> {
+        let lines = read_lines(path).await?;
+        let get_line = |i: usize| {
+            lines
+                .get(i)
+                .map(|(line, content)| (*line, content.as_str()))
+                .ok_or_else(|| GlobalSnapshotReadError::MissingLine {
+                    path: path.to_string(),
+                    line: lines.last().map_or(0, |(line, _)| *line) + 1,
+                })
+        };
+        let get_number = |i: usize| {
+            get_line(i).and_then(|(line, content)| {
+                content
+                    .parse::<u64>()
+                    .map_err(|_| GlobalSnapshotReadError::InvalidNumber {
+                        path: path.to_string(),
+                        line,
+                    })
+            })
+        };
+
+        let hash = get_line(0).and_then(|(line, content)| {
+            parse_hash(content).ok_or_else(|| GlobalSnapshotReadError::InvalidHash {
+                path: path.to_string(),
+                line,
+            })
+        })?;
+        let index = get_number(1)? as u32;
+        let timestamp = get_number(2)?;
+        let solid_entry_points_num = get_number(3)? as usize;
+        let seen_milestones_num = get_number(4)? as usize;
+
+        let mut solid_entry_points = HashMap::with_capacity(solid_entry_points_num);
+        for i in 5..5 + solid_entry_points_num {
+            let (line, content) = get_line(i)?;
+            let (hash, index) = parse_hash_index(path, line, content)?;
+            solid_entry_points.insert(hash, index);
+        }
+
+        let mut seen_milestones = HashMap::with_capacity(seen_milestones_num);
+        for i in 5 + solid_entry_points_num..5 + solid_entry_points_num + seen_milestones_num {
+            let (line, content) = get_line(i)?;
+            let (hash, index) = parse_hash_index(path, line, content)?;
+            seen_milestones.insert(hash, index);
+        }
+
+        Ok(LocalSnapshotMetadata::new(
+            hash,
+            index,
+            timestamp,
+            solid_entry_points,
+            seen_milestones,
+        ))
+    }
+
+    /// Writes the balances and, if paths are given, the metadata and the spent addresses in the formats read by
+    /// `from_config`.
+    pub async fn to_files(
+        &self,
+        file_path: &str,
+        meta_file_path: Option<&str>,
+        spent_addresses_file_path: Option<&str>,
+    ) -> Result<(), async_std::io::Error> {
+        let mut writer = BufWriter::new(File::create(file_path).await?);
+        for (address, balance) in self.state.balances() {
+            writer
+                .write_all(format!("{};{}\n", encode_trytes(address.to_inner()), balance).as_bytes())
+                .await?;
+        }
+        writer.flush().await?;
+
+        if let Some(meta_file_path) = meta_file_path {
+            let mut writer = BufWriter::new(File::create(meta_file_path).await?);
+            writer
+                .write_all(
+                    format!(
+                        "{}\n{}\n{}\n{}\n{}\n",
+                        encode_trytes(self.metadata.hash().as_trits()),
+                        self.metadata.index(),
+                        self.metadata.timestamp(),
+                        self.metadata.solid_entry_points().len(),
+                        self.metadata.seen_milestones().len()
+                    )
+                    .as_bytes(),
+                )
+                .await?;
+            for (hash, index) in self
+                .metadata
+                .solid_entry_points()
+                .iter()
+                .chain(self.metadata.seen_milestones().iter())
+            {
+                writer
+                    .write_all(format!("{};{}\n", encode_trytes(hash.as_trits()), index).as_bytes())
+                    .await?;
+            }
+            writer.flush().await?;
+        }
+
+        if let Some(spent_addresses_file_path) = spent_addresses_file_path {
+            let mut writer = BufWriter::new(File::create(spent_addresses_file_path).await?);
+            for address in self.state.spent_addresses() {
+                writer
+                    .write_all(format!("{}\n", encode_trytes(address.to_inner())).as_bytes())
+                    .await?;
+            }
+            writer.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn metadata(&self) -> &LocalSnapshotMetadata {
+        &self.metadata
+    }
+
+    pub fn state(&self) -> &SnapshotState {
+        &self.state
+    }
+
+    pub fn into_parts(self) -> (LocalSnapshotMetadata, SnapshotState) {
+        (self.metadata, self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use crate::GlobalSnapshotConfigBuilder;
+
+    use async_std::task::block_on;
+
+    use std::fs::{remove_file, write};
+
+    fn trytes(c: char) -> String {
+        format!("{}{}", "A".repeat(80), c)
+    }
+
+    fn file_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("{}_{}.txt", name, std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn read_balances_metadata_and_spent_addresses() {
+        let balances_path = file_path("global_balances");
+        let meta_path = file_path("global_meta");
+        let spent_path = file_path("global_spent");
+
+        write(
+            &balances_path,
+            format!(
+                "{};{}\n{};0\n\n{};{}\n",
+                trytes('A'),
+                IOTA_SUPPLY - 10,
+                trytes('B'),
+                trytes('C'),
+                10
+            ),
+        )
+        .unwrap();
+        write(
+            &meta_path,
+            format!(
+                "{}\n100\n1593606000\n1\n2\n{};90\n{};99\n{};100\n",
+                trytes('D'),
+                trytes('E'),
+                trytes('F'),
+                trytes('G')
+            ),
+        )
+        .unwrap();
+        write(&spent_path, format!("{}\n{}\n", trytes('H'), trytes('I'))).unwrap();
+
+        let config = GlobalSnapshotConfigBuilder::new()
+            .file_path(balances_path.clone())
+            .meta_file_path(meta_path.clone())
+            .spent_addresses_file_paths(vec![spent_path.clone()])
+            .finish();
+        let snapshot = block_on(GlobalSnapshot::from_config(&config)).unwrap();
+        remove_file(&balances_path).unwrap();
+        remove_file(&meta_path).unwrap();
+        remove_file(&spent_path).unwrap();
+
+        assert_eq!(snapshot.metadata().hash(), &parse_hash(&trytes('D')).unwrap());
+        assert_eq!(snapshot.metadata().index(), 100);
+        assert_eq!(snapshot.metadata().timestamp(), 1_593_606_000);
+        assert_eq!(snapshot.metadata().solid_entry_points().len(), 1);
+        assert_eq!(
+            snapshot
+                .metadata()
+                .solid_entry_points()
+                .get(&parse_hash(&trytes('E')).unwrap()),
+            Some(&90)
+        );
+        assert_eq!(snapshot.metadata().seen_milestones().len(), 2);
+        assert_eq!(
+            snapshot
+                .metadata()
+                .seen_milestones()
+                .get(&parse_hash(&trytes('G')).unwrap()),
+            Some(&100)
+        );
+        // The zero balance is not kept.
+        assert_eq!(snapshot.state().balances().len(), 2);
+        assert_eq!(
+            snapshot.state().balances().get(&parse_address(&trytes('C')).unwrap()),
+            Some(&10)
+        );
+        assert_eq!(snapshot.state().spent_addresses().len(), 2);
+        assert!(snapshot
+            .state()
+            .spent_addresses()
+            .contains(&parse_address(&trytes('I')).unwrap()));
+    }
+
+    #[test]
+    fn write_read_round_trip() {
+        let balances_path = file_path("global_round_trip");
+        let meta_path = file_path("global_round_trip_meta");
+        let spent_path = file_path("global_round_trip_spent");
+        let mut solid_entry_points = HashMap::new();
+        let mut balances = HashMap::new();
+
+        solid_entry_points.insert(parse_hash(&trytes('A')).unwrap(), 99);
+        balances.insert(parse_address(&trytes('B')).unwrap(), IOTA_SUPPLY as u64 - 1);
+        balances.insert(parse_address(&trytes('C')).unwrap(), 1);
+        let mut spent_addresses = HashSet::new();
+        spent_addresses.insert(parse_address(&trytes('E')).unwrap());
+        let snapshot = GlobalSnapshot::new(
+            LocalSnapshotMetadata::new(
+                parse_hash(&trytes('D')).unwrap(),
+                100,
+                1_593_606_000,
+                solid_entry_points,
+                HashMap::new(),
+            ),
+            SnapshotState::new(balances, spent_addresses),
+        );
+
+        block_on(snapshot.to_files(&balances_path, Some(&meta_path), Some(&spent_path))).unwrap();
+        let config = GlobalSnapshotConfigBuilder::new()
+            .file_path(balances_path.clone())
+            .meta_file_path(meta_path.clone())
+            .spent_addresses_file_paths(vec![spent_path.clone()])
+            .finish();
+        let read = block_on(GlobalSnapshot::from_config(&config)).unwrap();
+        remove_file(&balances_path).unwrap();
+        remove_file(&meta_path).unwrap();
+        remove_file(&spent_path).unwrap();
+
+        assert_eq!(read.metadata().hash(), snapshot.metadata().hash());
+        assert_eq!(read.metadata().index(), snapshot.metadata().index());
+        assert_eq!(read.metadata().timestamp(), snapshot.metadata().timestamp());
+        assert_eq!(
+            read.metadata().solid_entry_points(),
+            snapshot.metadata().solid_entry_points()
+        );
+        assert_eq!(read.state().balances(), snapshot.state().balances());
+        assert_eq!(read.state().spent_addresses(), snapshot.state().spent_addresses());
+    }
+
+    #[test]
+    fn read_without_metadata() {
+        let balances_path = file_path("global_no_meta");
+
+        write(&balances_path, format!("{};{}\n", trytes('A'), IOTA_SUPPLY)).unwrap();
+
+        let config = GlobalSnapshotConfigBuilder::new()
+            .file_path(balances_path.clone())
+            .index(42)
+            .finish();
+        let snapshot = block_on(GlobalSnapshot::from_config(&config)).unwrap();
+        remove_file(&balances_path).unwrap();
+
+        assert_eq!(snapshot.metadata().hash(), &Hash::zeros());
+        assert_eq!(snapshot.metadata().index(), 42);
+        assert!(snapshot.metadata().solid_entry_points().is_empty());
+        assert!(snapshot.state().spent_addresses().is_empty());
+        assert_eq!(snapshot.state().balances().len(), 1);
+    }
+
+    #[test]
+    fn read_invalid_balance_and_supply() {
+        let balances_path = file_path("global_invalid");
+        let config = GlobalSnapshotConfigBuilder::new()
+            .file_path(balances_path.clone())
+            .finish();
+
+        write(&balances_path, format!("{};1\n\n{};A\n", trytes('A'), trytes('B'))).unwrap();
+        match block_on(GlobalSnapshot::from_config(&config)) {
+            Err(GlobalSnapshotReadError::InvalidBalance { line, .. }) => assert_eq!(line, 3),
+            _ => panic!("expected an invalid balance"),
+        }
+
+        write(&balances_path, format!("{};1\n", trytes('A'))).unwrap();
+        match block_on(GlobalSnapshot::from_config(&config)) {
+            Err(GlobalSnapshotReadError::InvalidSupply(supply)) => assert_eq!(supply, 1),
+            _ => panic!("expected an invalid supply"),
+        }
+
+        remove_file(&balances_path).unwrap();
+    }
+}
@@ -10,9 +10,14 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 mod config;
+mod global;
 mod local;
 mod state;
 
-pub use config::{SnapshotConfig, SnapshotConfigBuilder};
-pub use local::{LocalSnapshot, LocalSnapshotMetadata, SnapshotReadError};
+pub use config::{LoadType, SnapshotConfig, SnapshotConfigBuilder};
+pub use global::{GlobalSnapshot, GlobalSnapshotConfig, GlobalSnapshotConfigBuilder, GlobalSnapshotReadError};
+pub use local::{
+    LocalSnapshot, LocalSnapshotEntry, LocalSnapshotHeader, LocalSnapshotMetadata, LocalSnapshotPruner,
+    LocalSnapshotStream, LocalSnapshotWorker, LocalSnapshotWorkerEvent, SnapshotReadError, SnapshotWriteError,
+};
 pub use state::SnapshotState;
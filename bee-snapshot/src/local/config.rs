@@ -12,10 +12,17 @@
 use serde::Deserialize;
 
 const DEFAULT_FILE_PATH: &str = "./data/snapshot.bin";
+const DEFAULT_DEPTH: u32 = 50;
+const DEFAULT_INTERVAL: u32 = 50;
+const DEFAULT_CHANNEL_SIZE: usize = 1000;
 
 #[derive(Default, Deserialize)]
 pub struct LocalSnapshotConfigBuilder {
     file_path: Option<String>,
+    depth: Option<u32>,
+    interval: Option<u32>,
+    sha256: Option<String>,
+    channel_size: Option<usize>,
 }
 
 impl LocalSnapshotConfigBuilder {
@@ -28,9 +35,33 @@ impl LocalSnapshotConfigBuilder {
         self
     }
 
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth.replace(depth);
+        self
+    }
+
+    pub fn interval(mut self, interval: u32) -> Self {
+        self.interval.replace(interval);
+        self
+    }
+
+    pub fn sha256(mut self, sha256: String) -> Self {
+        self.sha256.replace(sha256);
+        self
+    }
+
+    pub fn channel_size(mut self, channel_size: usize) -> Self {
+        self.channel_size.replace(channel_size);
+        self
+    }
+
     pub fn finish(self) -> LocalSnapshotConfig {
         LocalSnapshotConfig {
             file_path: self.file_path.unwrap_or_else(|| DEFAULT_FILE_PATH.to_string()),
+            depth: self.depth.unwrap_or(DEFAULT_DEPTH),
+            interval: self.interval.unwrap_or(DEFAULT_INTERVAL),
+            sha256: self.sha256,
+            channel_size: self.channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE),
         }
     }
 }
@@ -38,6 +69,10 @@ impl LocalSnapshotConfigBuilder {
 #[derive(Clone)]
 pub struct LocalSnapshotConfig {
     file_path: String,
+    depth: u32,
+    interval: u32,
+    sha256: Option<String>,
+    channel_size: usize,
 }
 
 impl LocalSnapshotConfig {
@@ -48,4 +83,24 @@ impl LocalSnapshotConfig {
     pub fn file_path(&self) -> &String {
         &self.file_path
     }
+
+    /// Number of milestones kept below the solid milestone when taking a local snapshot.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Number of milestones between two local snapshots.
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+
+    /// Expected SHA-256 of the snapshot file, as a hexadecimal string, if it should be checked when reading it.
+    pub fn sha256(&self) -> Option<&str> {
+        self.sha256.as_deref()
+    }
+
+    /// Number of confirmed milestones queued for the local snapshot worker before new ones are dropped.
+    pub fn channel_size(&self) -> usize {
+        self.channel_size
+    }
 }
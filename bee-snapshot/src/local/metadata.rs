@@ -18,10 +18,26 @@ pub struct LocalSnapshotMetadata {
     pub(crate) index: u32,
     pub(crate) timestamp: u64,
     pub(crate) solid_entry_points: HashMap<Hash, u32>,
-    pub(crate) seen_milestones: Vec<Hash>,
+    pub(crate) seen_milestones: HashMap<Hash, u32>,
 }
 
 impl LocalSnapshotMetadata {
+    pub fn new(
+        hash: Hash,
+        index: u32,
+        timestamp: u64,
+        solid_entry_points: HashMap<Hash, u32>,
+        seen_milestones: HashMap<Hash, u32>,
+    ) -> Self {
+        Self {
+            hash,
+            index,
+            timestamp,
+            solid_entry_points,
+            seen_milestones,
+        }
+    }
+
     pub fn hash(&self) -> &Hash {
         &self.hash
     }
@@ -38,7 +54,7 @@ impl LocalSnapshotMetadata {
         &self.solid_entry_points
     }
 
-    pub fn seen_milestones(&self) -> &Vec<Hash> {
+    pub fn seen_milestones(&self) -> &HashMap<Hash, u32> {
         &self.seen_milestones
     }
 }
@@ -11,8 +11,14 @@
 
 mod config;
 mod metadata;
+mod pruner;
 mod snapshot;
+mod stream;
+mod worker;
 
 pub use config::{LocalSnapshotConfig, LocalSnapshotConfigBuilder};
 pub use metadata::LocalSnapshotMetadata;
-pub use snapshot::{LocalSnapshot, SnapshotReadError};
+pub use pruner::LocalSnapshotPruner;
+pub use snapshot::{LocalSnapshot, SnapshotReadError, SnapshotWriteError};
+pub use stream::{LocalSnapshotEntry, LocalSnapshotHeader, LocalSnapshotStream};
+pub use worker::{LocalSnapshotWorker, LocalSnapshotWorkerEvent};
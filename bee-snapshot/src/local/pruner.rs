@@ -0,0 +1,32 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use bee_crypto::ternary::Hash;
+use bee_protocol::MilestoneIndex;
+
+use std::{collections::HashSet, fmt::Debug};
+
+use async_trait::async_trait;
+
+/// Removes what a local snapshot makes obsolete from wherever the tangle and the ledger are persisted.
+#[async_trait]
+pub trait LocalSnapshotPruner: Send + Sync {
+    type Error: Debug;
+
+    /// Deletes the `transactions` pruned from the tangle, then the transactions confirmed below `index`, except the
+    /// `solid_entry_points`, and the milestones and state deltas below `index`.
+    async fn prune(
+        &self,
+        transactions: &HashSet<Hash>,
+        index: MilestoneIndex,
+        solid_entry_points: &HashSet<Hash>,
+    ) -> Result<(), Self::Error>;
+}
@@ -9,214 +9,252 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use crate::{LocalSnapshotMetadata, SnapshotState};
+use crate::{LocalSnapshotEntry, LocalSnapshotMetadata, LocalSnapshotStream, SnapshotState};
 
 use bee_crypto::ternary::Hash;
-use bee_ternary::{T1B1Buf, Trits, T5B1};
-use bee_transaction::bundled::{Address, BundledTransactionField};
-
-use std::collections::HashMap;
-
-use async_std::{fs::File, prelude::*};
+use bee_ternary::T5B1Buf;
+use bee_transaction::bundled::BundledTransactionField;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use async_std::{
+    fs::{rename, File},
+    io::BufWriter,
+    prelude::*,
+};
 use bytemuck::cast_slice;
 use log::info;
 
+fn encode_hash(hash: &Hash) -> Vec<u8> {
+    cast_slice(hash.as_trits().encode::<T5B1Buf>().as_i8_slice()).to_vec()
+}
+
 pub struct LocalSnapshot {
     metadata: LocalSnapshotMetadata,
     state: SnapshotState,
 }
 
-const VERSION: u8 = 4;
+pub(crate) const VERSION: u8 = 4;
 // TODO export ?
 pub const IOTA_SUPPLY: u64 = 2_779_530_283_277_761;
 
-// TODO detail errors
+/// Errors occurring while reading a snapshot file.
+/// Offsets are in bytes from the beginning of the file and entries are numbered from 0 within their section.
 #[derive(Debug)]
 pub enum SnapshotReadError {
     IOError(async_std::io::Error),
-    InvalidVersion,
-    InvalidMilestoneHash,
-    InvalidMilestoneIndex,
-    NonZeroSpentAddressesNumber,
-    InvalidSolidEntryPointHash,
-    InvalidSeenMilestoneHash,
-    InvalidAddress,
-    InvalidSupply,
+    Truncated { offset: u64 },
+    InvalidVersion(u8),
+    InvalidMilestoneHash { offset: u64 },
+    InvalidSolidEntryPointHash { offset: u64, entry: u32 },
+    InvalidSolidEntryPointIndex { offset: u64, entry: u32, index: u32 },
+    DuplicateSolidEntryPoint { offset: u64, entry: u32 },
+    InvalidSeenMilestoneHash { offset: u64, entry: u32 },
+    InvalidAddress { offset: u64, entry: u32 },
+    DuplicateAddress { offset: u64, entry: u32 },
+    InvalidSupply(u64),
+    InvalidSpentAddress { offset: u64, entry: u32 },
+    TrailingData { offset: u64 },
+    InvalidChecksum { expected: String, actual: String },
+}
+
+impl fmt::Display for SnapshotReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotReadError::IOError(e) => write!(f, "I/O error: {}", e),
+            SnapshotReadError::Truncated { offset } => write!(f, "file truncated at offset {}", offset),
+            SnapshotReadError::InvalidVersion(version) => {
+                write!(f, "invalid version {}, expected {}", version, VERSION)
+            }
+            SnapshotReadError::InvalidMilestoneHash { offset } => {
+                write!(f, "invalid milestone hash at offset {}", offset)
+            }
+            SnapshotReadError::InvalidSolidEntryPointHash { offset, entry } => {
+                write!(f, "invalid hash of solid entry point {} at offset {}", entry, offset)
+            }
+            SnapshotReadError::InvalidSolidEntryPointIndex { offset, entry, index } => write!(
+                f,
+                "index {} of solid entry point {} at offset {} is above the snapshot index",
+                index, entry, offset
+            ),
+            SnapshotReadError::DuplicateSolidEntryPoint { offset, entry } => {
+                write!(f, "duplicate solid entry point {} at offset {}", entry, offset)
+            }
+            SnapshotReadError::InvalidSeenMilestoneHash { offset, entry } => {
+                write!(f, "invalid hash of seen milestone {} at offset {}", entry, offset)
+            }
+            SnapshotReadError::InvalidAddress { offset, entry } => {
+                write!(f, "invalid address of balance {} at offset {}", entry, offset)
+            }
+            SnapshotReadError::DuplicateAddress { offset, entry } => {
+                write!(f, "duplicate address of balance {} at offset {}", entry, offset)
+            }
+            SnapshotReadError::InvalidSupply(supply) => {
+                write!(f, "invalid supply {}, expected {}", supply, IOTA_SUPPLY)
+            }
+            SnapshotReadError::InvalidSpentAddress { offset, entry } => {
+                write!(f, "invalid spent address {} at offset {}", entry, offset)
+            }
+            SnapshotReadError::TrailingData { offset } => write!(f, "unexpected data at offset {}", offset),
+            SnapshotReadError::InvalidChecksum { expected, actual } => {
+                write!(f, "invalid SHA-256 {}, expected {}", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotReadError {}
+
+#[derive(Debug)]
+pub enum SnapshotWriteError {
+    IOError(async_std::io::Error),
 }
+
 impl LocalSnapshot {
+    pub fn new(metadata: LocalSnapshotMetadata, state: SnapshotState) -> Self {
+        Self { metadata, state }
+    }
+
     pub async fn from_file(path: &str) -> Result<LocalSnapshot, SnapshotReadError> {
-        let mut file = File::open(path).await.map_err(|e| SnapshotReadError::IOError(e))?;
+        Self::read(path, false, None).await
+    }
 
-        // Version byte
+    /// Reads the snapshot like `from_file` but also makes sure that nothing trails the balances and, if `sha256` is
+    /// given as a hexadecimal string, that it matches the SHA-256 of the file.
+    pub async fn verify_file(path: &str, sha256: Option<&str>) -> Result<LocalSnapshot, SnapshotReadError> {
+        Self::read(path, true, sha256).await
+    }
 
-        let mut buf = [0u8];
-        match file.read_exact(&mut buf).await {
-            Ok(_) => {
-                if buf[0] != VERSION {
-                    return Err(SnapshotReadError::InvalidVersion);
+    async fn read(path: &str, verify: bool, sha256: Option<&str>) -> Result<LocalSnapshot, SnapshotReadError> {
+        let mut stream = LocalSnapshotStream::open_with(path, verify, sha256).await?;
+        let metadata = stream.read_metadata().await?;
+        // The counts of the header are not trusted to preallocate, a corrupted one would exhaust the memory.
+        let mut balances = HashMap::new();
+        let mut spent_addresses = HashSet::new();
+
+        loop {
+            let offset = stream.offset();
+            match stream.next_entry().await? {
+                Some(LocalSnapshotEntry::Balance(address, value)) => {
+                    if balances.insert(address, value).is_some() {
+                        return Err(SnapshotReadError::DuplicateAddress {
+                            offset,
+                            entry: balances.len() as u32,
+                        });
+                    }
+                }
+                Some(LocalSnapshotEntry::SpentAddress(address)) => {
+                    spent_addresses.insert(address);
                 }
+                Some(_) => {}
+                None => break,
             }
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        }
+
+        Ok(LocalSnapshot {
+            metadata,
+            state: SnapshotState {
+                balances,
+                spent_addresses,
+            },
+        })
+    }
+
+    /// Writes the snapshot to `path` in the layout read by `from_file`.
+    /// The snapshot is first written to a temporary file that is then renamed, so that a crash never leaves a
+    /// half-written snapshot at `path`.
+    pub async fn to_file(&self, path: &str) -> Result<(), SnapshotWriteError> {
+        let tmp_path = format!("{}.tmp", path);
+
+        self.write_file(&tmp_path).await.map_err(SnapshotWriteError::IOError)?;
+        rename(&tmp_path, path).await.map_err(SnapshotWriteError::IOError)?;
+
+        info!("Wrote snapshot file {} with index {}.", path, self.metadata.index);
+
+        Ok(())
+    }
+
+    async fn write_file(&self, path: &str) -> Result<(), async_std::io::Error> {
+        let mut writer = BufWriter::new(File::create(path).await?);
+
+        // Version byte
+
+        writer.write_all(&[VERSION]).await?;
 
         // Milestone hash
 
-        let mut buf = [0u8; 49];
-        let hash = match file.read_exact(&mut buf).await {
-            Ok(_) => match Trits::<T5B1>::try_from_raw(cast_slice(&buf), 243) {
-                Ok(trits) => {
-                    Hash::try_from_inner(trits.encode::<T1B1Buf>()).map_err(|_| SnapshotReadError::InvalidMilestoneHash)
-                }
-                Err(_) => Err(SnapshotReadError::InvalidMilestoneHash),
-            },
-            Err(e) => Err(SnapshotReadError::IOError(e)),
-        }?;
+        writer.write_all(&encode_hash(&self.metadata.hash)).await?;
 
         // Milestone index
 
-        let mut buf = [0u8; std::mem::size_of::<u32>()];
-        let index = match file.read_exact(&mut buf).await {
-            Ok(_) => u32::from_le_bytes(buf),
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        writer.write_all(&self.metadata.index.to_le_bytes()).await?;
 
         // Timestamp
 
-        let mut buf = [0u8; std::mem::size_of::<u64>()];
-        let timestamp = match file.read_exact(&mut buf).await {
-            Ok(_) => u64::from_le_bytes(buf),
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        writer.write_all(&self.metadata.timestamp.to_le_bytes()).await?;
 
         // Number of solid entry points
 
-        let mut buf = [0u8; std::mem::size_of::<u32>()];
-        let solid_entry_points_num = match file.read_exact(&mut buf).await {
-            Ok(_) => u32::from_le_bytes(buf),
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        writer
+            .write_all(&(self.metadata.solid_entry_points.len() as u32).to_le_bytes())
+            .await?;
 
         // Number of seen milestones
 
-        let mut buf = [0u8; std::mem::size_of::<u32>()];
-        let seen_milestones_num = match file.read_exact(&mut buf).await {
-            Ok(_) => u32::from_le_bytes(buf),
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        writer
+            .write_all(&(self.metadata.seen_milestones.len() as u32).to_le_bytes())
+            .await?;
 
         // Number of balances
 
-        let mut buf = [0u8; std::mem::size_of::<u32>()];
-        let balances_num = match file.read_exact(&mut buf).await {
-            Ok(_) => u32::from_le_bytes(buf),
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        writer
+            .write_all(&(self.state.balances.len() as u32).to_le_bytes())
+            .await?;
 
         // Number of spent addresses
 
-        let mut buf = [0u8; std::mem::size_of::<u32>()];
-        match file.read_exact(&mut buf).await {
-            Ok(_) => {
-                if u32::from_le_bytes(buf) != 0 {
-                    return Err(SnapshotReadError::NonZeroSpentAddressesNumber);
-                }
-            }
-            Err(e) => return Err(SnapshotReadError::IOError(e)),
-        };
+        writer
+            .write_all(&(self.state.spent_addresses.len() as u32).to_le_bytes())
+            .await?;
 
         // Solid entry points
 
-        let mut buf_hash = [0u8; 49];
-        let mut buf_index = [0u8; std::mem::size_of::<u32>()];
-        let mut solid_entry_points = HashMap::with_capacity(solid_entry_points_num as usize);
-        for _ in 0..solid_entry_points_num {
-            let hash = match file.read_exact(&mut buf_hash).await {
-                Ok(_) => match Trits::<T5B1>::try_from_raw(cast_slice(&buf_hash), 243) {
-                    Ok(trits) => Hash::try_from_inner(trits.encode::<T1B1Buf>())
-                        .map_err(|_| SnapshotReadError::InvalidSolidEntryPointHash),
-                    Err(_) => Err(SnapshotReadError::InvalidSolidEntryPointHash),
-                },
-                Err(e) => Err(SnapshotReadError::IOError(e)),
-            }?;
-            let index = match file.read_exact(&mut buf_index).await {
-                Ok(_) => u32::from_le_bytes(buf_index),
-                Err(e) => return Err(SnapshotReadError::IOError(e)),
-            };
-            solid_entry_points.insert(hash, index);
+        for (hash, index) in self.metadata.solid_entry_points.iter() {
+            writer.write_all(&encode_hash(hash)).await?;
+            writer.write_all(&index.to_le_bytes()).await?;
         }
 
         // Seen milestones
 
-        let mut buf_hash = [0u8; 49];
-        let mut buf_index = [0u8; std::mem::size_of::<u32>()];
-        let mut seen_milestones = Vec::with_capacity(seen_milestones_num as usize);
-        for _ in 0..seen_milestones_num {
-            let seen_milestone = match file.read_exact(&mut buf_hash).await {
-                Ok(_) => match Trits::<T5B1>::try_from_raw(cast_slice(&buf_hash), 243) {
-                    Ok(trits) => Hash::try_from_inner(trits.encode::<T1B1Buf>())
-                        .map_err(|_| SnapshotReadError::InvalidSeenMilestoneHash),
-                    Err(_) => Err(SnapshotReadError::InvalidSeenMilestoneHash),
-                },
-                Err(e) => Err(SnapshotReadError::IOError(e)),
-            }?;
-            seen_milestones.push(seen_milestone);
-            // TODO should we use that ?
-            match file.read_exact(&mut buf_index).await {
-                Ok(_) => u32::from_le_bytes(buf_index),
-                Err(e) => return Err(SnapshotReadError::IOError(e)),
-            };
+        for (hash, index) in self.metadata.seen_milestones.iter() {
+            writer.write_all(&encode_hash(hash)).await?;
+            writer.write_all(&index.to_le_bytes()).await?;
         }
 
-        // amountOfBalances * balance:value - 49 bytes + int64
-
-        let mut buf_address = [0u8; 49];
-        let mut buf_value = [0u8; std::mem::size_of::<u64>()];
-        let mut balances = HashMap::with_capacity(balances_num as usize);
-        let mut supply: u64 = 0;
-        for i in 0..balances_num {
-            let address = match file.read_exact(&mut buf_address).await {
-                Ok(_) => match Trits::<T5B1>::try_from_raw(cast_slice(&buf_address), 243) {
-                    Ok(trits) => Address::try_from_inner(trits.encode::<T1B1Buf>())
-                        .map_err(|_| SnapshotReadError::InvalidAddress),
-                    Err(_) => Err(SnapshotReadError::InvalidAddress),
-                },
-                Err(e) => Err(SnapshotReadError::IOError(e)),
-            }?;
-            let value = match file.read_exact(&mut buf_value).await {
-                Ok(_) => u64::from_le_bytes(buf_value),
-                Err(e) => return Err(SnapshotReadError::IOError(e)),
-            };
-
-            if i % 10_000 == 0 && i != 0 {
-                info!(
-                    "Read {}/{} ({:.0}%) balances.",
-                    i,
-                    balances_num,
-                    ((i * 100) as f64) / (balances_num as f64)
-                );
-            }
+        // Balances
 
-            balances.insert(address, value);
-            supply += value;
+        for (address, value) in self.state.balances.iter() {
+            writer
+                .write_all(cast_slice(address.to_inner().encode::<T5B1Buf>().as_i8_slice()))
+                .await?;
+            writer.write_all(&value.to_le_bytes()).await?;
         }
 
-        if supply != IOTA_SUPPLY {
-            return Err(SnapshotReadError::InvalidSupply);
+        // Spent addresses
+
+        for address in self.state.spent_addresses.iter() {
+            writer
+                .write_all(cast_slice(address.to_inner().encode::<T5B1Buf>().as_i8_slice()))
+                .await?;
         }
 
-        // TODO spend addresses ?
-        // TODO hash
+        writer.flush().await?;
+        writer.get_ref().sync_all().await?;
 
-        Ok(LocalSnapshot {
-            metadata: LocalSnapshotMetadata {
-                hash,
-                index,
-                timestamp,
-                solid_entry_points,
-                seen_milestones,
-            },
-            state: SnapshotState { balances },
-        })
+        Ok(())
     }
 
     pub fn metadata(&self) -> &LocalSnapshotMetadata {
@@ -230,4 +268,185 @@ impl LocalSnapshot {
     pub fn into_state(self) -> SnapshotState {
         self.state
     }
+
+    pub fn into_parts(self) -> (LocalSnapshotMetadata, SnapshotState) {
+        (self.metadata, self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use bee_test::field::rand_trits_field;
+    use bee_transaction::bundled::Address;
+
+    use async_std::task::block_on;
+    use futures::stream::TryStreamExt;
+    use sha2::{Digest, Sha256};
+
+    use std::fs::{read, remove_file, OpenOptions};
+
+    fn create_snapshot() -> LocalSnapshot {
+        let mut solid_entry_points = HashMap::new();
+        let mut seen_milestones = HashMap::new();
+        let mut balances = HashMap::new();
+        let mut spent_addresses = HashSet::new();
+
+        for i in 0..10 {
+            solid_entry_points.insert(rand_trits_field::<Hash>(), 100 - i);
+            seen_milestones.insert(rand_trits_field::<Hash>(), 100 + i);
+            balances.insert(rand_trits_field::<Address>(), IOTA_SUPPLY / 10);
+            spent_addresses.insert(rand_trits_field::<Address>());
+        }
+        // Makes up for the rounding of the supply division.
+        balances.insert(rand_trits_field::<Address>(), IOTA_SUPPLY % 10);
+
+        LocalSnapshot::new(
+            LocalSnapshotMetadata::new(
+                rand_trits_field::<Hash>(),
+                100,
+                1_593_606_000,
+                solid_entry_points,
+                seen_milestones,
+            ),
+            SnapshotState::new(balances, spent_addresses),
+        )
+    }
+
+    fn snapshot_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("{}_{}.bin", name, std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn write_read_round_trip() {
+        let snapshot = create_snapshot();
+        let path = snapshot_path("round_trip");
+
+        block_on(snapshot.to_file(&path)).unwrap();
+        let read = block_on(LocalSnapshot::from_file(&path)).unwrap();
+        remove_file(&path).unwrap();
+
+        assert_eq!(read.metadata().hash(), snapshot.metadata().hash());
+        assert_eq!(read.metadata().index(), snapshot.metadata().index());
+        assert_eq!(read.metadata().timestamp(), snapshot.metadata().timestamp());
+        assert_eq!(
+            read.metadata().solid_entry_points(),
+            snapshot.metadata().solid_entry_points()
+        );
+        assert_eq!(read.metadata().seen_milestones(), snapshot.metadata().seen_milestones());
+        assert_eq!(read.state().balances(), snapshot.state().balances());
+        assert_eq!(read.state().spent_addresses(), snapshot.state().spent_addresses());
+    }
+
+    #[test]
+    fn stream_entries() {
+        let snapshot = create_snapshot();
+        let path = snapshot_path("stream");
+
+        block_on(snapshot.to_file(&path)).unwrap();
+        let stream = block_on(LocalSnapshotStream::open(&path)).unwrap();
+        assert_eq!(stream.header().index(), snapshot.metadata().index());
+        assert_eq!(
+            stream.header().balances_num() as usize,
+            snapshot.state().balances().len()
+        );
+        let entries = block_on(stream.into_stream().try_collect::<Vec<LocalSnapshotEntry>>()).unwrap();
+        remove_file(&path).unwrap();
+
+        let mut solid_entry_points = HashMap::new();
+        let mut balances = HashMap::new();
+        let mut spent_addresses = HashSet::new();
+        for (i, entry) in entries.into_iter().enumerate() {
+            match entry {
+                LocalSnapshotEntry::SolidEntryPoint(hash, index) => {
+                    assert!(i < 10);
+                    solid_entry_points.insert(hash, index);
+                }
+                LocalSnapshotEntry::SeenMilestone(..) => assert!((10..20).contains(&i)),
+                LocalSnapshotEntry::Balance(address, value) => {
+                    assert!((20..31).contains(&i));
+                    balances.insert(address, value);
+                }
+                LocalSnapshotEntry::SpentAddress(address) => {
+                    assert!(i >= 31);
+                    spent_addresses.insert(address);
+                }
+            }
+        }
+
+        assert_eq!(&solid_entry_points, snapshot.metadata().solid_entry_points());
+        assert_eq!(&balances, snapshot.state().balances());
+        assert_eq!(&spent_addresses, snapshot.state().spent_addresses());
+    }
+
+    #[test]
+    fn read_truncated() {
+        let path = snapshot_path("truncated");
+
+        block_on(create_snapshot().to_file(&path)).unwrap();
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len - 3).unwrap();
+        let result = block_on(LocalSnapshot::from_file(&path));
+        remove_file(&path).unwrap();
+
+        // The last spent address is cut.
+        match result {
+            Err(SnapshotReadError::Truncated { offset }) => assert_eq!(offset, len - 49),
+            _ => panic!("expected a truncated snapshot"),
+        }
+    }
+
+    #[test]
+    fn read_invalid_solid_entry_point_index() {
+        let mut snapshot = create_snapshot();
+        let path = snapshot_path("invalid_sep_index");
+
+        snapshot
+            .metadata
+            .solid_entry_points
+            .insert(rand_trits_field::<Hash>(), 101);
+        block_on(snapshot.to_file(&path)).unwrap();
+        let result = block_on(LocalSnapshot::from_file(&path));
+        remove_file(&path).unwrap();
+
+        match result {
+            Err(SnapshotReadError::InvalidSolidEntryPointIndex { index, .. }) => assert_eq!(index, 101),
+            _ => panic!("expected an invalid solid entry point index"),
+        }
+    }
+
+    #[test]
+    fn verify_checksum_and_trailing_data() {
+        let path = snapshot_path("verify");
+
+        block_on(create_snapshot().to_file(&path)).unwrap();
+        let sha256 = Sha256::digest(&read(&path).unwrap())
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<String>();
+
+        assert!(block_on(LocalSnapshot::verify_file(&path, Some(&sha256))).is_ok());
+        match block_on(LocalSnapshot::verify_file(&path, Some("00"))) {
+            Err(SnapshotReadError::InvalidChecksum { actual, .. }) => assert!(actual.eq_ignore_ascii_case(&sha256)),
+            _ => panic!("expected an invalid checksum"),
+        }
+
+        let file = OpenOptions::new().write(true).open(&path).unwrap();
+        let len = file.metadata().unwrap().len();
+        file.set_len(len + 1).unwrap();
+        let result = block_on(LocalSnapshot::verify_file(&path, None));
+        remove_file(&path).unwrap();
+
+        match result {
+            Err(SnapshotReadError::TrailingData { offset }) => assert_eq!(offset, len),
+            _ => panic!("expected trailing data"),
+        }
+    }
 }
@@ -0,0 +1,426 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::{
+    local::snapshot::{SnapshotReadError, IOTA_SUPPLY, VERSION},
+    LocalSnapshotMetadata,
+};
+
+use bee_crypto::ternary::Hash;
+use bee_ledger::LedgerSnapshotEntry;
+use bee_ternary::{T1B1Buf, Trits, T5B1};
+use bee_transaction::bundled::{Address, BundledTransactionField};
+
+use async_std::{
+    fs::File,
+    io::{BufReader, ErrorKind},
+    prelude::*,
+};
+use bytemuck::cast_slice;
+use futures::stream::{self, Stream, StreamExt};
+use log::info;
+use sha2::{Digest, Sha256};
+
+use std::collections::HashMap;
+
+// Reads a snapshot file while keeping track of the offset and, if needed, of the SHA-256 of the read bytes.
+struct SnapshotReader {
+    reader: BufReader<File>,
+    offset: u64,
+    sha256: Option<Sha256>,
+}
+
+impl SnapshotReader {
+    async fn open(path: &str, sha256: bool) -> Result<Self, SnapshotReadError> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path).await.map_err(SnapshotReadError::IOError)?),
+            offset: 0,
+            sha256: if sha256 { Some(Sha256::new()) } else { None },
+        })
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), SnapshotReadError> {
+        match self.reader.read_exact(buf).await {
+            Ok(_) => {
+                if let Some(sha256) = self.sha256.as_mut() {
+                    sha256.update(&buf);
+                }
+                self.offset += buf.len() as u64;
+                Ok(())
+            }
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Err(SnapshotReadError::Truncated { offset: self.offset }),
+            Err(e) => Err(SnapshotReadError::IOError(e)),
+        }
+    }
+
+    async fn read_u32(&mut self) -> Result<u32, SnapshotReadError> {
+        let mut buf = [0u8; std::mem::size_of::<u32>()];
+        self.read_exact(&mut buf).await?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    async fn read_u64(&mut self) -> Result<u64, SnapshotReadError> {
+        let mut buf = [0u8; std::mem::size_of::<u64>()];
+        self.read_exact(&mut buf).await?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    // Returns `None` if the bytes don't encode a valid hash.
+    async fn read_hash(&mut self) -> Result<Option<Hash>, SnapshotReadError> {
+        let mut buf = [0u8; 49];
+        self.read_exact(&mut buf).await?;
+        Ok(Trits::<T5B1>::try_from_raw(cast_slice(&buf), 243)
+            .ok()
+            .and_then(|trits| Hash::try_from_inner(trits.encode::<T1B1Buf>()).ok()))
+    }
+
+    // Returns `None` if the bytes don't encode a valid address.
+    async fn read_address(&mut self) -> Result<Option<Address>, SnapshotReadError> {
+        let mut buf = [0u8; 49];
+        self.read_exact(&mut buf).await?;
+        Ok(Trits::<T5B1>::try_from_raw(cast_slice(&buf), 243)
+            .ok()
+            .and_then(|trits| Address::try_from_inner(trits.encode::<T1B1Buf>()).ok()))
+    }
+}
+
+/// The fixed-size part of a snapshot file, preceding its entries.
+#[derive(Clone)]
+pub struct LocalSnapshotHeader {
+    hash: Hash,
+    index: u32,
+    timestamp: u64,
+    solid_entry_points_num: u32,
+    seen_milestones_num: u32,
+    balances_num: u32,
+    spent_addresses_num: u32,
+}
+
+impl LocalSnapshotHeader {
+    pub fn hash(&self) -> &Hash {
+        &self.hash
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn solid_entry_points_num(&self) -> u32 {
+        self.solid_entry_points_num
+    }
+
+    pub fn seen_milestones_num(&self) -> u32 {
+        self.seen_milestones_num
+    }
+
+    pub fn balances_num(&self) -> u32 {
+        self.balances_num
+    }
+
+    pub fn spent_addresses_num(&self) -> u32 {
+        self.spent_addresses_num
+    }
+}
+
+/// An entry of a snapshot file, in the order of the file: solid entry points, seen milestones, balances, then spent
+/// addresses.
+pub enum LocalSnapshotEntry {
+    SolidEntryPoint(Hash, u32),
+    SeenMilestone(Hash, u32),
+    Balance(Address, u64),
+    SpentAddress(Address),
+}
+
+/// Reads a snapshot file entry by entry, so that it can be processed without holding it in memory.
+/// Unlike `LocalSnapshot::from_file`, duplicate solid entry points and addresses are not detected, which would
+/// require keeping track of all of them; the supply is still checked once all the balances have been read.
+pub struct LocalSnapshotStream {
+    reader: SnapshotReader,
+    header: LocalSnapshotHeader,
+    entry: u64,
+    supply: u64,
+    verify: bool,
+    sha256: Option<String>,
+    done: bool,
+}
+
+impl LocalSnapshotStream {
+    pub async fn open(path: &str) -> Result<Self, SnapshotReadError> {
+        Self::open_with(path, false, None).await
+    }
+
+    /// Opens the snapshot like `open` but also makes sure, after the last entry, that nothing trails the balances
+    /// and, if `sha256` is given as a hexadecimal string, that it matches the SHA-256 of the file.
+    pub async fn verify(path: &str, sha256: Option<&str>) -> Result<Self, SnapshotReadError> {
+        Self::open_with(path, true, sha256).await
+    }
+
+    pub(crate) async fn open_with(path: &str, verify: bool, sha256: Option<&str>) -> Result<Self, SnapshotReadError> {
+        let mut reader = SnapshotReader::open(path, sha256.is_some()).await?;
+
+        // Version byte
+
+        let mut buf = [0u8];
+        reader.read_exact(&mut buf).await?;
+        if buf[0] != VERSION {
+            return Err(SnapshotReadError::InvalidVersion(buf[0]));
+        }
+
+        // Milestone hash
+
+        let offset = reader.offset;
+        let hash = reader
+            .read_hash()
+            .await?
+            .ok_or(SnapshotReadError::InvalidMilestoneHash { offset })?;
+
+        // Milestone index
+
+        let index = reader.read_u32().await?;
+
+        // Timestamp
+
+        let timestamp = reader.read_u64().await?;
+
+        // Number of solid entry points
+
+        let solid_entry_points_num = reader.read_u32().await?;
+
+        // Number of seen milestones
+
+        let seen_milestones_num = reader.read_u32().await?;
+
+        // Number of balances
+
+        let balances_num = reader.read_u32().await?;
+
+        // Number of spent addresses
+
+        let spent_addresses_num = reader.read_u32().await?;
+
+        Ok(Self {
+            reader,
+            header: LocalSnapshotHeader {
+                hash,
+                index,
+                timestamp,
+                solid_entry_points_num,
+                seen_milestones_num,
+                balances_num,
+                spent_addresses_num,
+            },
+            entry: 0,
+            supply: 0,
+            verify,
+            sha256: sha256.map(|sha256| sha256.to_string()),
+            done: false,
+        })
+    }
+
+    pub fn header(&self) -> &LocalSnapshotHeader {
+        &self.header
+    }
+
+    /// Reads the solid entry points and the seen milestones, which precede the ledger entries, and checks the former
+    /// for duplicates. Must be called before any other entry is read.
+    pub async fn read_metadata(&mut self) -> Result<LocalSnapshotMetadata, SnapshotReadError> {
+        let metadata_end = self.header.solid_entry_points_num as u64 + self.header.seen_milestones_num as u64;
+        let mut solid_entry_points = HashMap::new();
+        let mut seen_milestones = HashMap::new();
+
+        while self.entry < metadata_end {
+            let offset = self.reader.offset;
+            match self.next_entry().await? {
+                Some(LocalSnapshotEntry::SolidEntryPoint(hash, index)) => {
+                    if solid_entry_points.insert(hash, index).is_some() {
+                        return Err(SnapshotReadError::DuplicateSolidEntryPoint {
+                            offset,
+                            entry: solid_entry_points.len() as u32,
+                        });
+                    }
+                }
+                Some(LocalSnapshotEntry::SeenMilestone(hash, index)) => {
+                    seen_milestones.insert(hash, index);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(LocalSnapshotMetadata {
+            hash: self.header.hash,
+            index: self.header.index,
+            timestamp: self.header.timestamp,
+            solid_entry_points,
+            seen_milestones,
+        })
+    }
+
+    /// Offset in bytes of the next entry.
+    pub fn offset(&self) -> u64 {
+        self.reader.offset
+    }
+
+    /// Returns the next entry or, once all of them have been read and checked, `None`.
+    pub async fn next_entry(&mut self) -> Result<Option<LocalSnapshotEntry>, SnapshotReadError> {
+        let solid_entry_points_end = self.header.solid_entry_points_num as u64;
+        let seen_milestones_end = solid_entry_points_end + self.header.seen_milestones_num as u64;
+        let balances_end = seen_milestones_end + self.header.balances_num as u64;
+        let spent_addresses_end = balances_end + self.header.spent_addresses_num as u64;
+        let offset = self.reader.offset;
+
+        let next = if self.entry < solid_entry_points_end {
+            // Solid entry points
+
+            let entry = self.entry as u32;
+            let hash = self
+                .reader
+                .read_hash()
+                .await?
+                .ok_or(SnapshotReadError::InvalidSolidEntryPointHash { offset, entry })?;
+            let index = self.reader.read_u32().await?;
+            if index > self.header.index {
+                return Err(SnapshotReadError::InvalidSolidEntryPointIndex { offset, entry, index });
+            }
+
+            LocalSnapshotEntry::SolidEntryPoint(hash, index)
+        } else if self.entry < seen_milestones_end {
+            // Seen milestones
+
+            let entry = (self.entry - solid_entry_points_end) as u32;
+            let hash = self
+                .reader
+                .read_hash()
+                .await?
+                .ok_or(SnapshotReadError::InvalidSeenMilestoneHash { offset, entry })?;
+            let index = self.reader.read_u32().await?;
+
+            LocalSnapshotEntry::SeenMilestone(hash, index)
+        } else if self.entry < balances_end {
+            // amountOfBalances * balance:value - 49 bytes + int64
+
+            let entry = (self.entry - seen_milestones_end) as u32;
+            let address = self
+                .reader
+                .read_address()
+                .await?
+                .ok_or(SnapshotReadError::InvalidAddress { offset, entry })?;
+            let value = self.reader.read_u64().await?;
+
+            if entry % 10_000 == 0 && entry != 0 {
+                info!(
+                    "Read {}/{} ({:.0}%) balances.",
+                    entry,
+                    self.header.balances_num,
+                    ((entry as u64 * 100) as f64) / (self.header.balances_num as f64)
+                );
+            }
+
+            self.supply = self.supply.saturating_add(value);
+
+            LocalSnapshotEntry::Balance(address, value)
+        } else if self.entry < spent_addresses_end {
+            // Spent addresses
+
+            if self.entry == balances_end {
+                self.check_supply()?;
+            }
+
+            let entry = (self.entry - balances_end) as u32;
+            let address = self
+                .reader
+                .read_address()
+                .await?
+                .ok_or(SnapshotReadError::InvalidSpentAddress { offset, entry })?;
+
+            LocalSnapshotEntry::SpentAddress(address)
+        } else {
+            if !self.done {
+                self.done = true;
+                self.finish().await?;
+            }
+            return Ok(None);
+        };
+
+        self.entry += 1;
+
+        Ok(Some(next))
+    }
+
+    fn check_supply(&self) -> Result<(), SnapshotReadError> {
+        if self.supply != IOTA_SUPPLY {
+            return Err(SnapshotReadError::InvalidSupply(self.supply));
+        }
+
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<(), SnapshotReadError> {
+        if self.header.spent_addresses_num == 0 {
+            self.check_supply()?;
+        }
+
+        if self.verify {
+            let offset = self.reader.offset;
+            match self.reader.read_exact(&mut [0u8]).await {
+                Ok(_) => return Err(SnapshotReadError::TrailingData { offset }),
+                Err(SnapshotReadError::Truncated { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let (Some(expected), Some(sha256)) = (self.sha256.as_ref(), self.reader.sha256.take()) {
+            let actual = sha256
+                .finalize()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            if !actual.eq_ignore_ascii_case(expected) {
+                return Err(SnapshotReadError::InvalidChecksum {
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Turns the remaining balances and spent addresses into the stream the ledger is bootstrapped from, once the
+    /// metadata has been read.
+    pub fn into_ledger_stream(self) -> impl Stream<Item = Result<LedgerSnapshotEntry, SnapshotReadError>> {
+        self.into_stream().filter_map(|entry| async move {
+            match entry {
+                Ok(LocalSnapshotEntry::Balance(address, value)) => {
+                    Some(Ok(LedgerSnapshotEntry::Balance(address, value)))
+                }
+                Ok(LocalSnapshotEntry::SpentAddress(address)) => Some(Ok(LedgerSnapshotEntry::SpentAddress(address))),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+
+    /// Turns the snapshot into a stream of entries that ends after the first error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<LocalSnapshotEntry, SnapshotReadError>> {
+        stream::unfold(Some(self), |snapshot| async move {
+            let mut snapshot = snapshot?;
+            match snapshot.next_entry().await {
+                Ok(Some(entry)) => Some((Ok(entry), Some(snapshot))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+}
@@ -0,0 +1,363 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::{
+    LocalSnapshot, LocalSnapshotConfig, LocalSnapshotMetadata, LocalSnapshotPruner, SnapshotState, SnapshotWriteError,
+};
+
+use bee_crypto::ternary::Hash;
+use bee_ledger::LedgerWorkerEvent;
+use bee_protocol::{
+    tangle::{tangle, MsTangle},
+    MilestoneIndex,
+};
+use bee_transaction::bundled::BundledTransactionField;
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    future::FutureExt,
+    select,
+    sink::SinkExt,
+    stream::StreamExt,
+};
+use log::{error, info, warn};
+
+#[derive(Debug)]
+enum Error {
+    MissingMilestone,
+    MissingBalances,
+    LedgerUnreachable,
+    Write(SnapshotWriteError),
+}
+
+/// Notifies the worker that the ledger confirmed the milestone of the given index.
+pub struct LocalSnapshotWorkerEvent(pub MilestoneIndex);
+
+/// Periodically takes a local snapshot `depth` milestones below the confirmed one and prunes the tangle, the ledger
+/// and, through the `pruner`, the storage below it. The balances of the snapshot are queried from the ledger.
+pub struct LocalSnapshotWorker<P: LocalSnapshotPruner> {
+    config: LocalSnapshotConfig,
+    ledger: mpsc::Sender<LedgerWorkerEvent>,
+    pruner: Arc<P>,
+}
+
+impl<P: LocalSnapshotPruner> LocalSnapshotWorker<P> {
+    pub fn new(config: LocalSnapshotConfig, ledger: mpsc::Sender<LedgerWorkerEvent>, pruner: Arc<P>) -> Self {
+        Self { config, ledger, pruner }
+    }
+
+    async fn process(&mut self, confirmed_index: MilestoneIndex) {
+        let snapshot_index = tangle().get_snapshot_milestone_index();
+        let target_index = MilestoneIndex(confirmed_index.saturating_sub(self.config.depth()));
+
+        if target_index <= snapshot_index || *target_index - *snapshot_index < self.config.interval() {
+            return;
+        }
+
+        // The current solid entry points and the transactions confirmed since the last snapshot are the candidates to
+        // be the solid entry points of the new one; the others get pruned.
+        let mut candidates = tangle().get_solid_entry_points();
+        for hash in confirmed_cone(tangle(), snapshot_index, target_index) {
+            if let Some(metadata) = tangle().get_metadata(&hash) {
+                candidates.insert(hash, metadata.milestone_index());
+            }
+        }
+
+        if let Err(e) = self.take_snapshot(target_index, candidates.clone()).await {
+            error!("Taking local snapshot at milestone {} failed: {:?}.", *target_index, e);
+            return;
+        }
+
+        self.prune(
+            snapshot_index,
+            target_index,
+            candidates.into_iter().map(|(hash, _)| hash),
+        )
+        .await;
+    }
+
+    async fn take_snapshot(
+        &mut self,
+        index: MilestoneIndex,
+        candidates: HashMap<Hash, MilestoneIndex>,
+    ) -> Result<(), Error> {
+        let hash = tangle().get_milestone_hash(index).ok_or(Error::MissingMilestone)?;
+        let timestamp = *tangle()
+            .fetch(&hash)
+            .await
+            .ok_or(Error::MissingMilestone)?
+            .timestamp()
+            .to_inner();
+
+        let (balances_tx, balances_rx) = oneshot::channel();
+        self.ledger
+            .send(LedgerWorkerEvent::GetBalancesAt(index, balances_tx))
+            .await
+            .map_err(|_| Error::LedgerUnreachable)?;
+        let balances = balances_rx
+            .await
+            .map_err(|_| Error::LedgerUnreachable)?
+            .ok_or(Error::MissingBalances)?;
+
+        let (spent_addresses_tx, spent_addresses_rx) = oneshot::channel();
+        self.ledger
+            .send(LedgerWorkerEvent::GetSpentAddresses(spent_addresses_tx))
+            .await
+            .map_err(|_| Error::LedgerUnreachable)?;
+        let spent_addresses = spent_addresses_rx.await.map_err(|_| Error::LedgerUnreachable)?;
+
+        let mut solid_entry_points = solid_entry_points(tangle(), candidates, index);
+        solid_entry_points.insert(hash, index);
+        solid_entry_points.remove(&Hash::zeros());
+
+        let mut seen_milestones = HashMap::new();
+        for seen_index in *index + 1..=*tangle().get_last_milestone_index() {
+            if let Some(hash) = tangle().get_milestone_hash(MilestoneIndex(seen_index)) {
+                seen_milestones.insert(hash, seen_index);
+            }
+        }
+
+        let snapshot = LocalSnapshot::new(
+            LocalSnapshotMetadata::new(
+                hash,
+                *index,
+                timestamp,
+                solid_entry_points
+                    .iter()
+                    .map(|(hash, index)| (*hash, **index))
+                    .collect(),
+                seen_milestones,
+            ),
+            SnapshotState::new(balances, spent_addresses),
+        );
+
+        snapshot.to_file(self.config.file_path()).await.map_err(Error::Write)?;
+
+        // New solid entry points are added before the transactions they replace get pruned.
+        for (hash, index) in solid_entry_points.iter() {
+            tangle().add_solid_entry_point(*hash, *index);
+        }
+        for (hash, _) in tangle().get_solid_entry_points() {
+            if hash != Hash::zeros() && !solid_entry_points.contains_key(&hash) {
+                tangle().remove_solid_entry_point(&hash);
+            }
+        }
+        tangle().update_snapshot_milestone_index(index);
+
+        info!(
+            "Took local snapshot at milestone {} with {} solid entry points and {} balances.",
+            *index,
+            solid_entry_points.len(),
+            snapshot.state().balances().len()
+        );
+
+        Ok(())
+    }
+
+    async fn prune(&mut self, from: MilestoneIndex, to: MilestoneIndex, candidates: impl Iterator<Item = Hash>) {
+        let transactions = prunable_transactions(tangle(), candidates);
+        let mut milestones = HashSet::new();
+
+        for index in *from..*to {
+            if let Some(hash) = tangle().get_milestone_hash(MilestoneIndex(index)) {
+                tangle().remove_milestone(MilestoneIndex(index));
+                milestones.insert(hash);
+            }
+        }
+
+        // Removing a transaction takes it out of the tips and makes the remaining parents it was the last child of tips
+        // again.
+        for hash in transactions.iter() {
+            tangle().remove(hash);
+        }
+
+        let solid_entry_points = tangle().get_solid_entry_points().keys().copied().collect();
+        if let Err(e) = self.pruner.prune(&transactions, to, &solid_entry_points).await {
+            warn!("Pruning storage below milestone {} failed: {:?}.", *to, e);
+        }
+
+        // The Merkle roots of the pruned milestones go along with their state deltas.
+        if let Err(e) = self.ledger.send(LedgerWorkerEvent::Prune(to)).await {
+            warn!("Pruning ledger below milestone {} failed: {:?}.", *to, e);
+        }
+
+        info!(
+            "Pruned {} transaction(s) and {} milestone(s) below milestone {}.",
+            transactions.len(),
+            milestones.len(),
+            *to
+        );
+    }
+
+    pub async fn run(mut self, receiver: mpsc::Receiver<LocalSnapshotWorkerEvent>, shutdown: oneshot::Receiver<()>) {
+        info!("Running.");
+
+        let mut receiver_fused = receiver.fuse();
+        let mut shutdown_fused = shutdown.fuse();
+
+        loop {
+            select! {
+                event = receiver_fused.next() => {
+                    if let Some(LocalSnapshotWorkerEvent(index)) = event {
+                        self.process(index).await;
+                    }
+                },
+                _ = shutdown_fused => {
+                    break;
+                }
+            }
+        }
+
+        info!("Stopped.");
+    }
+}
+
+// Transactions confirmed by the milestones in `(from, to]`, walked from the milestones down to the solid entry points.
+fn confirmed_cone(tangle: &MsTangle, from: MilestoneIndex, to: MilestoneIndex) -> Vec<Hash> {
+    let mut transactions = Vec::new();
+    let mut visited = HashSet::new();
+    let mut hashes = (*from + 1..=*to)
+        .filter_map(|index| tangle.get_milestone_hash(MilestoneIndex(index)))
+        .collect::<Vec<Hash>>();
+
+    while let Some(hash) = hashes.pop() {
+        if !visited.insert(hash) || tangle.is_solid_entry_point(&hash) {
+            continue;
+        }
+
+        if !tangle.get_metadata(&hash).map_or(false, |metadata| {
+            metadata.flags().is_confirmed() && metadata.milestone_index() > from && metadata.milestone_index() <= to
+        }) {
+            continue;
+        }
+
+        if let Some((trunk, branch)) = tangle.get_parents(&hash) {
+            hashes.push(trunk);
+            hashes.push(branch);
+        }
+
+        transactions.push(hash);
+    }
+
+    transactions
+}
+
+// Among the candidates, the ones approved by a transaction confirmed by a milestone above `index` are solid entry
+// points, since the tangle above `index` can't be solidified without them.
+fn solid_entry_points(
+    tangle: &MsTangle,
+    candidates: HashMap<Hash, MilestoneIndex>,
+    index: MilestoneIndex,
+) -> HashMap<Hash, MilestoneIndex> {
+    candidates
+        .into_iter()
+        .filter(|(hash, _)| {
+            tangle.get_children(hash).iter().any(|child| {
+                tangle.get_metadata(child).map_or(false, |metadata| {
+                    metadata.flags().is_confirmed() && metadata.milestone_index() > index
+                })
+            })
+        })
+        .collect()
+}
+
+// Candidates that didn't become solid entry points, along with the unconfirmed transactions approving them; the latter
+// are below max depth and will never be confirmed.
+// Must be called once the solid entry points of the new snapshot are set.
+fn prunable_transactions(tangle: &MsTangle, candidates: impl Iterator<Item = Hash>) -> HashSet<Hash> {
+    let mut transactions = HashSet::new();
+    let mut hashes = candidates.filter(|hash| tangle.contains(hash)).collect::<Vec<Hash>>();
+
+    while let Some(hash) = hashes.pop() {
+        if tangle.is_solid_entry_point(&hash) || !transactions.insert(hash) {
+            continue;
+        }
+
+        for child in tangle.get_children(&hash) {
+            if !tangle
+                .get_metadata(&child)
+                .map_or(true, |metadata| metadata.flags().is_confirmed())
+            {
+                hashes.push(child);
+            }
+        }
+    }
+
+    transactions
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use bee_protocol::tangle::TransactionMetadata;
+    use bee_test::{field::rand_trits_field, transaction::create_random_attached_tx};
+
+    #[test]
+    fn solid_entry_points_and_pruning() {
+        let tangle = MsTangle::new();
+
+        let sep = rand_trits_field::<Hash>();
+        tangle.add_solid_entry_point(sep, MilestoneIndex(0));
+
+        // a <- b <- ms1 <- c <- ms2, d is an orphan approving a.
+        let (a_hash, a) = create_random_attached_tx(sep, sep);
+        let (b_hash, b) = create_random_attached_tx(a_hash, a_hash);
+        let (ms1_hash, ms1) = create_random_attached_tx(b_hash, b_hash);
+        let (c_hash, c) = create_random_attached_tx(ms1_hash, ms1_hash);
+        let (ms2_hash, ms2) = create_random_attached_tx(c_hash, c_hash);
+        let (d_hash, d) = create_random_attached_tx(a_hash, a_hash);
+
+        for (hash, transaction) in vec![
+            (a_hash, a),
+            (b_hash, b),
+            (ms1_hash, ms1),
+            (c_hash, c),
+            (ms2_hash, ms2),
+            (d_hash, d),
+        ] {
+            tangle.insert(transaction, hash, TransactionMetadata::new());
+        }
+        for hash in [a_hash, b_hash, ms1_hash].iter() {
+            tangle.update_metadata(hash, |metadata| metadata.confirm(MilestoneIndex(1)));
+        }
+        for hash in [c_hash, ms2_hash].iter() {
+            tangle.update_metadata(hash, |metadata| metadata.confirm(MilestoneIndex(2)));
+        }
+        tangle.add_milestone(MilestoneIndex(1), ms1_hash);
+        tangle.add_milestone(MilestoneIndex(2), ms2_hash);
+
+        let mut candidates = tangle.get_solid_entry_points();
+        for hash in confirmed_cone(&tangle, MilestoneIndex(0), MilestoneIndex(1)) {
+            candidates.insert(hash, tangle.get_metadata(&hash).unwrap().milestone_index());
+        }
+        assert_eq!(candidates.len(), 4);
+
+        let solid_entry_points = solid_entry_points(&tangle, candidates.clone(), MilestoneIndex(1));
+        assert_eq!(solid_entry_points.len(), 1);
+        assert_eq!(solid_entry_points.get(&ms1_hash), Some(&MilestoneIndex(1)));
+
+        tangle.add_solid_entry_point(ms1_hash, MilestoneIndex(1));
+        tangle.remove_solid_entry_point(&sep);
+
+        let transactions = prunable_transactions(&tangle, candidates.keys().copied());
+        assert_eq!(transactions.len(), 3);
+        assert!(transactions.contains(&a_hash));
+        assert!(transactions.contains(&b_hash));
+        assert!(transactions.contains(&d_hash));
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use bee_crypto::ternary::Hash;
+use bee_snapshot::{
+    GlobalSnapshot, GlobalSnapshotConfigBuilder, GlobalSnapshotReadError, LocalSnapshot, LocalSnapshotEntry,
+    LocalSnapshotMetadata, LocalSnapshotStream, SnapshotReadError, SnapshotState, SnapshotWriteError,
+};
+use bee_ternary::{Trits, T1B1};
+use bee_transaction::bundled::{Address, BundledTransactionField};
+
+use std::{collections::HashSet, fmt, path::Path};
+
+use async_std::task::block_on;
+use structopt::StructOpt;
+
+const GLOBAL_EXTENSION: &str = "txt";
+const GLOBAL_META_EXTENSION: &str = "meta";
+const GLOBAL_SPENT_ADDRESSES_EXTENSION: &str = "spent";
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "bee-snapshot",
+    about = "Inspects and converts snapshots. Files with the \"txt\" extension are global text snapshots, whose \
+             metadata and spent addresses are read from the files with the same name and the \"meta\" and \"spent\" \
+             extensions if they exist; any other file is a local binary snapshot."
+)]
+enum Command {
+    #[structopt(about = "Prints the metadata of a snapshot")]
+    Info { path: String },
+    #[structopt(about = "Dumps the balances of a snapshot")]
+    Dump {
+        path: String,
+        #[structopt(
+            short = "f",
+            long = "format",
+            default_value = "csv",
+            help = "Output format amongst \"csv\" and \"json\""
+        )]
+        format: Format,
+    },
+    #[structopt(about = "Prints the balances differing between two snapshots")]
+    Diff { from: String, to: String },
+    #[structopt(about = "Converts a snapshot between the local binary and the global text formats")]
+    Convert { from: String, to: String },
+}
+
+#[derive(Debug)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format \"{}\"", s)),
+        }
+    }
+}
+
+enum Error {
+    LocalRead(SnapshotReadError),
+    GlobalRead(GlobalSnapshotReadError),
+    LocalWrite(SnapshotWriteError),
+    GlobalWrite(async_std::io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::LocalRead(e) => write!(f, "reading the local snapshot failed: {}", e),
+            Error::GlobalRead(e) => write!(f, "reading the global snapshot failed: {}", e),
+            Error::LocalWrite(e) => write!(f, "writing the local snapshot failed: {:?}", e),
+            Error::GlobalWrite(e) => write!(f, "writing the global snapshot failed: {}", e),
+        }
+    }
+}
+
+fn is_global(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map_or(false, |extension| extension == GLOBAL_EXTENSION)
+}
+
+fn sibling_path(path: &str, extension: &str) -> String {
+    Path::new(path).with_extension(extension).to_string_lossy().to_string()
+}
+
+fn encode_trytes(trits: &Trits<T1B1>) -> String {
+    trits.iter_trytes().map(char::from).collect()
+}
+
+fn read(path: &str) -> Result<(LocalSnapshotMetadata, SnapshotState), Error> {
+    if is_global(path) {
+        let meta_path = sibling_path(path, GLOBAL_META_EXTENSION);
+        let spent_addresses_path = sibling_path(path, GLOBAL_SPENT_ADDRESSES_EXTENSION);
+        let mut config = GlobalSnapshotConfigBuilder::new().file_path(path.to_string());
+        if Path::new(&meta_path).exists() {
+            config = config.meta_file_path(meta_path);
+        }
+        if Path::new(&spent_addresses_path).exists() {
+            config = config.spent_addresses_file_paths(vec![spent_addresses_path]);
+        }
+        block_on(GlobalSnapshot::from_config(&config.finish()))
+            .map(GlobalSnapshot::into_parts)
+            .map_err(Error::GlobalRead)
+    } else {
+        block_on(LocalSnapshot::from_file(path))
+            .map(LocalSnapshot::into_parts)
+            .map_err(Error::LocalRead)
+    }
+}
+
+// What `info` prints, gathered either from a whole snapshot or from the entries of a streamed one.
+struct Summary {
+    hash: Hash,
+    index: u32,
+    timestamp: u64,
+    solid_entry_points_num: usize,
+    seen_milestones_num: usize,
+    balances_num: usize,
+    spent_addresses_num: usize,
+    supply: u64,
+}
+
+fn info(path: &str) -> Result<(), Error> {
+    let summary = if is_global(path) {
+        let (metadata, state) = read(path)?;
+        Summary {
+            hash: *metadata.hash(),
+            index: metadata.index(),
+            timestamp: metadata.timestamp(),
+            solid_entry_points_num: metadata.solid_entry_points().len(),
+            seen_milestones_num: metadata.seen_milestones().len(),
+            balances_num: state.balances().len(),
+            spent_addresses_num: state.spent_addresses().len(),
+            supply: state.balances().values().sum(),
+        }
+    } else {
+        // Local snapshots are streamed so that even large ones are inspected with bounded memory.
+        let mut stream = block_on(LocalSnapshotStream::verify(path, None)).map_err(Error::LocalRead)?;
+        let header = stream.header().clone();
+        let mut supply = 0;
+        while let Some(entry) = block_on(stream.next_entry()).map_err(Error::LocalRead)? {
+            if let LocalSnapshotEntry::Balance(_, value) = entry {
+                supply += value;
+            }
+        }
+        Summary {
+            hash: *header.hash(),
+            index: header.index(),
+            timestamp: header.timestamp(),
+            solid_entry_points_num: header.solid_entry_points_num() as usize,
+            seen_milestones_num: header.seen_milestones_num() as usize,
+            balances_num: header.balances_num() as usize,
+            spent_addresses_num: header.spent_addresses_num() as usize,
+            supply,
+        }
+    };
+
+    println!("Hash: {}", encode_trytes(summary.hash.as_trits()));
+    println!("Index: {}", summary.index);
+    println!("Timestamp: {}", summary.timestamp);
+    println!("Solid entry points: {}", summary.solid_entry_points_num);
+    println!("Seen milestones: {}", summary.seen_milestones_num);
+    println!("Balances: {}", summary.balances_num);
+    println!("Spent addresses: {}", summary.spent_addresses_num);
+    println!("Supply: {}", summary.supply);
+
+    Ok(())
+}
+
+fn dump(path: &str, format: Format) -> Result<(), Error> {
+    let (_, state) = read(path)?;
+    let mut balances = state
+        .into_balances()
+        .into_iter()
+        .map(|(address, balance)| (encode_trytes(address.to_inner()), balance))
+        .collect::<Vec<(String, u64)>>();
+    balances.sort();
+
+    match format {
+        Format::Csv => {
+            println!("address,balance");
+            for (address, balance) in balances {
+                println!("{},{}", address, balance);
+            }
+        }
+        Format::Json => {
+            let entries = balances
+                .iter()
+                .map(|(address, balance)| format!("  {{ \"address\": \"{}\", \"balance\": {} }}", address, balance))
+                .collect::<Vec<String>>();
+            println!("[\n{}\n]", entries.join(",\n"));
+        }
+    }
+
+    Ok(())
+}
+
+fn diff(from: &str, to: &str) -> Result<(), Error> {
+    let (from_metadata, from_state) = read(from)?;
+    let (to_metadata, to_state) = read(to)?;
+    let from_balances = from_state.balances();
+    let to_balances = to_state.balances();
+    let mut differences = from_balances
+        .keys()
+        .chain(to_balances.keys())
+        .collect::<HashSet<&Address>>()
+        .into_iter()
+        .filter_map(|address| {
+            let from_balance = from_balances.get(address).copied().unwrap_or(0);
+            let to_balance = to_balances.get(address).copied().unwrap_or(0);
+            if from_balance != to_balance {
+                Some((encode_trytes(address.to_inner()), from_balance, to_balance))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<(String, u64, u64)>>();
+    differences.sort();
+
+    println!("Index: {} -> {}", from_metadata.index(), to_metadata.index());
+    println!("address,from,to");
+    for (address, from_balance, to_balance) in differences.iter() {
+        println!("{},{},{}", address, from_balance, to_balance);
+    }
+    println!("{} differing balances.", differences.len());
+
+    Ok(())
+}
+
+fn convert(from: &str, to: &str) -> Result<(), Error> {
+    let (metadata, state) = read(from)?;
+
+    if is_global(to) {
+        block_on(GlobalSnapshot::new(metadata, state).to_files(
+            to,
+            Some(&sibling_path(to, GLOBAL_META_EXTENSION)),
+            Some(&sibling_path(to, GLOBAL_SPENT_ADDRESSES_EXTENSION)),
+        ))
+        .map_err(Error::GlobalWrite)
+    } else {
+        block_on(LocalSnapshot::new(metadata, state).to_file(to)).map_err(Error::LocalWrite)
+    }
+}
+
+fn main() {
+    let result = match Command::from_args() {
+        Command::Info { path } => info(&path),
+        Command::Dump { path, format } => dump(&path, format),
+        Command::Diff { from, to } => diff(&from, &to),
+        Command::Convert { from, to } => convert(&from, &to),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Program aborted. Error was: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -11,20 +11,36 @@
 
 use bee_transaction::bundled::Address;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // TODO Abstract balances
 
 pub struct SnapshotState {
     pub(crate) balances: HashMap<Address, u64>,
+    pub(crate) spent_addresses: HashSet<Address>,
 }
 
 impl SnapshotState {
+    pub fn new(balances: HashMap<Address, u64>, spent_addresses: HashSet<Address>) -> Self {
+        Self {
+            balances,
+            spent_addresses,
+        }
+    }
+
     pub fn balances(&self) -> &HashMap<Address, u64> {
         &self.balances
     }
 
+    pub fn spent_addresses(&self) -> &HashSet<Address> {
+        &self.spent_addresses
+    }
+
     pub fn into_balances(self) -> HashMap<Address, u64> {
         self.balances
     }
+
+    pub fn into_parts(self) -> (HashMap<Address, u64>, HashSet<Address>) {
+        (self.balances, self.spent_addresses)
+    }
 }
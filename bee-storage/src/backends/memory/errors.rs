@@ -0,0 +1,40 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use std::{error::Error as StdError, fmt};
+
+#[derive(Debug, Clone)]
+pub enum MemoryBackendError {
+    TransactionDoesNotExist,
+    MilestoneDoesNotExist,
+    StateDeltaDoesNotExist,
+    NegativeBalance,
+    BackupNotSupported,
+}
+
+impl fmt::Display for MemoryBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MemoryBackendError::TransactionDoesNotExist => write!(f, "Transaction does not exist"),
+            MemoryBackendError::MilestoneDoesNotExist => write!(f, "Milestone does not exist"),
+            MemoryBackendError::StateDeltaDoesNotExist => write!(f, "State delta does not exist"),
+            MemoryBackendError::NegativeBalance => write!(f, "State delta leads to a negative balance"),
+            MemoryBackendError::BackupNotSupported => write!(f, "In-memory storage can not be backed up"),
+        }
+    }
+}
+
+// Allow this type to be treated like an error
+impl StdError for MemoryBackendError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,433 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+mod errors;
+mod test;
+
+use crate::storage::{HashesToApprovers, MissingHashesToRCApprovers, StateDeltaMap, StorageBackend};
+
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
+use bee_transaction::{
+    bundled::{Address, BundledTransaction as Transaction, Tag},
+    TransactionVertex,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    sync::{Mutex, RwLock},
+};
+
+pub use errors::MemoryBackendError;
+
+use async_trait::async_trait;
+
+#[derive(Default)]
+struct Ledger {
+    index: Option<MilestoneIndex>,
+    balances: HashMap<Address, u64>,
+    deltas: HashMap<MilestoneIndex, StateDeltaMap>,
+    spent_addresses: HashSet<Address>,
+}
+
+impl Ledger {
+    // Applies `deltas` to the balances, zero balances being removed, or leaves them untouched if any would become
+    // negative.
+    fn apply<'a>(&mut self, deltas: impl Iterator<Item = (&'a Address, i64)>) -> Result<(), MemoryBackendError> {
+        let mut balances = Vec::new();
+
+        for (address, delta) in deltas {
+            let balance = *self.balances.get(address).unwrap_or(&0) as i64 + delta;
+
+            if balance < 0 {
+                return Err(MemoryBackendError::NegativeBalance);
+            }
+
+            balances.push((address.clone(), balance as u64));
+        }
+
+        for (address, balance) in balances {
+            if balance == 0 {
+                self.balances.remove(&address);
+            } else {
+                self.balances.insert(address, balance);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A storage backend keeping everything in memory, e.g. for tests; nothing outlives the instance.
+#[derive(Default)]
+pub struct MemoryBackendStorage {
+    transactions: RwLock<HashMap<Hash, Transaction>>,
+    solid_transactions: RwLock<HashSet<Hash>>,
+    snapshot_indices: RwLock<HashMap<Hash, u32>>,
+    milestones: RwLock<HashMap<Hash, MilestoneIndex>>,
+    ledger: Mutex<Ledger>,
+}
+
+impl MemoryBackendStorage {
+    // Lookups are scans; the backend is not meant to hold many transactions.
+    fn find_transaction_hashes(&self, filter: impl Fn(&Transaction) -> bool) -> HashSet<Hash> {
+        self.transactions
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, tx)| filter(tx))
+            .map(|(hash, _)| *hash)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryBackendStorage {
+    type StorageError = MemoryBackendError;
+
+    fn new() -> Self {
+        Self::default()
+    }
+
+    async fn establish_connection(&mut self, _url: &str) -> Result<(), MemoryBackendError> {
+        Ok(())
+    }
+
+    async fn destroy_connection(&mut self) -> Result<(), MemoryBackendError> {
+        Ok(())
+    }
+
+    async fn map_existing_transaction_hashes_to_approvers(&self) -> Result<HashesToApprovers, MemoryBackendError> {
+        let mut hash_to_approvers = HashMap::new();
+
+        for (hash, transaction) in self.transactions.read().unwrap().iter() {
+            hash_to_approvers
+                .entry(*transaction.trunk())
+                .or_insert_with(HashSet::new)
+                .insert(*hash);
+            hash_to_approvers
+                .entry(*transaction.branch())
+                .or_insert_with(HashSet::new)
+                .insert(*hash);
+        }
+
+        Ok(hash_to_approvers)
+    }
+
+    async fn map_missing_transaction_hashes_to_approvers(
+        &self,
+        all_hashes: HashSet<Hash>,
+    ) -> Result<MissingHashesToRCApprovers, MemoryBackendError> {
+        let mut missing_to_approvers = HashMap::new();
+
+        for (hash, transaction) in self.transactions.read().unwrap().iter() {
+            let approver = Rc::new(*hash);
+
+            for approvee in [transaction.trunk(), transaction.branch()].iter() {
+                if !all_hashes.contains(*approvee) {
+                    missing_to_approvers
+                        .entry(**approvee)
+                        .or_insert_with(HashSet::new)
+                        .insert(approver.clone());
+                }
+            }
+        }
+
+        Ok(missing_to_approvers)
+    }
+
+    async fn insert_transaction(&self, tx_hash: Hash, tx: Transaction) -> Result<(), MemoryBackendError> {
+        self.transactions.write().unwrap().insert(tx_hash, tx);
+        Ok(())
+    }
+
+    async fn insert_transactions(&self, transactions: HashMap<Hash, Transaction>) -> Result<(), MemoryBackendError> {
+        self.transactions.write().unwrap().extend(transactions);
+        Ok(())
+    }
+
+    async fn find_transaction(&self, tx_hash: Hash) -> Result<Transaction, MemoryBackendError> {
+        self.transactions
+            .read()
+            .unwrap()
+            .get(&tx_hash)
+            .cloned()
+            .ok_or(MemoryBackendError::TransactionDoesNotExist)
+    }
+
+    async fn update_transactions_set_solid(&self, transaction_hashes: HashSet<Hash>) -> Result<(), MemoryBackendError> {
+        self.solid_transactions.write().unwrap().extend(transaction_hashes);
+        Ok(())
+    }
+
+    async fn update_transactions_set_snapshot_index(
+        &self,
+        transaction_hashes: HashSet<Hash>,
+        snapshot_index: MilestoneIndex,
+    ) -> Result<(), MemoryBackendError> {
+        let mut snapshot_indices = self.snapshot_indices.write().unwrap();
+
+        for hash in transaction_hashes {
+            snapshot_indices.insert(hash, *snapshot_index);
+        }
+
+        Ok(())
+    }
+
+    async fn get_transactions_solid_state(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> Result<Vec<bool>, MemoryBackendError> {
+        let solid_transactions = self.solid_transactions.read().unwrap();
+
+        Ok(transaction_hashes
+            .iter()
+            .map(|hash| solid_transactions.contains(hash))
+            .collect())
+    }
+
+    async fn get_transactions_snapshot_index(
+        &self,
+        transaction_hashes: Vec<Hash>,
+    ) -> Result<Vec<u32>, MemoryBackendError> {
+        let snapshot_indices = self.snapshot_indices.read().unwrap();
+
+        // The absence of a value means the transaction is not known to be confirmed.
+        Ok(transaction_hashes
+            .iter()
+            .map(|hash| *snapshot_indices.get(hash).unwrap_or(&0))
+            .collect())
+    }
+
+    async fn delete_transactions(&self, transaction_hashes: &HashSet<Hash>) -> Result<(), MemoryBackendError> {
+        let mut transactions = self.transactions.write().unwrap();
+        let mut solid_transactions = self.solid_transactions.write().unwrap();
+        let mut snapshot_indices = self.snapshot_indices.write().unwrap();
+
+        for hash in transaction_hashes {
+            transactions.remove(hash);
+            solid_transactions.remove(hash);
+            snapshot_indices.remove(hash);
+        }
+
+        Ok(())
+    }
+
+    async fn find_transaction_hashes_by_address(&self, address: &Address) -> Result<HashSet<Hash>, MemoryBackendError> {
+        Ok(self.find_transaction_hashes(|tx| tx.address() == address))
+    }
+
+    async fn find_transaction_hashes_by_bundle(&self, bundle: Hash) -> Result<HashSet<Hash>, MemoryBackendError> {
+        Ok(self.find_transaction_hashes(|tx| *tx.bundle() == bundle))
+    }
+
+    async fn find_transaction_hashes_by_tag(&self, tag: &Tag) -> Result<HashSet<Hash>, MemoryBackendError> {
+        Ok(self.find_transaction_hashes(|tx| tx.tag() == tag))
+    }
+
+    async fn find_transaction_hashes_by_approvee(&self, approvee: Hash) -> Result<HashSet<Hash>, MemoryBackendError> {
+        Ok(self.find_transaction_hashes(|tx| *tx.trunk() == approvee || *tx.branch() == approvee))
+    }
+
+    async fn insert_milestone(&self, milestone: Milestone) -> Result<(), MemoryBackendError> {
+        self.milestones
+            .write()
+            .unwrap()
+            .insert(*milestone.hash(), milestone.index());
+        Ok(())
+    }
+
+    async fn find_milestone(&self, milestone_hash: Hash) -> Result<Milestone, MemoryBackendError> {
+        self.milestones
+            .read()
+            .unwrap()
+            .get(&milestone_hash)
+            .map(|index| Milestone::new(milestone_hash, *index))
+            .ok_or(MemoryBackendError::MilestoneDoesNotExist)
+    }
+
+    async fn load_milestones(&self) -> Result<Vec<Milestone>, MemoryBackendError> {
+        Ok(self
+            .milestones
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(hash, index)| Milestone::new(*hash, *index))
+            .collect())
+    }
+
+    async fn delete_milestones(&self, milestone_hashes: &HashSet<Hash>) -> Result<(), MemoryBackendError> {
+        let mut milestones = self.milestones.write().unwrap();
+
+        for hash in milestone_hashes {
+            milestones.remove(hash);
+        }
+
+        Ok(())
+    }
+
+    async fn insert_state_delta(
+        &self,
+        state_delta: StateDeltaMap,
+        index: MilestoneIndex,
+    ) -> Result<(), MemoryBackendError> {
+        let mut ledger = self.ledger.lock().unwrap();
+
+        ledger.apply(
+            state_delta
+                .address_to_delta
+                .iter()
+                .map(|(address, delta)| (address, *delta)),
+        )?;
+        ledger.deltas.insert(index, state_delta);
+        ledger.index = Some(index);
+
+        Ok(())
+    }
+
+    async fn load_state_delta(&self, index: MilestoneIndex) -> Result<StateDeltaMap, MemoryBackendError> {
+        self.ledger
+            .lock()
+            .unwrap()
+            .deltas
+            .get(&index)
+            .cloned()
+            .ok_or(MemoryBackendError::StateDeltaDoesNotExist)
+    }
+
+    async fn revert_state_delta(&self) -> Result<(MilestoneIndex, StateDeltaMap), MemoryBackendError> {
+        let mut ledger = self.ledger.lock().unwrap();
+
+        let index = ledger.index.ok_or(MemoryBackendError::StateDeltaDoesNotExist)?;
+        let state_delta = ledger
+            .deltas
+            .get(&index)
+            .cloned()
+            .ok_or(MemoryBackendError::StateDeltaDoesNotExist)?;
+
+        ledger.apply(
+            state_delta
+                .address_to_delta
+                .iter()
+                .map(|(address, delta)| (address, -delta)),
+        )?;
+        ledger.deltas.remove(&index);
+        ledger.index = Some(MilestoneIndex(*index - 1));
+
+        Ok((index, state_delta))
+    }
+
+    async fn insert_balances(
+        &self,
+        balances: HashMap<Address, u64>,
+        index: MilestoneIndex,
+    ) -> Result<(), MemoryBackendError> {
+        let mut ledger = self.ledger.lock().unwrap();
+
+        ledger.balances = balances.into_iter().filter(|(_, balance)| *balance != 0).collect();
+        ledger.index = Some(index);
+
+        Ok(())
+    }
+
+    async fn clear_balances(&self) -> Result<(), MemoryBackendError> {
+        let mut ledger = self.ledger.lock().unwrap();
+
+        ledger.balances.clear();
+        ledger.index = None;
+
+        Ok(())
+    }
+
+    async fn append_balances(&self, balances: HashMap<Address, u64>) -> Result<(), MemoryBackendError> {
+        self.ledger
+            .lock()
+            .unwrap()
+            .balances
+            .extend(balances.into_iter().filter(|(_, balance)| *balance != 0));
+
+        Ok(())
+    }
+
+    async fn insert_ledger_index(&self, index: MilestoneIndex) -> Result<(), MemoryBackendError> {
+        self.ledger.lock().unwrap().index = Some(index);
+
+        Ok(())
+    }
+
+    async fn load_balances(&self) -> Result<Option<(MilestoneIndex, HashMap<Address, u64>)>, MemoryBackendError> {
+        let ledger = self.ledger.lock().unwrap();
+
+        Ok(ledger.index.map(|index| (index, ledger.balances.clone())))
+    }
+
+    async fn insert_spent_addresses(&self, addresses: HashSet<Address>) -> Result<(), MemoryBackendError> {
+        self.ledger.lock().unwrap().spent_addresses.extend(addresses);
+        Ok(())
+    }
+
+    async fn load_spent_addresses(&self) -> Result<HashSet<Address>, MemoryBackendError> {
+        Ok(self.ledger.lock().unwrap().spent_addresses.clone())
+    }
+
+    async fn prune_below(
+        &self,
+        index: MilestoneIndex,
+        solid_entry_points: &HashSet<Hash>,
+    ) -> Result<(), MemoryBackendError> {
+        let prunable: HashSet<Hash> = {
+            let transactions = self.transactions.read().unwrap();
+            let snapshot_indices = self.snapshot_indices.read().unwrap();
+            let cutoff = self
+                .milestones
+                .read()
+                .unwrap()
+                .iter()
+                .find(|(_, milestone_index)| **milestone_index == index)
+                .and_then(|(hash, _)| transactions.get(hash))
+                .map(|milestone| milestone.get_timestamp());
+
+            transactions
+                .iter()
+                .filter(|(hash, tx)| {
+                    let prunable = match snapshot_indices.get(*hash).copied().unwrap_or(0) {
+                        0 => cutoff.map_or(false, |cutoff| tx.get_timestamp() < cutoff),
+                        snapshot_index => snapshot_index < *index,
+                    };
+                    prunable && !solid_entry_points.contains(*hash)
+                })
+                .map(|(hash, _)| *hash)
+                .collect()
+        };
+
+        self.delete_transactions(&prunable).await?;
+        self.milestones
+            .write()
+            .unwrap()
+            .retain(|_, milestone_index| *milestone_index >= index);
+        self.ledger
+            .lock()
+            .unwrap()
+            .deltas
+            .retain(|milestone_index, _| *milestone_index >= index);
+
+        Ok(())
+    }
+
+    async fn backup(&self, _path: &str) -> Result<(), MemoryBackendError> {
+        Err(MemoryBackendError::BackupNotSupported)
+    }
+
+    async fn restore(_path: &str, _url: &str) -> Result<MilestoneIndex, MemoryBackendError> {
+        Err(MemoryBackendError::BackupNotSupported)
+    }
+}
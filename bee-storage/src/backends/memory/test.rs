@@ -0,0 +1,37 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::{backends::memory::MemoryBackendStorage, tests::test::TestableStorage};
+
+impl TestableStorage for MemoryBackendStorage {
+    fn test_name() -> String {
+        String::from("memory")
+    }
+
+    fn setup() {}
+
+    fn teardown() {}
+
+    fn test_db_url() -> String {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{backends::memory::MemoryBackendStorage, tests::test::StorageTestRunner};
+
+    #[test]
+    fn test_all() {
+        StorageTestRunner::<MemoryBackendStorage>::run_all_tests();
+    }
+}
@@ -9,5 +9,12 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
+mod memory;
 mod rocksdb;
 mod sqlx;
+
+pub use self::{
+    memory::{MemoryBackendError, MemoryBackendStorage},
+    rocksdb::{RocksDbBackendError, RocksDbBackendStorage, RocksDbBatchWriter},
+    sqlx::{SqliteBackendStorage, SqlxBackendError, SqlxBackendStorage},
+};
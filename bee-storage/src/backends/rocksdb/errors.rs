@@ -15,6 +15,13 @@ use std::{error::Error as StdError, fmt};
 pub enum RocksDbBackendError {
     RocksDbError(String),
     TransactionDoesNotExist,
+    StateDeltaDoesNotExist,
+    NegativeBalance,
+    NewerSchemaVersion(u32),
+    InvalidBackup(String),
+    UnsupportedBackupFormat(String),
+    CorruptedData(String),
+    IoError(String),
 }
 
 impl fmt::Display for RocksDbBackendError {
@@ -22,6 +29,21 @@ impl fmt::Display for RocksDbBackendError {
         match *self {
             RocksDbBackendError::RocksDbError(ref reason) => write!(f, "RocksDB core error: {:?}", reason),
             RocksDbBackendError::TransactionDoesNotExist => write!(f, "Transaction does not exist"),
+            RocksDbBackendError::StateDeltaDoesNotExist => write!(f, "State delta does not exist"),
+            RocksDbBackendError::NegativeBalance => write!(f, "State delta leads to a negative balance"),
+            RocksDbBackendError::NewerSchemaVersion(version) => {
+                write!(f, "Schema version {} is newer than the supported one", version)
+            }
+            RocksDbBackendError::InvalidBackup(ref reason) => write!(f, "Invalid backup: {:?}", reason),
+            RocksDbBackendError::UnsupportedBackupFormat(ref path) => {
+                write!(
+                    f,
+                    "Unsupported backup format, expected a database directory: {:?}",
+                    path
+                )
+            }
+            RocksDbBackendError::CorruptedData(ref what) => write!(f, "Corrupted data: {}", what),
+            RocksDbBackendError::IoError(ref reason) => write!(f, "I/O error: {:?}", reason),
         }
     }
 }
@@ -38,3 +60,9 @@ impl From<rocksdb::Error> for RocksDbBackendError {
         RocksDbBackendError::RocksDbError(err.to_string())
     }
 }
+
+impl From<std::io::Error> for RocksDbBackendError {
+    fn from(err: std::io::Error) -> Self {
+        RocksDbBackendError::IoError(err.to_string())
+    }
+}
@@ -12,36 +12,75 @@
 mod errors;
 mod models;
 mod test;
+mod writer;
 
 use crate::storage::{
-    Connection, HashesToApprovers, MissingHashesToRCApprovers, StateDeltaMap, Storage, StorageBackend,
+    decode_address, encode_address, Connection, HashesToApprovers, MissingHashesToRCApprovers, StateDeltaMap, Storage,
+    StorageBackend,
 };
 
-use bee_protocol::{Milestone, MilestoneIndex};
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
 use bee_ternary::{T1B1Buf, T5B1Buf, TritBuf, Trits, T5B1};
-use bee_transaction::{BundledTransaction as Transaction, BundledTransactionField, Hash, TransactionVertex};
+use bee_transaction::{
+    bundled::{Address, BundledTransaction as Transaction, BundledTransactionField, Tag},
+    TransactionVertex,
+};
 
 use std::{
     collections::{HashMap, HashSet},
-    mem, ptr,
+    fs, mem,
+    path::Path,
+    ptr,
     rc::Rc,
+    sync::Arc,
 };
 
-use errors::RocksDbBackendError;
+pub use errors::RocksDbBackendError;
+pub use writer::RocksDbBatchWriter;
 
+use async_std::task;
 use async_trait::async_trait;
 
 use bytemuck::{cast_slice, cast_slice_mut};
-use rocksdb::{ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType, IteratorMode, Options, WriteOptions, DB};
+use rocksdb::{
+    checkpoint::Checkpoint, ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType, Direction, IteratorMode,
+    Options, WriteOptions, DB,
+};
 use std::borrow::BorrowMut;
 
 const TRANSACTION_CF_HASH_TO_TRANSACTION: &str = "transaction_hash_to_transaction";
 const TRANSACTION_CF_HASH_TO_SOLID: &str = "transaction_hash_to_solid";
 const TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX: &str = "transaction_hash_to_snapshot_index";
 const TRANSACTION_CF_HASH_TO_APROVEES: &str = "transaction_hash_to_aprovees";
+const TRANSACTION_CF_ADDRESS_TO_HASH: &str = "transaction_address_to_hash";
+const TRANSACTION_CF_BUNDLE_TO_HASH: &str = "transaction_bundle_to_hash";
+const TRANSACTION_CF_TAG_TO_HASH: &str = "transaction_tag_to_hash";
+const TRANSACTION_CF_APPROVEE_TO_HASH: &str = "transaction_approvee_to_hash";
 const MILESTONE_CF_HASH_TO_INDEX: &str = "milestone_hash_to_index";
 const MILESTONE_CF_INDEX_TO_HASH: &str = "milestone_index_to_hash";
-const MILESTONE_CF_HASH_TO_DELTA: &str = "milestone_hash_to_delta";
+const MILESTONE_CF_INDEX_TO_DELTA: &str = "milestone_index_to_delta";
+const LEDGER_CF_ADDRESS_TO_BALANCE: &str = "ledger_address_to_balance";
+const LEDGER_CF_INDEX: &str = "ledger_index";
+const LEDGER_CF_SPENT_ADDRESS: &str = "ledger_spent_address";
+const SCHEMA_CF_VERSION: &str = "schema_version";
+
+const LEDGER_INDEX_KEY: &[u8] = b"index";
+const SCHEMA_VERSION_KEY: &[u8] = b"version";
+
+// Archives a backup may have been packed into, which `restore` doesn't unpack.
+const ARCHIVE_EXTENSIONS: &[&str] = &[".tar", ".tar.gz", ".tgz", ".tar.xz", ".tar.zst", ".zip"];
+
+// Upgrades, in order, the layout of version `i + 1` to version `i + 2`. Migrations may be written in several batches,
+// so they must be safe to run again: the version they lead to is only stored once they are complete, and an
+// interrupted upgrade is started over on the next open.
+const MIGRATIONS: &[fn(&DB) -> Result<(), RocksDbBackendError>] = &[build_transaction_indexes];
+
+// Number of records a migration reads before writing what it derived from them, to bound the memory of its batches.
+const MIGRATION_BATCH_SIZE: usize = 10_000;
+
+// Version 1 is the layout that predates the version record.
+const SCHEMA_VERSION: u32 = 1 + MIGRATIONS.len() as u32;
 
 struct Approvees<'a> {
     trunk: &'a Hash,
@@ -102,8 +141,337 @@ fn decode_aprovees(buff: &[u8]) -> (Hash, Hash) {
     (trunk, branch)
 }
 
+#[inline]
+fn decode_u32(buff: &[u8]) -> u32 {
+    let mut u32_buffer: [u8; 4] = [0; 4];
+    u32_buffer.copy_from_slice(buff);
+    u32::from_le_bytes(u32_buffer)
+}
+
+#[inline]
+fn decode_u64(buff: &[u8]) -> u64 {
+    let mut u64_buffer: [u8; 8] = [0; 8];
+    u64_buffer.copy_from_slice(buff);
+    u64::from_le_bytes(u64_buffer)
+}
+
+fn decode_stored_address(buff: &[u8]) -> Result<Address, RocksDbBackendError> {
+    decode_address(buff).ok_or_else(|| RocksDbBackendError::CorruptedData(format!("address {:?}", buff)))
+}
+
+fn decode_state_delta(buff: &[u8], index: MilestoneIndex) -> Result<StateDeltaMap, RocksDbBackendError> {
+    StateDeltaMap::decode(buff)
+        .ok_or_else(|| RocksDbBackendError::CorruptedData(format!("state delta of milestone {}", *index)))
+}
+
+#[inline]
+fn encode_hash(hash: &Hash) -> Vec<u8> {
+    cast_slice(hash.to_inner().encode::<T5B1Buf>().as_i8_slice()).to_vec()
+}
+
+// The secondary indexes map a key to transaction hashes by storing the key followed by each hash, with empty values,
+// so that a lookup is a prefix scan. Returns the column families and keys of the entries indexing `tx`.
+fn transaction_index_entries(hash_buf: &[u8], tx: &Transaction) -> [(&'static str, Vec<u8>); 5] {
+    let entry = |key: Vec<u8>| [key.as_slice(), hash_buf].concat();
+
+    [
+        (TRANSACTION_CF_ADDRESS_TO_HASH, entry(encode_address(tx.address()))),
+        (TRANSACTION_CF_BUNDLE_TO_HASH, entry(encode_hash(tx.bundle()))),
+        (
+            TRANSACTION_CF_TAG_TO_HASH,
+            entry(cast_slice(tx.tag().to_inner().encode::<T5B1Buf>().as_i8_slice()).to_vec()),
+        ),
+        (TRANSACTION_CF_APPROVEE_TO_HASH, entry(encode_hash(tx.trunk()))),
+        (TRANSACTION_CF_APPROVEE_TO_HASH, entry(encode_hash(tx.branch()))),
+    ]
+}
+
+fn batch_transaction_indexes(db: &DB, batch: &mut rocksdb::WriteBatch, hash_buf: &[u8], tx: &Transaction) {
+    for (cf, key) in transaction_index_entries(hash_buf, tx).iter() {
+        batch.put_cf(&db.cf_handle(cf).unwrap(), key, b"");
+    }
+}
+
+fn find_indexed_transaction_hashes(db: &DB, cf: &str, key: &[u8]) -> HashSet<Hash> {
+    let cf = db.cf_handle(cf).unwrap();
+
+    db.iterator_cf(&cf, IteratorMode::From(key, Direction::Forward))
+        .take_while(|(entry, _)| entry.starts_with(key))
+        .map(|(entry, _)| decode_hash(&entry[key.len()..]))
+        .collect()
+}
+
+// Writes to `batch` the transaction, its approvees and its index entries.
+fn batch_transaction(db: &DB, batch: &mut rocksdb::WriteBatch, tx_hash: &Hash, tx: &Transaction) {
+    let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
+    let transaction_cf_hash_to_aprovees = db.cf_handle(TRANSACTION_CF_HASH_TO_APROVEES).unwrap();
+
+    let mut tx_trit_buf = TritBuf::<T1B1Buf>::zeros(Transaction::trit_len());
+    let mut aprovees_trit_buf = TritBuf::<T1B1Buf>::zeros(Hash::trit_len() * 2);
+
+    tx.into_trits_allocated(tx_trit_buf.as_slice_mut());
+    let hash_buf = tx_hash.to_inner().encode::<T5B1Buf>();
+    batch.put_cf(
+        &transaction_cf_hash_to_transaction,
+        cast_slice(hash_buf.as_i8_slice()),
+        cast_slice(tx_trit_buf.encode::<T5B1Buf>().as_i8_slice()),
+    );
+
+    let aprovees = Approvees {
+        trunk: tx.trunk(),
+        branch: tx.branch(),
+    };
+    aprovees.into_trits_allocated(aprovees_trit_buf.borrow_mut());
+
+    batch.put_cf(
+        &transaction_cf_hash_to_aprovees,
+        cast_slice(hash_buf.as_i8_slice()),
+        cast_slice(aprovees_trit_buf.encode::<T5B1Buf>().as_i8_slice()),
+    );
+
+    batch_transaction_indexes(db, batch, cast_slice(hash_buf.as_i8_slice()), tx);
+}
+
+fn batch_solid(db: &DB, batch: &mut rocksdb::WriteBatch, hash: &Hash) {
+    let transaction_cf_hash_to_solid = db.cf_handle(TRANSACTION_CF_HASH_TO_SOLID).unwrap();
+
+    batch.put_cf(&transaction_cf_hash_to_solid, encode_hash(hash), unsafe {
+        mem::transmute::<bool, [u8; 1]>(true)
+    });
+}
+
+fn batch_snapshot_index(db: &DB, batch: &mut rocksdb::WriteBatch, hash: &Hash, snapshot_index: MilestoneIndex) {
+    let transaction_cf_hash_to_snapshot_index = db.cf_handle(TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX).unwrap();
+
+    batch.put_cf(
+        &transaction_cf_hash_to_snapshot_index,
+        encode_hash(hash),
+        snapshot_index.to_le_bytes(),
+    );
+}
+
+fn batch_milestone(db: &DB, batch: &mut rocksdb::WriteBatch, milestone: &Milestone) {
+    let milestone_cf_hash_to_index = db.cf_handle(MILESTONE_CF_HASH_TO_INDEX).unwrap();
+    let milestone_cf_index_to_hash = db.cf_handle(MILESTONE_CF_INDEX_TO_HASH).unwrap();
+
+    let hash_buf = encode_hash(milestone.hash());
+    batch.put_cf(&milestone_cf_hash_to_index, &hash_buf, milestone.index().to_le_bytes());
+    batch.put_cf(&milestone_cf_index_to_hash, milestone.index().to_le_bytes(), &hash_buf);
+}
+
+// Writes to `batch` the deletion of the transaction and of everything stored along with it.
+fn batch_delete_transaction(
+    db: &DB,
+    batch: &mut rocksdb::WriteBatch,
+    hash_buf: &[u8],
+) -> Result<(), RocksDbBackendError> {
+    let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
+
+    // The index entries are derived from the transaction, which has to be read before being deleted.
+    if let Some(tx_buf) = db.get_cf(&transaction_cf_hash_to_transaction, hash_buf)? {
+        let tx = decode_transaction(&tx_buf);
+        for (cf, key) in transaction_index_entries(hash_buf, &tx).iter() {
+            batch.delete_cf(&db.cf_handle(cf).unwrap(), key);
+        }
+    }
+
+    for cf in &[
+        TRANSACTION_CF_HASH_TO_TRANSACTION,
+        TRANSACTION_CF_HASH_TO_SOLID,
+        TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX,
+        TRANSACTION_CF_HASH_TO_APROVEES,
+    ] {
+        batch.delete_cf(&db.cf_handle(cf).unwrap(), hash_buf);
+    }
+
+    Ok(())
+}
+
+// Migration to version 2, indexing the transactions stored before the secondary indexes existed.
+fn build_transaction_indexes(db: &DB) -> Result<(), RocksDbBackendError> {
+    let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
+    let mut batch = rocksdb::WriteBatch::default();
+    let mut len = 0;
+
+    for (key, value) in db.iterator_cf(&transaction_cf_hash_to_transaction, IteratorMode::Start) {
+        batch_transaction_indexes(db, &mut batch, key.as_ref(), &decode_transaction(value.as_ref()));
+        len += 1;
+
+        if len == MIGRATION_BATCH_SIZE {
+            db.write(mem::take(&mut batch))?;
+            len = 0;
+        }
+    }
+
+    db.write(batch)?;
+
+    Ok(())
+}
+
+// Brings the database to `SCHEMA_VERSION`, refusing to touch databases written by a newer version.
+fn migrate(db: &DB) -> Result<(), RocksDbBackendError> {
+    let schema_cf_version = db.cf_handle(SCHEMA_CF_VERSION).unwrap();
+    let mut version = db
+        .get_cf(&schema_cf_version, SCHEMA_VERSION_KEY)?
+        .map_or(1, |buf| decode_u32(buf.as_ref()));
+
+    if version > SCHEMA_VERSION {
+        return Err(RocksDbBackendError::NewerSchemaVersion(version));
+    }
+
+    while version < SCHEMA_VERSION {
+        MIGRATIONS[version as usize - 1](db)?;
+        version += 1;
+        db.put_cf(&schema_cf_version, SCHEMA_VERSION_KEY, version.to_le_bytes())?;
+    }
+
+    // Stamps new databases, and the ones predating the version record.
+    if db.get_cf(&schema_cf_version, SCHEMA_VERSION_KEY)?.is_none() {
+        db.put_cf(&schema_cf_version, SCHEMA_VERSION_KEY, SCHEMA_VERSION.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+// Writes to `batch` the balances resulting from applying `deltas` to the stored ones, zero balances being deleted.
+fn batch_balance_deltas<'a>(
+    db: &DB,
+    batch: &mut rocksdb::WriteBatch,
+    deltas: impl Iterator<Item = (&'a Address, i64)>,
+) -> Result<(), RocksDbBackendError> {
+    let ledger_cf_address_to_balance = db.cf_handle(LEDGER_CF_ADDRESS_TO_BALANCE).unwrap();
+
+    for (address, delta) in deltas {
+        let address_buf = encode_address(address);
+        let balance = db
+            .get_cf(&ledger_cf_address_to_balance, &address_buf)?
+            .map_or(0, |balance_buf| decode_u64(&balance_buf)) as i64
+            + delta;
+
+        if balance < 0 {
+            return Err(RocksDbBackendError::NegativeBalance);
+        } else if balance == 0 {
+            batch.delete_cf(&ledger_cf_address_to_balance, &address_buf);
+        } else {
+            batch.put_cf(
+                &ledger_cf_address_to_balance,
+                &address_buf,
+                (balance as u64).to_le_bytes(),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Opens, or creates, the database at `url` with all the column families.
+fn open_db(url: &str) -> Result<DB, RocksDbBackendError> {
+    let transaction_cf_hash_to_trnsaction =
+        ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_TRANSACTION, Options::default());
+    let transaction_cf_hash_to_solid = ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_SOLID, Options::default());
+    let transaction_cf_hash_to_snapshot_index =
+        ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX, Options::default());
+
+    let transaction_cf_hash_to_aprovees =
+        ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_APROVEES, Options::default());
+
+    let milestone_cf_hash_to_index = ColumnFamilyDescriptor::new(MILESTONE_CF_HASH_TO_INDEX, Options::default());
+    let milestone_cf_index_to_hash = ColumnFamilyDescriptor::new(MILESTONE_CF_INDEX_TO_HASH, Options::default());
+    let milestone_cf_index_to_delta = ColumnFamilyDescriptor::new(MILESTONE_CF_INDEX_TO_DELTA, Options::default());
+    let ledger_cf_address_to_balance = ColumnFamilyDescriptor::new(LEDGER_CF_ADDRESS_TO_BALANCE, Options::default());
+    let ledger_cf_index = ColumnFamilyDescriptor::new(LEDGER_CF_INDEX, Options::default());
+    let ledger_cf_spent_address = ColumnFamilyDescriptor::new(LEDGER_CF_SPENT_ADDRESS, Options::default());
+    let transaction_cf_address_to_hash =
+        ColumnFamilyDescriptor::new(TRANSACTION_CF_ADDRESS_TO_HASH, Options::default());
+    let transaction_cf_bundle_to_hash = ColumnFamilyDescriptor::new(TRANSACTION_CF_BUNDLE_TO_HASH, Options::default());
+    let transaction_cf_tag_to_hash = ColumnFamilyDescriptor::new(TRANSACTION_CF_TAG_TO_HASH, Options::default());
+    let transaction_cf_approvee_to_hash =
+        ColumnFamilyDescriptor::new(TRANSACTION_CF_APPROVEE_TO_HASH, Options::default());
+    let schema_cf_version = ColumnFamilyDescriptor::new(SCHEMA_CF_VERSION, Options::default());
+    let mut opts = Options::default();
+    // TODO - figure this out
+    opts.set_max_write_buffer_number(4);
+    opts.create_missing_column_families(true);
+    opts.create_if_missing(true);
+    opts.set_compaction_style(DBCompactionStyle::Universal);
+    opts.set_max_background_compactions(4);
+    opts.set_max_background_flushes(4);
+    opts.set_disable_auto_compactions(true);
+    opts.increase_parallelism(num_cpus::get() as i32);
+    opts.set_compression_type(DBCompressionType::Zlib);
+
+    Ok(DB::open_cf_descriptors(
+        &opts,
+        url,
+        vec![
+            transaction_cf_hash_to_trnsaction,
+            transaction_cf_hash_to_solid,
+            transaction_cf_hash_to_aprovees,
+            transaction_cf_hash_to_snapshot_index,
+            milestone_cf_hash_to_index,
+            milestone_cf_index_to_hash,
+            milestone_cf_index_to_delta,
+            ledger_cf_address_to_balance,
+            ledger_cf_index,
+            ledger_cf_spent_address,
+            transaction_cf_address_to_hash,
+            transaction_cf_bundle_to_hash,
+            transaction_cf_tag_to_hash,
+            transaction_cf_approvee_to_hash,
+            schema_cf_version,
+        ],
+    )?)
+}
+
+// Returns the index of the last milestone of a backup, checking that this version can open it and that its ledger
+// does not refer to a later milestone.
+fn validate_backup(db: &DB) -> Result<MilestoneIndex, RocksDbBackendError> {
+    let schema_cf_version = db.cf_handle(SCHEMA_CF_VERSION).unwrap();
+    let version = db
+        .get_cf(&schema_cf_version, SCHEMA_VERSION_KEY)?
+        .map_or(1, |buf| decode_u32(buf.as_ref()));
+
+    if version > SCHEMA_VERSION {
+        return Err(RocksDbBackendError::NewerSchemaVersion(version));
+    }
+
+    let milestone_cf_index_to_hash = db.cf_handle(MILESTONE_CF_INDEX_TO_HASH).unwrap();
+    let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+
+    // Indexes are little-endian encoded, iterating them is not in index order.
+    let last_index = db
+        .iterator_cf(&milestone_cf_index_to_hash, IteratorMode::Start)
+        .map(|(key, _)| decode_u32(key.as_ref()))
+        .max()
+        .ok_or_else(|| RocksDbBackendError::InvalidBackup("no milestone".to_string()))?;
+
+    if let Some(index_buf) = db.get_cf(&ledger_cf_index, LEDGER_INDEX_KEY)? {
+        let ledger_index = decode_u32(&index_buf);
+        if ledger_index > last_index {
+            return Err(RocksDbBackendError::InvalidBackup(format!(
+                "ledger index {} is past the last milestone {}",
+                ledger_index, last_index
+            )));
+        }
+    }
+
+    Ok(MilestoneIndex(last_index))
+}
+
+// Checkpoints are flat directories.
+fn copy_dir(from: &Path, to: &Path) -> Result<(), RocksDbBackendError> {
+    fs::create_dir_all(to)?;
+
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        fs::copy(entry.path(), to.join(entry.file_name()))?;
+    }
+
+    Ok(())
+}
+
 pub struct RocksDBBackendConnection {
-    db: Option<DB>,
+    db: Option<Arc<DB>>,
 }
 
 impl RocksDBBackendConnection {
@@ -117,44 +485,11 @@ impl Connection for RocksDBBackendConnection {
     type StorageError = RocksDbBackendError;
 
     async fn establish_connection(&mut self, url: &str) -> Result<(), RocksDbBackendError> {
-        let transaction_cf_hash_to_trnsaction =
-            ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_TRANSACTION, Options::default());
-        let transaction_cf_hash_to_solid =
-            ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_SOLID, Options::default());
-        let transaction_cf_hash_to_snapshot_index =
-            ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX, Options::default());
-
-        let transaction_cf_hash_to_aprovees =
-            ColumnFamilyDescriptor::new(TRANSACTION_CF_HASH_TO_APROVEES, Options::default());
-
-        let milestone_cf_hash_to_index = ColumnFamilyDescriptor::new(MILESTONE_CF_HASH_TO_INDEX, Options::default());
-        let milestone_cf_index_to_hash = ColumnFamilyDescriptor::new(MILESTONE_CF_INDEX_TO_HASH, Options::default());
-        let milestone_cf_hash_to_delta = ColumnFamilyDescriptor::new(MILESTONE_CF_HASH_TO_DELTA, Options::default());
-        let mut opts = Options::default();
-        // TODO - figure this out
-        opts.set_max_write_buffer_number(4);
-        opts.create_missing_column_families(true);
-        opts.create_if_missing(true);
-        opts.set_compaction_style(DBCompactionStyle::Universal);
-        opts.set_max_background_compactions(4);
-        opts.set_max_background_flushes(4);
-        opts.set_disable_auto_compactions(true);
-        opts.increase_parallelism(num_cpus::get() as i32);
-        opts.set_compression_type(DBCompressionType::Zlib);
-
-        self.db = Some(DB::open_cf_descriptors(
-            &opts,
-            url,
-            vec![
-                transaction_cf_hash_to_trnsaction,
-                transaction_cf_hash_to_solid,
-                transaction_cf_hash_to_aprovees,
-                transaction_cf_hash_to_snapshot_index,
-                milestone_cf_hash_to_index,
-                milestone_cf_index_to_hash,
-                milestone_cf_hash_to_delta,
-            ],
-        )?);
+        let db = open_db(url)?;
+
+        migrate(&db)?;
+
+        self.db = Some(Arc::new(db));
 
         Ok(())
     }
@@ -168,6 +503,17 @@ impl Connection for RocksDBBackendConnection {
 
 pub struct RocksDbBackendStorage(Storage<RocksDBBackendConnection>);
 
+impl RocksDbBackendStorage {
+    fn db(&self) -> &DB {
+        self.0.connection.db.as_ref().unwrap()
+    }
+
+    // For the blocking work that is moved off the executor.
+    fn shared_db(&self) -> Arc<DB> {
+        self.0.connection.db.as_ref().unwrap().clone()
+    }
+}
+
 #[async_trait]
 impl StorageBackend for RocksDbBackendStorage {
     type StorageError = RocksDbBackendError;
@@ -189,7 +535,7 @@ impl StorageBackend for RocksDbBackendStorage {
         Ok(())
     }
 
-    fn map_existing_transaction_hashes_to_approvers(&self) -> Result<HashesToApprovers, RocksDbBackendError> {
+    async fn map_existing_transaction_hashes_to_approvers(&self) -> Result<HashesToApprovers, RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
 
         let mut hash_to_approvers = HashMap::new();
@@ -212,7 +558,7 @@ impl StorageBackend for RocksDbBackendStorage {
         Ok(hash_to_approvers)
     }
 
-    fn map_missing_transaction_hashes_to_approvers(
+    async fn map_missing_transaction_hashes_to_approvers(
         &self,
         all_hashes: HashSet<Hash>,
     ) -> Result<MissingHashesToRCApprovers, RocksDbBackendError> {
@@ -246,37 +592,10 @@ impl StorageBackend for RocksDbBackendStorage {
     // Implement all methods here
     async fn insert_transaction(&self, tx_hash: Hash, tx: Transaction) -> Result<(), RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
 
-        let mut tx_trit_buf = TritBuf::<T1B1Buf>::zeros(Transaction::trit_len());
-        let mut aprovees_trit_buf = TritBuf::<T1B1Buf>::zeros(Hash::trit_len() * 2);
-
-        tx.into_trits_allocated(tx_trit_buf.as_slice_mut());
-        let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
-        let transaction_cf_hash_to_aprovees = db.cf_handle(TRANSACTION_CF_HASH_TO_APROVEES).unwrap();
-
-        let hash_buf = tx_hash.to_inner().encode::<T5B1Buf>();
-        db.put_cf(
-            &transaction_cf_hash_to_transaction,
-            cast_slice(hash_buf.as_i8_slice()),
-            cast_slice(tx_trit_buf.encode::<T5B1Buf>().as_i8_slice()),
-        )?;
-
-        let aprovees = Approvees {
-            trunk: tx.trunk(),
-            branch: tx.branch(),
-        };
-        aprovees.into_trits_allocated(aprovees_trit_buf.borrow_mut());
-
-        let mut write_options = WriteOptions::default();
-        write_options.set_sync(false);
-        write_options.disable_wal(true);
-
-        db.put_cf_opt(
-            &transaction_cf_hash_to_aprovees,
-            cast_slice(hash_buf.as_i8_slice()),
-            cast_slice(aprovees_trit_buf.encode::<T5B1Buf>().as_i8_slice()),
-            &write_options,
-        )?;
+        batch_transaction(db, &mut batch, &tx_hash, &tx);
+        db.write(batch)?;
 
         Ok(())
     }
@@ -284,32 +603,9 @@ impl StorageBackend for RocksDbBackendStorage {
     async fn insert_transactions(&self, transactions: HashMap<Hash, Transaction>) -> Result<(), Self::StorageError> {
         let db = self.0.connection.db.as_ref().unwrap();
         let mut batch = rocksdb::WriteBatch::default();
-        let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
-        let transaction_cf_hash_to_aprovees = db.cf_handle(TRANSACTION_CF_HASH_TO_APROVEES).unwrap();
-
-        let mut tx_trit_buf = TritBuf::<T1B1Buf>::zeros(Transaction::trit_len());
-        let mut aprovees_trit_buf = TritBuf::<T1B1Buf>::zeros(Hash::trit_len() * 2);
 
         for (tx_hash, tx) in transactions {
-            tx.into_trits_allocated(tx_trit_buf.as_slice_mut());
-            let hash_buf = tx_hash.to_inner().encode::<T5B1Buf>();
-            batch.put_cf(
-                &transaction_cf_hash_to_transaction,
-                cast_slice(hash_buf.as_i8_slice()),
-                cast_slice(tx_trit_buf.encode::<T5B1Buf>().as_i8_slice()),
-            );
-
-            let aprovees = Approvees {
-                trunk: tx.trunk(),
-                branch: tx.branch(),
-            };
-            aprovees.into_trits_allocated(aprovees_trit_buf.borrow_mut());
-
-            batch.put_cf(
-                &transaction_cf_hash_to_aprovees,
-                cast_slice(hash_buf.as_i8_slice()),
-                cast_slice(aprovees_trit_buf.encode::<T5B1Buf>().as_i8_slice()),
-            );
+            batch_transaction(db, &mut batch, &tx_hash, &tx);
         }
 
         let mut write_options = WriteOptions::default();
@@ -341,21 +637,12 @@ impl StorageBackend for RocksDbBackendStorage {
     ) -> Result<(), RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
         let mut batch = rocksdb::WriteBatch::default();
-        let transaction_cf_hash_to_solid = db.cf_handle(TRANSACTION_CF_HASH_TO_SOLID).unwrap();
         for hash in transaction_hashes {
-            let hash_buf = hash.to_inner().encode::<T5B1Buf>();
-            batch.put_cf(
-                &transaction_cf_hash_to_solid,
-                cast_slice(hash_buf.as_i8_slice()),
-                unsafe { mem::transmute::<bool, [u8; 1]>(true) },
-            );
+            batch_solid(db, &mut batch, &hash);
         }
 
-        let mut write_options = WriteOptions::default();
-        write_options.set_sync(false);
-        write_options.disable_wal(true);
-
-        db.write_opt(batch, &write_options)?;
+        // Unlike bulk loads, these updates can't be replayed after a crash, so they go through the write-ahead log.
+        db.write(batch)?;
 
         Ok(())
     }
@@ -367,21 +654,11 @@ impl StorageBackend for RocksDbBackendStorage {
     ) -> Result<(), RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
         let mut batch = rocksdb::WriteBatch::default();
-        let transaction_cf_hash_to_snapshot_index = db.cf_handle(TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX).unwrap();
         for hash in transaction_hashes {
-            let hash_buf = hash.to_inner().encode::<T5B1Buf>();
-            batch.put_cf(
-                &transaction_cf_hash_to_snapshot_index,
-                cast_slice(hash_buf.as_i8_slice()),
-                snapshot_index.to_le_bytes(),
-            );
+            batch_snapshot_index(db, &mut batch, &hash, snapshot_index);
         }
 
-        let mut write_options = WriteOptions::default();
-        write_options.set_sync(false);
-        write_options.disable_wal(true);
-
-        db.write_opt(batch, &write_options)?;
+        db.write(batch)?;
 
         Ok(())
     }
@@ -438,39 +715,67 @@ impl StorageBackend for RocksDbBackendStorage {
     async fn delete_transactions(&self, transaction_hashes: &HashSet<Hash>) -> Result<(), RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
         let mut batch = rocksdb::WriteBatch::default();
-        let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
 
         for hash in transaction_hashes {
-            let hash_buf = hash.to_inner().encode::<T5B1Buf>();
-            batch.delete_cf(&transaction_cf_hash_to_transaction, cast_slice(hash_buf.as_i8_slice()));
+            batch_delete_transaction(db, &mut batch, &encode_hash(hash))?;
         }
 
-        let mut write_options = WriteOptions::default();
-        write_options.set_sync(false);
-        write_options.disable_wal(true);
+        // Deletions go through the write-ahead log so that a crash can't bring pruned transactions back.
+        db.write(batch)?;
 
-        db.write_opt(batch, &write_options)?;
         Ok(())
     }
 
-    async fn insert_milestone(&self, milestone: Milestone) -> Result<(), RocksDbBackendError> {
+    async fn find_transaction_hashes_by_address(
+        &self,
+        address: &Address,
+    ) -> Result<HashSet<Hash>, RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
 
-        let milestone_cf_hash_to_index = db.cf_handle(MILESTONE_CF_HASH_TO_INDEX).unwrap();
-        let milestone_cf_index_to_hash = db.cf_handle(MILESTONE_CF_INDEX_TO_HASH).unwrap();
+        Ok(find_indexed_transaction_hashes(
+            db,
+            TRANSACTION_CF_ADDRESS_TO_HASH,
+            &encode_address(address),
+        ))
+    }
 
-        let hash_buf = milestone.hash().to_inner().encode::<T5B1Buf>();
-        db.put_cf(
-            &milestone_cf_hash_to_index,
-            cast_slice(hash_buf.as_i8_slice()),
-            milestone.index().to_le_bytes(),
-        )?;
+    async fn find_transaction_hashes_by_bundle(&self, bundle: Hash) -> Result<HashSet<Hash>, RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+
+        Ok(find_indexed_transaction_hashes(
+            db,
+            TRANSACTION_CF_BUNDLE_TO_HASH,
+            &encode_hash(&bundle),
+        ))
+    }
+
+    async fn find_transaction_hashes_by_tag(&self, tag: &Tag) -> Result<HashSet<Hash>, RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+
+        Ok(find_indexed_transaction_hashes(
+            db,
+            TRANSACTION_CF_TAG_TO_HASH,
+            cast_slice(tag.to_inner().encode::<T5B1Buf>().as_i8_slice()),
+        ))
+    }
+
+    async fn find_transaction_hashes_by_approvee(&self, approvee: Hash) -> Result<HashSet<Hash>, RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+
+        Ok(find_indexed_transaction_hashes(
+            db,
+            TRANSACTION_CF_APPROVEE_TO_HASH,
+            &encode_hash(&approvee),
+        ))
+    }
+
+    async fn insert_milestone(&self, milestone: Milestone) -> Result<(), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        batch_milestone(db, &mut batch, &milestone);
+        db.write(batch)?;
 
-        db.put_cf(
-            &milestone_cf_index_to_hash,
-            milestone.index().to_le_bytes(),
-            cast_slice(hash_buf.as_i8_slice()),
-        )?;
         Ok(())
     }
 
@@ -494,22 +799,41 @@ impl StorageBackend for RocksDbBackendStorage {
         ))
     }
 
+    async fn load_milestones(&self) -> Result<Vec<Milestone>, RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let milestone_cf_hash_to_index = db.cf_handle(MILESTONE_CF_HASH_TO_INDEX).unwrap();
+
+        let mut milestones = Vec::new();
+        let mut index_buf: [u8; 4] = [0; 4];
+
+        for (key, value) in db.iterator_cf(&milestone_cf_hash_to_index, IteratorMode::Start) {
+            index_buf.copy_from_slice(value.as_ref());
+            milestones.push(Milestone::new(
+                decode_hash(key.as_ref()),
+                MilestoneIndex(u32::from_le_bytes(index_buf)),
+            ));
+        }
+
+        Ok(milestones)
+    }
+
     async fn delete_milestones(&self, milestone_hashes: &HashSet<Hash>) -> Result<(), RocksDbBackendError> {
         let db = self.0.connection.db.as_ref().unwrap();
 
         let milestone_cf_hash_to_index = db.cf_handle(MILESTONE_CF_HASH_TO_INDEX).unwrap();
+        let milestone_cf_index_to_hash = db.cf_handle(MILESTONE_CF_INDEX_TO_HASH).unwrap();
         let mut batch = rocksdb::WriteBatch::default();
 
         for hash in milestone_hashes {
-            let hash_buf = hash.to_inner().encode::<T5B1Buf>();
-            batch.delete_cf(&milestone_cf_hash_to_index, cast_slice(hash_buf.as_i8_slice()));
+            let hash_buf = encode_hash(hash);
+            // The index is needed to delete the other direction of the mapping.
+            if let Some(index_buf) = db.get_cf(&milestone_cf_hash_to_index, &hash_buf)? {
+                batch.delete_cf(&milestone_cf_index_to_hash, index_buf);
+            }
+            batch.delete_cf(&milestone_cf_hash_to_index, &hash_buf);
         }
 
-        let mut write_options = WriteOptions::default();
-        write_options.set_sync(false);
-        write_options.disable_wal(true);
-
-        db.write_opt(batch, &write_options)?;
+        db.write(batch)?;
 
         Ok(())
     }
@@ -519,22 +843,326 @@ impl StorageBackend for RocksDbBackendStorage {
         state_delta: StateDeltaMap,
         index: MilestoneIndex,
     ) -> Result<(), RocksDbBackendError> {
-        // let db = self.0.connection.db.as_ref().unwrap();
-        // let milestone_cf_hash_to_delta = db.cf_handle(MILESTONE_CF_HASH_TO_DELTA).unwrap();
-        // // TODO - handle error, assert the milestone exists?
-        // let encoded: Vec<u8> = bincode::serialize(&state_delta).unwrap();
-        //
-        // db.put_cf(&milestone_cf_hash_to_delta, index.to_le_bytes(), encoded)?;
+        let db = self.0.connection.db.as_ref().unwrap();
+        let milestone_cf_index_to_delta = db.cf_handle(MILESTONE_CF_INDEX_TO_DELTA).unwrap();
+        let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        batch_balance_deltas(
+            db,
+            &mut batch,
+            state_delta
+                .address_to_delta
+                .iter()
+                .map(|(address, delta)| (address, *delta)),
+        )?;
+
+        batch.put_cf(&milestone_cf_index_to_delta, index.to_le_bytes(), state_delta.encode());
+        batch.put_cf(&ledger_cf_index, LEDGER_INDEX_KEY, index.to_le_bytes());
+
+        // The ledger is written through the WAL, unlike the tangle, since it can't be recovered from the network.
+        db.write(batch)?;
+
         Ok(())
     }
 
     async fn load_state_delta(&self, index: MilestoneIndex) -> Result<StateDeltaMap, RocksDbBackendError> {
-        // let db = self.0.connection.db.as_ref().unwrap();
-        // let milestone_cf_hash_to_delta = db.cf_handle(MILESTONE_CF_HASH_TO_DELTA).unwrap();
-        //
-        // let res = db.get_cf(&milestone_cf_hash_to_delta, index.to_le_bytes())?;
-        //
-        // Ok(bincode::deserialize(&res.unwrap()).unwrap())
-        Ok((Default::default()))
+        let db = self.0.connection.db.as_ref().unwrap();
+        let milestone_cf_index_to_delta = db.cf_handle(MILESTONE_CF_INDEX_TO_DELTA).unwrap();
+
+        match db.get_cf(&milestone_cf_index_to_delta, index.to_le_bytes())? {
+            Some(delta_buf) => decode_state_delta(&delta_buf, index),
+            None => Err(RocksDbBackendError::StateDeltaDoesNotExist),
+        }
+    }
+
+    async fn revert_state_delta(&self) -> Result<(MilestoneIndex, StateDeltaMap), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let milestone_cf_index_to_delta = db.cf_handle(MILESTONE_CF_INDEX_TO_DELTA).unwrap();
+        let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        let index = match db.get_cf(&ledger_cf_index, LEDGER_INDEX_KEY)? {
+            Some(index_buf) => MilestoneIndex(decode_u32(&index_buf)),
+            None => return Err(RocksDbBackendError::StateDeltaDoesNotExist),
+        };
+        let state_delta = match db.get_cf(&milestone_cf_index_to_delta, index.to_le_bytes())? {
+            Some(delta_buf) => decode_state_delta(&delta_buf, index)?,
+            None => return Err(RocksDbBackendError::StateDeltaDoesNotExist),
+        };
+
+        batch_balance_deltas(
+            db,
+            &mut batch,
+            state_delta
+                .address_to_delta
+                .iter()
+                .map(|(address, delta)| (address, -delta)),
+        )?;
+
+        batch.delete_cf(&milestone_cf_index_to_delta, index.to_le_bytes());
+        batch.put_cf(&ledger_cf_index, LEDGER_INDEX_KEY, (*index - 1).to_le_bytes());
+
+        db.write(batch)?;
+
+        Ok((index, state_delta))
+    }
+
+    async fn insert_balances(
+        &self,
+        balances: HashMap<Address, u64>,
+        index: MilestoneIndex,
+    ) -> Result<(), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_address_to_balance = db.cf_handle(LEDGER_CF_ADDRESS_TO_BALANCE).unwrap();
+        let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for (key, _) in db.iterator_cf(&ledger_cf_address_to_balance, IteratorMode::Start) {
+            batch.delete_cf(&ledger_cf_address_to_balance, key);
+        }
+
+        for (address, balance) in balances.iter().filter(|(_, balance)| **balance != 0) {
+            batch.put_cf(
+                &ledger_cf_address_to_balance,
+                encode_address(address),
+                balance.to_le_bytes(),
+            );
+        }
+
+        batch.put_cf(&ledger_cf_index, LEDGER_INDEX_KEY, index.to_le_bytes());
+
+        db.write(batch)?;
+
+        Ok(())
+    }
+
+    async fn clear_balances(&self) -> Result<(), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_address_to_balance = db.cf_handle(LEDGER_CF_ADDRESS_TO_BALANCE).unwrap();
+        let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for (key, _) in db.iterator_cf(&ledger_cf_address_to_balance, IteratorMode::Start) {
+            batch.delete_cf(&ledger_cf_address_to_balance, key);
+        }
+
+        batch.delete_cf(&ledger_cf_index, LEDGER_INDEX_KEY);
+
+        db.write(batch)?;
+
+        Ok(())
+    }
+
+    async fn append_balances(&self, balances: HashMap<Address, u64>) -> Result<(), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_address_to_balance = db.cf_handle(LEDGER_CF_ADDRESS_TO_BALANCE).unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for (address, balance) in balances.iter().filter(|(_, balance)| **balance != 0) {
+            batch.put_cf(
+                &ledger_cf_address_to_balance,
+                encode_address(address),
+                balance.to_le_bytes(),
+            );
+        }
+
+        db.write(batch)?;
+
+        Ok(())
+    }
+
+    async fn insert_ledger_index(&self, index: MilestoneIndex) -> Result<(), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+
+        db.put_cf(&ledger_cf_index, LEDGER_INDEX_KEY, index.to_le_bytes())?;
+
+        Ok(())
+    }
+
+    async fn load_balances(&self) -> Result<Option<(MilestoneIndex, HashMap<Address, u64>)>, RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_address_to_balance = db.cf_handle(LEDGER_CF_ADDRESS_TO_BALANCE).unwrap();
+        let ledger_cf_index = db.cf_handle(LEDGER_CF_INDEX).unwrap();
+
+        let index = match db.get_cf(&ledger_cf_index, LEDGER_INDEX_KEY)? {
+            Some(index_buf) => MilestoneIndex(decode_u32(&index_buf)),
+            None => return Ok(None),
+        };
+
+        let mut balances = HashMap::new();
+
+        for (key, value) in db.iterator_cf(&ledger_cf_address_to_balance, IteratorMode::Start) {
+            balances.insert(decode_stored_address(key.as_ref())?, decode_u64(value.as_ref()));
+        }
+
+        Ok(Some((index, balances)))
+    }
+
+    async fn insert_spent_addresses(&self, addresses: HashSet<Address>) -> Result<(), RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_spent_address = db.cf_handle(LEDGER_CF_SPENT_ADDRESS).unwrap();
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for address in addresses.iter() {
+            batch.put_cf(&ledger_cf_spent_address, encode_address(address), b"");
+        }
+
+        db.write(batch)?;
+
+        Ok(())
+    }
+
+    async fn load_spent_addresses(&self) -> Result<HashSet<Address>, RocksDbBackendError> {
+        let db = self.0.connection.db.as_ref().unwrap();
+        let ledger_cf_spent_address = db.cf_handle(LEDGER_CF_SPENT_ADDRESS).unwrap();
+
+        db.iterator_cf(&ledger_cf_spent_address, IteratorMode::Start)
+            .map(|(key, _)| decode_stored_address(key.as_ref()))
+            .collect()
+    }
+
+    async fn prune_below(
+        &self,
+        index: MilestoneIndex,
+        solid_entry_points: &HashSet<Hash>,
+    ) -> Result<(), RocksDbBackendError> {
+        let db = self.shared_db();
+        let solid_entry_points: HashSet<Vec<u8>> = solid_entry_points.iter().map(encode_hash).collect();
+
+        // Scans whole column families and compacts them, so it is kept off the executor.
+        task::spawn_blocking(move || -> Result<(), RocksDbBackendError> {
+            let mut batch = rocksdb::WriteBatch::default();
+
+            let transaction_cf_hash_to_transaction = db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap();
+            let transaction_cf_hash_to_snapshot_index = db.cf_handle(TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX).unwrap();
+            let milestone_cf_index_to_hash = db.cf_handle(MILESTONE_CF_INDEX_TO_HASH).unwrap();
+            let milestone_cf_hash_to_index = db.cf_handle(MILESTONE_CF_HASH_TO_INDEX).unwrap();
+
+            // A snapshot index of 0, or none, marks an unconfirmed transaction.
+            for (key, value) in db.iterator_cf(&transaction_cf_hash_to_snapshot_index, IteratorMode::Start) {
+                let snapshot_index = decode_u32(value.as_ref());
+                if snapshot_index != 0 && snapshot_index < *index && !solid_entry_points.contains(key.as_ref()) {
+                    batch_delete_transaction(&db, &mut batch, key.as_ref())?;
+                }
+            }
+
+            // Unconfirmed transactions attached before the milestone `index` can't be confirmed anymore.
+            let cutoff = match db.get_cf(&milestone_cf_index_to_hash, index.to_le_bytes())? {
+                Some(hash_buf) => db
+                    .get_cf(&transaction_cf_hash_to_transaction, hash_buf)?
+                    .map(|tx_buf| decode_transaction(&tx_buf).get_timestamp()),
+                None => None,
+            };
+
+            if let Some(cutoff) = cutoff {
+                for (key, value) in db.iterator_cf(&transaction_cf_hash_to_transaction, IteratorMode::Start) {
+                    let confirmed = db
+                        .get_cf(&transaction_cf_hash_to_snapshot_index, &key)?
+                        .map_or(false, |snapshot_index| decode_u32(&snapshot_index) != 0);
+                    if !confirmed
+                        && !solid_entry_points.contains(key.as_ref())
+                        && decode_transaction(&value).get_timestamp() < cutoff
+                    {
+                        batch_delete_transaction(&db, &mut batch, key.as_ref())?;
+                    }
+                }
+            }
+
+            for (key, value) in db.iterator_cf(&milestone_cf_index_to_hash, IteratorMode::Start) {
+                if decode_u32(key.as_ref()) < *index {
+                    batch.delete_cf(&milestone_cf_index_to_hash, &key);
+                    batch.delete_cf(&milestone_cf_hash_to_index, &value);
+                }
+            }
+
+            let milestone_cf_index_to_delta = db.cf_handle(MILESTONE_CF_INDEX_TO_DELTA).unwrap();
+
+            for (key, _) in db.iterator_cf(&milestone_cf_index_to_delta, IteratorMode::Start) {
+                if decode_u32(key.as_ref()) < *index {
+                    batch.delete_cf(&milestone_cf_index_to_delta, &key);
+                }
+            }
+
+            db.write(batch)?;
+
+            // Automatic compactions are disabled, the space of the deleted entries is only reclaimed here.
+            for cf in &[
+                TRANSACTION_CF_HASH_TO_TRANSACTION,
+                TRANSACTION_CF_HASH_TO_SOLID,
+                TRANSACTION_CF_HASH_TO_SNAPSHOT_INDEX,
+                TRANSACTION_CF_HASH_TO_APROVEES,
+                TRANSACTION_CF_ADDRESS_TO_HASH,
+                TRANSACTION_CF_BUNDLE_TO_HASH,
+                TRANSACTION_CF_TAG_TO_HASH,
+                TRANSACTION_CF_APPROVEE_TO_HASH,
+                MILESTONE_CF_HASH_TO_INDEX,
+                MILESTONE_CF_INDEX_TO_HASH,
+                MILESTONE_CF_INDEX_TO_DELTA,
+            ] {
+                db.compact_range_cf(&db.cf_handle(cf).unwrap(), None::<&[u8]>, None::<&[u8]>);
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn backup(&self, path: &str) -> Result<(), RocksDbBackendError> {
+        let db = self.shared_db();
+        let path = path.to_string();
+
+        // A checkpoint hard links the table files when on the same filesystem, so it is cheap and doesn't block writes,
+        // but it still flushes the memtables and touches the filesystem.
+        task::spawn_blocking(move || -> Result<(), RocksDbBackendError> {
+            Checkpoint::new(&db)?.create_checkpoint(path)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn restore(path: &str, url: &str) -> Result<MilestoneIndex, RocksDbBackendError> {
+        // Backups are checkpoint directories; archives have to be extracted beforehand.
+        if ARCHIVE_EXTENSIONS.iter().any(|extension| path.ends_with(extension)) {
+            return Err(RocksDbBackendError::UnsupportedBackupFormat(path.to_string()));
+        }
+
+        // The backup is validated on a copy, next to the database, that only replaces it once known to be usable.
+        let staging = format!("{}.restore", url);
+        if Path::new(&staging).exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        copy_dir(Path::new(path), Path::new(&staging))?;
+
+        let last_index = match open_db(&staging).and_then(|db| validate_backup(&db)) {
+            Ok(last_index) => last_index,
+            Err(e) => {
+                fs::remove_dir_all(&staging)?;
+                return Err(e);
+            }
+        };
+
+        // The database is moved aside rather than removed, so that it is still there if the copy can't take its place.
+        let previous = format!("{}.previous", url);
+        if Path::new(&previous).exists() {
+            fs::remove_dir_all(&previous)?;
+        }
+        let replaced = Path::new(url).exists();
+        if replaced {
+            fs::rename(url, &previous)?;
+        }
+        if let Err(e) = fs::rename(&staging, url) {
+            if replaced {
+                fs::rename(&previous, url)?;
+            }
+            return Err(e.into());
+        }
+        if replaced {
+            fs::remove_dir_all(&previous)?;
+        }
+
+        Ok(last_index)
     }
 }
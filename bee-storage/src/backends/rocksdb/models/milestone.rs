@@ -11,8 +11,8 @@
 
 use crate::{backends::rocksdb::RocksDbBackendError, models::MilestoneStorage};
 
-use bee_protocol::Milestone;
-use bee_transaction::Hash;
+use bee_crypto::ternary::Hash;
+use bee_tangle::Milestone;
 
 use async_trait::async_trait;
 
@@ -32,10 +32,292 @@ impl TestableStorage for RocksDbBackendStorage {
 #[cfg(test)]
 mod tests {
 
-    use crate::{backends::rocksdb::RocksDbBackendStorage, tests::test::StorageTestRunner};
+    use crate::{
+        backends::rocksdb::{
+            decode_hash, decode_transaction, encode_hash, RocksDbBackendError, RocksDbBackendStorage,
+            RocksDbBatchWriter, MILESTONE_CF_HASH_TO_INDEX, MILESTONE_CF_INDEX_TO_DELTA, MILESTONE_CF_INDEX_TO_HASH,
+            SCHEMA_CF_VERSION, SCHEMA_VERSION, SCHEMA_VERSION_KEY, TRANSACTION_CF_HASH_TO_TRANSACTION,
+        },
+        config::StorageConfig,
+        storage::{StateDeltaMap, StorageBackend},
+        tests::test::StorageTestRunner,
+    };
+
+    use bee_tangle::MilestoneIndex;
+    use bee_transaction::{bundled::Address, TransactionVertex};
+
+    use std::{
+        env,
+        path::Path,
+        process::{self, Command},
+        sync::Arc,
+    };
+
+    use futures::executor::block_on;
+    use rocksdb::IteratorMode;
 
     #[test]
     fn test_all() {
         StorageTestRunner::<RocksDbBackendStorage>::run_all_tests();
     }
+
+    #[test]
+    fn test_newer_schema_version() {
+        const DB_NAME: &str = "test_db_newer_schema_version";
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+        let db = storage.0.connection.db.as_ref().unwrap();
+        let schema_cf_version = db.cf_handle(SCHEMA_CF_VERSION).unwrap();
+        db.put_cf(
+            &schema_cf_version,
+            SCHEMA_VERSION_KEY,
+            (SCHEMA_VERSION + 1).to_le_bytes(),
+        )
+        .unwrap();
+        drop(storage);
+
+        let mut storage = RocksDbBackendStorage::new();
+        let res = block_on(storage.establish_connection(DB_NAME));
+        drop(storage);
+        rocksdb::DB::destroy(&rocksdb::Options::default(), DB_NAME).unwrap();
+
+        match res {
+            Err(RocksDbBackendError::NewerSchemaVersion(version)) => assert_eq!(version, SCHEMA_VERSION + 1),
+            _ => panic!("a database with a newer schema version was opened"),
+        }
+    }
+
+    // Set in the child process that aborts in the middle of a batch.
+    const CRASH_CHILD_VAR: &str = "BEE_STORAGE_TEST_CRASH_CHILD";
+
+    #[test]
+    fn test_batch_writer_crash_consistency() {
+        const DB_NAME: &str = "test_db_batch_writer_crash_consistency";
+
+        if env::var_os(CRASH_CHILD_VAR).is_some() {
+            let mut storage = RocksDbBackendStorage::new();
+            block_on(storage.establish_connection(DB_NAME)).unwrap();
+            let storage = Arc::new(storage);
+            let config = StorageConfig::build().batch_size(100).sync(false).finish();
+            let mut writer = RocksDbBatchWriter::new(storage.clone(), config);
+
+            for _ in 0..10 {
+                let (hash, tx) = bee_test::transaction::create_random_tx();
+                block_on(writer.insert_transaction(hash, tx)).unwrap();
+                block_on(writer.set_solid(vec![hash])).unwrap();
+            }
+            block_on(writer.flush()).unwrap();
+
+            for _ in 0..10 {
+                let (hash, tx) = bee_test::transaction::create_random_tx();
+                block_on(writer.insert_transaction(hash, tx)).unwrap();
+                block_on(writer.set_solid(vec![hash])).unwrap();
+            }
+            assert_eq!(writer.len(), 20);
+
+            // Neither the writer nor the database get a chance to clean up.
+            process::abort();
+        }
+
+        let status = Command::new(env::current_exe().unwrap())
+            .args(&[
+                "backends::rocksdb::test::tests::test_batch_writer_crash_consistency",
+                "--exact",
+                "--test-threads=1",
+            ])
+            .env(CRASH_CHILD_VAR, "1")
+            .status()
+            .unwrap();
+        assert!(!status.success(), "the child process did not abort");
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+
+        // Only the flushed batch survived, each write of it in full.
+        let stored: Vec<_> = {
+            let db = storage.db();
+            db.iterator_cf(
+                db.cf_handle(TRANSACTION_CF_HASH_TO_TRANSACTION).unwrap(),
+                IteratorMode::Start,
+            )
+            .map(|(key, value)| (decode_hash(&key), decode_transaction(&value)))
+            .collect()
+        };
+        assert_eq!(stored.len(), 10);
+        for (hash, tx) in stored.iter() {
+            assert_eq!(
+                block_on(storage.get_transactions_solid_state(vec![*hash])).unwrap(),
+                vec![true]
+            );
+            assert!(block_on(storage.find_transaction_hashes_by_approvee(*tx.trunk()))
+                .unwrap()
+                .contains(hash));
+        }
+
+        drop(storage);
+        rocksdb::DB::destroy(&rocksdb::Options::default(), DB_NAME).unwrap();
+    }
+
+    #[test]
+    fn test_delete_milestone_indexes() {
+        const DB_NAME: &str = "test_db_delete_milestone_indexes";
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+
+        let milestone = bee_test::milestone::create_random_milestone(MilestoneIndex(3));
+        let hash = *milestone.hash();
+        block_on(storage.insert_milestone(milestone)).unwrap();
+        block_on(storage.delete_milestones(&vec![hash].into_iter().collect())).unwrap();
+
+        let db = storage.db();
+        assert!(db
+            .get_cf(db.cf_handle(MILESTONE_CF_HASH_TO_INDEX).unwrap(), encode_hash(&hash))
+            .unwrap()
+            .is_none());
+        assert!(db
+            .get_cf(db.cf_handle(MILESTONE_CF_INDEX_TO_HASH).unwrap(), 3u32.to_le_bytes())
+            .unwrap()
+            .is_none());
+
+        drop(storage);
+        rocksdb::DB::destroy(&rocksdb::Options::default(), DB_NAME).unwrap();
+    }
+
+    #[test]
+    fn test_load_truncated_state_delta() {
+        const DB_NAME: &str = "test_db_load_truncated_state_delta";
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+
+        let mut state_delta = StateDeltaMap::default();
+        state_delta
+            .address_to_delta
+            .insert(bee_test::field::rand_trits_field::<Address>(), 42);
+        let mut delta_buf = state_delta.encode();
+        delta_buf.pop();
+
+        let res = {
+            let db = storage.db();
+            db.put_cf(
+                db.cf_handle(MILESTONE_CF_INDEX_TO_DELTA).unwrap(),
+                5u32.to_le_bytes(),
+                delta_buf,
+            )
+            .unwrap();
+            block_on(storage.load_state_delta(MilestoneIndex(5)))
+        };
+
+        drop(storage);
+        rocksdb::DB::destroy(&rocksdb::Options::default(), DB_NAME).unwrap();
+
+        match res {
+            Err(RocksDbBackendError::CorruptedData(_)) => {}
+            _ => panic!("a truncated state delta was loaded"),
+        }
+    }
+
+    #[test]
+    fn test_backup_restore() {
+        const DB_NAME: &str = "test_db_backup_restore";
+        const BACKUP_NAME: &str = "test_db_backup_restore_backup";
+        const RESTORED_NAME: &str = "test_db_backup_restore_restored";
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+
+        let (backed_up_hash, backed_up_tx) = bee_test::transaction::create_random_tx();
+        let (later_hash, later_tx) = bee_test::transaction::create_random_tx();
+        block_on(storage.insert_transaction(backed_up_hash, backed_up_tx.clone())).unwrap();
+        block_on(storage.insert_milestone(bee_test::milestone::create_random_milestone(MilestoneIndex(7)))).unwrap();
+        block_on(storage.backup(BACKUP_NAME)).unwrap();
+        block_on(storage.insert_transaction(later_hash, later_tx)).unwrap();
+        drop(storage);
+
+        assert_eq!(
+            block_on(RocksDbBackendStorage::restore(BACKUP_NAME, RESTORED_NAME)).unwrap(),
+            MilestoneIndex(7)
+        );
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(RESTORED_NAME)).unwrap();
+        assert_eq!(
+            block_on(storage.find_transaction(backed_up_hash)).unwrap(),
+            backed_up_tx
+        );
+        assert!(block_on(storage.find_transaction(later_hash)).is_err());
+        drop(storage);
+
+        for name in &[DB_NAME, BACKUP_NAME, RESTORED_NAME] {
+            rocksdb::DB::destroy(&rocksdb::Options::default(), name).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_restore_without_milestone() {
+        const DB_NAME: &str = "test_db_restore_without_milestone";
+        const BACKUP_NAME: &str = "test_db_restore_without_milestone_backup";
+        const RESTORED_NAME: &str = "test_db_restore_without_milestone_restored";
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+        block_on(storage.backup(BACKUP_NAME)).unwrap();
+        drop(storage);
+
+        let res = block_on(RocksDbBackendStorage::restore(BACKUP_NAME, RESTORED_NAME));
+        let restored = Path::new(RESTORED_NAME).exists();
+
+        for name in &[DB_NAME, BACKUP_NAME] {
+            rocksdb::DB::destroy(&rocksdb::Options::default(), name).unwrap();
+        }
+
+        match res {
+            Err(RocksDbBackendError::InvalidBackup(_)) => assert!(!restored),
+            _ => panic!("a backup without milestone was restored"),
+        }
+    }
+
+    #[test]
+    fn test_restore_over_database() {
+        const DB_NAME: &str = "test_db_restore_over_database";
+        const BACKUP_NAME: &str = "test_db_restore_over_database_backup";
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+
+        let (later_hash, later_tx) = bee_test::transaction::create_random_tx();
+        block_on(storage.insert_milestone(bee_test::milestone::create_random_milestone(MilestoneIndex(3)))).unwrap();
+        block_on(storage.backup(BACKUP_NAME)).unwrap();
+        block_on(storage.insert_transaction(later_hash, later_tx)).unwrap();
+        drop(storage);
+
+        assert_eq!(
+            block_on(RocksDbBackendStorage::restore(BACKUP_NAME, DB_NAME)).unwrap(),
+            MilestoneIndex(3)
+        );
+        assert!(!Path::new(&format!("{}.previous", DB_NAME)).exists());
+
+        let mut storage = RocksDbBackendStorage::new();
+        block_on(storage.establish_connection(DB_NAME)).unwrap();
+        assert!(block_on(storage.find_transaction(later_hash)).is_err());
+        drop(storage);
+
+        for name in &[DB_NAME, BACKUP_NAME] {
+            rocksdb::DB::destroy(&rocksdb::Options::default(), name).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_restore_archive() {
+        match block_on(RocksDbBackendStorage::restore(
+            "test_db_restore_archive.tar.gz",
+            "test_db_restore_archive",
+        )) {
+            Err(RocksDbBackendError::UnsupportedBackupFormat(_)) => {}
+            _ => panic!("an archive was restored"),
+        }
+        assert!(!Path::new("test_db_restore_archive").exists());
+    }
 }
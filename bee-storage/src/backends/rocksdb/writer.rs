@@ -0,0 +1,230 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use super::{
+    batch_milestone, batch_snapshot_index, batch_solid, batch_transaction, RocksDbBackendError, RocksDbBackendStorage,
+};
+use crate::{config::StorageConfig, worker::StorageWorkerEvent};
+
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
+use bee_transaction::bundled::BundledTransaction as Transaction;
+
+use std::{
+    mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_std::task;
+use futures::{
+    channel::{mpsc, oneshot},
+    future::FutureExt,
+    select,
+    stream::StreamExt,
+};
+use log::{error, info};
+use rocksdb::{WriteBatch, WriteOptions};
+
+/// Groups writes into a single `WriteBatch`, flushed atomically once it holds `batch_size` writes or is older than the
+/// flush interval. Flushes go through the write-ahead log, so a crash loses at most the unflushed writes and never part
+/// of a batch. A batch that fails to be written is kept and written again by the next flush.
+pub struct RocksDbBatchWriter {
+    storage: Arc<RocksDbBackendStorage>,
+    config: StorageConfig,
+    // The writes are only turned into a `WriteBatch` when flushed, so that a failed batch can be written again.
+    writes: Vec<StorageWorkerEvent>,
+    len: usize,
+    oldest: Option<Instant>,
+    listener: Option<Box<dyn Fn(&[Hash]) + Send>>,
+}
+
+impl RocksDbBatchWriter {
+    pub fn new(storage: Arc<RocksDbBackendStorage>, config: StorageConfig) -> Self {
+        Self {
+            storage,
+            config,
+            writes: Vec::new(),
+            len: 0,
+            oldest: None,
+            listener: None,
+        }
+    }
+
+    /// Calls `listener` with the hashes of the transactions of every batch successfully flushed.
+    pub fn on_flush(mut self, listener: impl Fn(&[Hash]) + Send + 'static) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    /// Number of writes waiting in the batch.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub async fn insert_transaction(
+        &mut self,
+        hash: Hash,
+        transaction: Transaction,
+    ) -> Result<(), RocksDbBackendError> {
+        self.add(StorageWorkerEvent::Transaction(hash, transaction), 1).await
+    }
+
+    pub async fn set_solid(&mut self, hashes: Vec<Hash>) -> Result<(), RocksDbBackendError> {
+        let len = hashes.len();
+        self.add(StorageWorkerEvent::Solid(hashes), len).await
+    }
+
+    pub async fn set_snapshot_index(
+        &mut self,
+        hashes: Vec<Hash>,
+        index: MilestoneIndex,
+    ) -> Result<(), RocksDbBackendError> {
+        let len = hashes.len();
+        self.add(StorageWorkerEvent::SnapshotIndex(hashes, index), len).await
+    }
+
+    pub async fn insert_milestone(&mut self, milestone: Milestone) -> Result<(), RocksDbBackendError> {
+        self.add(StorageWorkerEvent::Milestone(milestone), 1).await
+    }
+
+    async fn add(&mut self, write: StorageWorkerEvent, len: usize) -> Result<(), RocksDbBackendError> {
+        self.writes.push(write);
+        self.len += len;
+        self.oldest.get_or_insert_with(Instant::now);
+
+        if self.len >= self.config.batch_size() {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the pending batch as a single atomic operation. If it fails, the batch is kept for the next flush.
+    pub async fn flush(&mut self) -> Result<(), RocksDbBackendError> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let db = self.storage.shared_db();
+        let sync = self.config.sync();
+        let writes = mem::take(&mut self.writes);
+
+        let (writes, res) = task::spawn_blocking(move || {
+            let mut batch = WriteBatch::default();
+
+            for write in writes.iter() {
+                match write {
+                    StorageWorkerEvent::Transaction(hash, transaction) => {
+                        batch_transaction(&db, &mut batch, hash, transaction)
+                    }
+                    StorageWorkerEvent::Milestone(milestone) => batch_milestone(&db, &mut batch, milestone),
+                    StorageWorkerEvent::Solid(hashes) => {
+                        for hash in hashes {
+                            batch_solid(&db, &mut batch, hash);
+                        }
+                    }
+                    StorageWorkerEvent::SnapshotIndex(hashes, index) => {
+                        for hash in hashes {
+                            batch_snapshot_index(&db, &mut batch, hash, *index);
+                        }
+                    }
+                }
+            }
+
+            let mut write_options = WriteOptions::default();
+            write_options.set_sync(sync);
+
+            let res = db.write_opt(batch, &write_options);
+
+            (writes, res)
+        })
+        .await;
+
+        if let Err(e) = res {
+            self.writes = writes;
+            // Waits for another flush interval rather than retrying right away.
+            self.oldest = Some(Instant::now());
+            return Err(e.into());
+        }
+
+        self.len = 0;
+        self.oldest = None;
+
+        if let Some(listener) = &self.listener {
+            let transactions = writes
+                .iter()
+                .filter_map(|write| match write {
+                    StorageWorkerEvent::Transaction(hash, _) => Some(*hash),
+                    _ => None,
+                })
+                .collect::<Vec<Hash>>();
+            listener(&transactions);
+        }
+
+        Ok(())
+    }
+
+    async fn process(&mut self, event: StorageWorkerEvent) {
+        let len = match &event {
+            StorageWorkerEvent::Solid(hashes) | StorageWorkerEvent::SnapshotIndex(hashes, _) => hashes.len(),
+            _ => 1,
+        };
+
+        if let Err(e) = self.add(event, len).await {
+            error!("Flushing write batch failed: {:?}.", e);
+        }
+    }
+
+    pub async fn run(mut self, receiver: mpsc::Receiver<StorageWorkerEvent>, shutdown: oneshot::Receiver<()>) {
+        info!("Running.");
+
+        let mut receiver_fused = receiver.fuse();
+        let mut shutdown_fused = shutdown.fuse();
+
+        loop {
+            // Flushes when the oldest pending write has waited for the flush interval.
+            let timeout = match self.oldest {
+                Some(oldest) => self
+                    .config
+                    .flush_interval()
+                    .checked_sub(oldest.elapsed())
+                    .unwrap_or_else(|| Duration::from_secs(0)),
+                None => self.config.flush_interval(),
+            };
+            let mut flush_timeout = Box::pin(task::sleep(timeout)).fuse();
+
+            select! {
+                event = receiver_fused.next() => {
+                    if let Some(event) = event {
+                        self.process(event).await;
+                    }
+                },
+                _ = flush_timeout => {
+                    if let Err(e) = self.flush().await {
+                        error!("Flushing write batch failed: {:?}.", e);
+                    }
+                },
+                _ = shutdown_fused => break
+            }
+        }
+
+        if let Err(e) = self.flush().await {
+            error!("Flushing write batch failed, {} writes are lost: {:?}.", self.len, e);
+        }
+
+        info!("Stopped.");
+    }
+}
@@ -0,0 +1,690 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+// Query logic shared by the Postgres and SQLite flavours. Fetching rows goes through the query extension traits of each
+// database, so the implementation is generated for each of them, in a module providing:
+// - the statements of its dialect and its `MIGRATIONS` and `SCHEMA_VERSION`;
+// - the `PgQueryAs` or `SqliteQueryAs` trait and the `AttachmentData`, wrappers, `encode_buffer` and storage imports;
+// - a connection type holding an optional `connection_pool`;
+// - `backup_to` and `restore_from` methods implementing `backup` and `restore`.
+// `$timestamp` is the integer type timestamps are bound as.
+macro_rules! impl_sqlx_backend {
+    ($storage:ident, $connection:ident, $pool:ty, $timestamp:ty) => {
+        // Databases without a version record are at version 0; the initial migration only creates missing tables so
+        // that it also adopts databases set up before versioning.
+        async fn migrate(pool: &$pool) -> Result<(), SqlxBackendError> {
+            let mut conn_transaction = pool.begin().await?;
+
+            sqlx::query(CREATE_SCHEMA_VERSION_TABLE_STATEMENT)
+                .execute(&mut conn_transaction)
+                .await?;
+            let version: Option<SchemaVersionWrapper> = sqlx::query_as(SELECT_SCHEMA_VERSION_STATEMENT)
+                .fetch_optional(&mut conn_transaction)
+                .await?;
+
+            conn_transaction.commit().await?;
+
+            let version = version.map_or(0, |version| version.0);
+
+            if version > SCHEMA_VERSION {
+                return Err(SqlxBackendError::NewerSchemaVersion(version));
+            }
+
+            // Each migration is applied along with its version bump, an interrupted upgrade being resumed on the next
+            // start. Migrations are plain SQL scripts, executed as a whole since statements may contain semicolons.
+            for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+                let mut conn_transaction = pool.begin().await?;
+
+                sqlx::Executor::execute(&mut conn_transaction, *migration).await?;
+
+                sqlx::query(UPSERT_SCHEMA_VERSION_STATEMENT)
+                    .bind(index as i32 + 1)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                conn_transaction.commit().await?;
+            }
+
+            Ok(())
+        }
+
+        impl $storage {
+            fn pool(&self) -> &$pool {
+                self.0
+                    .connection
+                    .connection_pool
+                    .as_ref()
+                    .expect(CONNECTION_NOT_INITIALIZED)
+            }
+
+            // Pages through the trunks and branches of all the transactions.
+            async fn fetch_attachment_data(&self) -> Result<Vec<AttachmentData>, SqlxBackendError> {
+                const MAX_RECORDS_AT_ONCE: i32 = 1000;
+
+                let mut pool = self.pool();
+                let mut start: i32 = 0;
+                let mut attachment_data = Vec::new();
+
+                loop {
+                    let rows: Vec<AttachmentData> = sqlx::query_as(SELECT_HASH_BRANCH_TRUNK_LIMIT_STATEMENT)
+                        .bind(start)
+                        .bind(MAX_RECORDS_AT_ONCE)
+                        .fetch_all(&mut pool)
+                        .await?;
+                    let len = rows.len();
+
+                    attachment_data.extend(rows);
+
+                    if len < MAX_RECORDS_AT_ONCE as usize {
+                        break;
+                    }
+
+                    start += MAX_RECORDS_AT_ONCE;
+                }
+
+                Ok(attachment_data)
+            }
+        }
+
+        #[async_trait]
+        impl StorageBackend for $storage {
+            type StorageError = SqlxBackendError;
+
+            fn new() -> Self {
+                let stor = Storage {
+                    connection: $connection::new(),
+                };
+                $storage(stor)
+            }
+
+            async fn establish_connection(&mut self, url: &str) -> Result<(), SqlxBackendError> {
+                self.0.connection.establish_connection(url).await?;
+                Ok(())
+            }
+
+            async fn destroy_connection(&mut self) -> Result<(), SqlxBackendError> {
+                self.0.connection.destroy_connection().await?;
+                Ok(())
+            }
+
+            async fn map_existing_transaction_hashes_to_approvers(
+                &self,
+            ) -> Result<HashesToApprovers, SqlxBackendError> {
+                let mut hash_to_approvers = HashMap::new();
+
+                for attachment_data in self.fetch_attachment_data().await? {
+                    hash_to_approvers
+                        .entry(attachment_data.branch)
+                        .or_insert_with(HashSet::new)
+                        .insert(attachment_data.hash);
+                    hash_to_approvers
+                        .entry(attachment_data.trunk)
+                        .or_insert_with(HashSet::new)
+                        .insert(attachment_data.hash);
+                }
+
+                Ok(hash_to_approvers)
+            }
+
+            async fn map_missing_transaction_hashes_to_approvers(
+                &self,
+                all_hashes: HashSet<Hash>,
+            ) -> Result<MissingHashesToRCApprovers, SqlxBackendError> {
+                // The rows are all fetched before building the map, whose `Rc`s must not be held across an await.
+                let attachment_data = self.fetch_attachment_data().await?;
+                let mut missing_to_approvers = HashMap::new();
+
+                for attachment_data in attachment_data {
+                    let approver = Rc::new(attachment_data.hash);
+
+                    if !all_hashes.contains(&attachment_data.branch) {
+                        missing_to_approvers
+                            .entry(attachment_data.branch)
+                            .or_insert_with(HashSet::new)
+                            .insert(approver.clone());
+                    }
+
+                    if !all_hashes.contains(&attachment_data.trunk) {
+                        missing_to_approvers
+                            .entry(attachment_data.trunk)
+                            .or_insert_with(HashSet::new)
+                            .insert(approver.clone());
+                    }
+                }
+
+                Ok(missing_to_approvers)
+            }
+
+            async fn insert_transaction(&self, tx_hash: Hash, tx: Transaction) -> Result<(), SqlxBackendError> {
+                let mut transactions = HashMap::new();
+                transactions.insert(tx_hash, tx);
+
+                self.insert_transactions(transactions).await
+            }
+
+            async fn insert_transactions(
+                &self,
+                transactions: HashMap<Hash, Transaction>,
+            ) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for (tx_hash, tx) in transactions {
+                    sqlx::query(INSERT_TRANSACTION_STATEMENT)
+                        .bind(encode_buffer(tx.payload().to_inner().encode::<T5B1Buf>()))
+                        .bind(encode_buffer(tx.address().to_inner().encode::<T5B1Buf>()))
+                        .bind(*tx.value().to_inner())
+                        .bind(encode_buffer(tx.obsolete_tag().to_inner().encode::<T5B1Buf>()))
+                        .bind(*tx.timestamp().to_inner() as $timestamp)
+                        .bind(*tx.index().to_inner() as i32)
+                        .bind(*tx.last_index().to_inner() as i32)
+                        .bind(encode_buffer(tx.bundle().to_inner().encode::<T5B1Buf>()))
+                        .bind(encode_buffer(tx.trunk().to_inner().encode::<T5B1Buf>()))
+                        .bind(encode_buffer(tx.branch().to_inner().encode::<T5B1Buf>()))
+                        .bind(encode_buffer(tx.tag().to_inner().encode::<T5B1Buf>()))
+                        .bind(*tx.attachment_ts().to_inner() as $timestamp)
+                        .bind(*tx.attachment_lbts().to_inner() as $timestamp)
+                        .bind(*tx.attachment_ubts().to_inner() as $timestamp)
+                        .bind(encode_buffer(tx.nonce().to_inner().encode::<T5B1Buf>()))
+                        .bind(encode_buffer(tx_hash.to_inner().encode::<T5B1Buf>()))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn find_transaction(&self, tx_hash: Hash) -> Result<Transaction, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let tx_wrapper: TransactionWrapper = sqlx::query_as(FIND_TRANSACTION_BY_HASH_STATEMENT)
+                    .bind(encode_buffer(tx_hash.to_inner().encode::<T5B1Buf>()))
+                    .fetch_one(&mut pool)
+                    .await?;
+
+                Ok(tx_wrapper.0)
+            }
+
+            async fn update_transactions_set_solid(
+                &self,
+                transaction_hashes: HashSet<Hash>,
+            ) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for hash in transaction_hashes.iter() {
+                    sqlx::query(UPDATE_SET_SOLID_STATEMENT)
+                        .bind(encode_buffer(hash.to_inner().encode::<T5B1Buf>()))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn update_transactions_set_snapshot_index(
+                &self,
+                transaction_hashes: HashSet<Hash>,
+                snapshot_index: MilestoneIndex,
+            ) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for hash in transaction_hashes.iter() {
+                    sqlx::query(UPDATE_SNAPSHOT_INDEX_STATEMENT)
+                        .bind(*snapshot_index as i32)
+                        .bind(encode_buffer(hash.to_inner().encode::<T5B1Buf>()))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn get_transactions_solid_state(
+                &self,
+                transaction_hashes: Vec<Hash>,
+            ) -> Result<Vec<bool>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let mut solid_states = vec![false; transaction_hashes.len()];
+
+                let statement = select_solid_states_by_hashes_statement(transaction_hashes.len());
+                let mut query = sqlx::query_as(&statement);
+
+                for hash in transaction_hashes {
+                    query = query.bind(encode_buffer(hash.to_inner().encode::<T5B1Buf>()))
+                }
+
+                let solid_state_wrapper_vec: Vec<SolidStateWrapper> = query.fetch_all(&mut pool).await?;
+                for (index, solid_state_wrapper) in solid_state_wrapper_vec.iter().enumerate() {
+                    solid_states[index] = solid_state_wrapper.0;
+                }
+
+                Ok(solid_states)
+            }
+
+            async fn get_transactions_snapshot_index(
+                &self,
+                transaction_hashes: Vec<Hash>,
+            ) -> Result<Vec<u32>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let mut snapshot_indexes: Vec<u32> = vec![0; transaction_hashes.len()];
+
+                let statement = select_snapshot_indexes_by_hashes_statement(transaction_hashes.len());
+                let mut query = sqlx::query_as(&statement);
+
+                for hash in transaction_hashes {
+                    query = query.bind(encode_buffer(hash.to_inner().encode::<T5B1Buf>()))
+                }
+
+                let snapshot_index_wrapper_vec: Vec<SnapshotIndexWrapper> = query.fetch_all(&mut pool).await?;
+                for (index, snapshot_index_wrapper) in snapshot_index_wrapper_vec.iter().enumerate() {
+                    snapshot_indexes[index] = snapshot_index_wrapper.0;
+                }
+
+                Ok(snapshot_indexes)
+            }
+
+            async fn delete_transactions(&self, transaction_hashes: &HashSet<Hash>) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for hash in transaction_hashes.iter() {
+                    sqlx::query(DELETE_TRANSACTION_STATEMENT)
+                        .bind(encode_buffer(hash.to_inner().encode::<T5B1Buf>()))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn find_transaction_hashes_by_address(
+                &self,
+                address: &Address,
+            ) -> Result<HashSet<Hash>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let hashes: Vec<HashWrapper> = sqlx::query_as(SELECT_HASHES_BY_ADDRESS_STATEMENT)
+                    .bind(encode_address(address))
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(hashes.into_iter().map(|hash| hash.0).collect())
+            }
+
+            async fn find_transaction_hashes_by_bundle(&self, bundle: Hash) -> Result<HashSet<Hash>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let hashes: Vec<HashWrapper> = sqlx::query_as(SELECT_HASHES_BY_BUNDLE_STATEMENT)
+                    .bind(encode_buffer(bundle.to_inner().encode::<T5B1Buf>()))
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(hashes.into_iter().map(|hash| hash.0).collect())
+            }
+
+            async fn find_transaction_hashes_by_tag(&self, tag: &Tag) -> Result<HashSet<Hash>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let hashes: Vec<HashWrapper> = sqlx::query_as(SELECT_HASHES_BY_TAG_STATEMENT)
+                    .bind(encode_buffer(tag.to_inner().encode::<T5B1Buf>()))
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(hashes.into_iter().map(|hash| hash.0).collect())
+            }
+
+            async fn find_transaction_hashes_by_approvee(
+                &self,
+                approvee: Hash,
+            ) -> Result<HashSet<Hash>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let hashes: Vec<HashWrapper> = sqlx::query_as(SELECT_HASHES_BY_APPROVEE_STATEMENT)
+                    .bind(encode_buffer(approvee.to_inner().encode::<T5B1Buf>()))
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(hashes.into_iter().map(|hash| hash.0).collect())
+            }
+
+            async fn insert_milestone(&self, milestone: Milestone) -> Result<(), SqlxBackendError> {
+                let mut pool = self.pool();
+
+                sqlx::query(INSERT_MILESTONE_STATEMENT)
+                    .bind(*milestone.index() as i32)
+                    .bind(encode_buffer(milestone.hash().to_inner().encode::<T5B1Buf>()))
+                    .execute(&mut pool)
+                    .await?;
+
+                Ok(())
+            }
+
+            async fn find_milestone(&self, milestone_hash: Hash) -> Result<Milestone, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let milestone_wrapper: MilestoneWrapper = sqlx::query_as(FIND_MILESTONE_BY_HASH_STATEMENT)
+                    .bind(encode_buffer(milestone_hash.to_inner().encode::<T5B1Buf>()))
+                    .fetch_one(&mut pool)
+                    .await?;
+
+                Ok(milestone_wrapper.0)
+            }
+
+            async fn load_milestones(&self) -> Result<Vec<Milestone>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let milestones: Vec<MilestoneWrapper> = sqlx::query_as(SELECT_MILESTONES_STATEMENT)
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(milestones.into_iter().map(|milestone| milestone.0).collect())
+            }
+
+            async fn delete_milestones(&self, milestone_hashes: &HashSet<Hash>) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for hash in milestone_hashes.iter() {
+                    sqlx::query(DELETE_MILESTONE_BY_HASH_STATEMENT)
+                        .bind(encode_buffer(hash.to_inner().encode::<T5B1Buf>()))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn insert_state_delta(
+                &self,
+                state_delta: StateDeltaMap,
+                index: MilestoneIndex,
+            ) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for (address, delta) in state_delta.address_to_delta.iter() {
+                    let address_buf = encode_address(address);
+                    let balance: Option<BalanceWrapper> = sqlx::query_as(SELECT_BALANCE_BY_ADDRESS_STATEMENT)
+                        .bind(address_buf.clone())
+                        .fetch_optional(&mut conn_transaction)
+                        .await?;
+                    let balance = balance.map_or(0, |balance| balance.1) as i64 + delta;
+
+                    // Returning early drops the transaction, which rolls it back.
+                    if balance < 0 {
+                        return Err(SqlxBackendError::NegativeBalance);
+                    } else if balance == 0 {
+                        sqlx::query(DELETE_BALANCE_BY_ADDRESS_STATEMENT)
+                            .bind(address_buf)
+                            .execute(&mut conn_transaction)
+                            .await?;
+                    } else {
+                        sqlx::query(UPSERT_BALANCE_STATEMENT)
+                            .bind(address_buf)
+                            .bind(balance)
+                            .execute(&mut conn_transaction)
+                            .await?;
+                    }
+                }
+
+                sqlx::query(INSERT_STATE_DELTA_STATEMENT)
+                    .bind(*index as i32)
+                    .bind(state_delta.encode())
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                sqlx::query(UPSERT_LEDGER_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn load_state_delta(&self, index: MilestoneIndex) -> Result<StateDeltaMap, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let state_delta_wrapper: StateDeltaWrapper = sqlx::query_as(LOAD_STATE_DELTA_BY_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .fetch_one(&mut pool)
+                    .await?;
+
+                Ok(state_delta_wrapper.0)
+            }
+
+            async fn revert_state_delta(&self) -> Result<(MilestoneIndex, StateDeltaMap), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                let index: LedgerIndexWrapper = sqlx::query_as(SELECT_LEDGER_INDEX_STATEMENT)
+                    .fetch_one(&mut conn_transaction)
+                    .await?;
+                let index = index.0;
+                let state_delta: StateDeltaWrapper = sqlx::query_as(LOAD_STATE_DELTA_BY_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .fetch_one(&mut conn_transaction)
+                    .await?;
+                let state_delta = state_delta.0;
+
+                for (address, delta) in state_delta.address_to_delta.iter() {
+                    let address_buf = encode_address(address);
+                    let balance: Option<BalanceWrapper> = sqlx::query_as(SELECT_BALANCE_BY_ADDRESS_STATEMENT)
+                        .bind(address_buf.clone())
+                        .fetch_optional(&mut conn_transaction)
+                        .await?;
+                    let balance = balance.map_or(0, |balance| balance.1) as i64 - delta;
+
+                    // Returning early drops the transaction, which rolls it back.
+                    if balance < 0 {
+                        return Err(SqlxBackendError::NegativeBalance);
+                    } else if balance == 0 {
+                        sqlx::query(DELETE_BALANCE_BY_ADDRESS_STATEMENT)
+                            .bind(address_buf)
+                            .execute(&mut conn_transaction)
+                            .await?;
+                    } else {
+                        sqlx::query(UPSERT_BALANCE_STATEMENT)
+                            .bind(address_buf)
+                            .bind(balance)
+                            .execute(&mut conn_transaction)
+                            .await?;
+                    }
+                }
+
+                sqlx::query(DELETE_STATE_DELTA_BY_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                sqlx::query(UPSERT_LEDGER_INDEX_STATEMENT)
+                    .bind((*index - 1) as i32)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                conn_transaction.commit().await?;
+
+                Ok((index, state_delta))
+            }
+
+            async fn insert_balances(
+                &self,
+                balances: HashMap<Address, u64>,
+                index: MilestoneIndex,
+            ) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                sqlx::query(DELETE_BALANCES_STATEMENT)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                for (address, balance) in balances.iter().filter(|(_, balance)| **balance != 0) {
+                    sqlx::query(UPSERT_BALANCE_STATEMENT)
+                        .bind(encode_address(address))
+                        .bind(*balance as i64)
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                sqlx::query(UPSERT_LEDGER_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn clear_balances(&self) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                sqlx::query(DELETE_BALANCES_STATEMENT)
+                    .execute(&mut conn_transaction)
+                    .await?;
+                sqlx::query(DELETE_LEDGER_INDEX_STATEMENT)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn append_balances(&self, balances: HashMap<Address, u64>) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for (address, balance) in balances.iter().filter(|(_, balance)| **balance != 0) {
+                    sqlx::query(UPSERT_BALANCE_STATEMENT)
+                        .bind(encode_address(address))
+                        .bind(*balance as i64)
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn insert_ledger_index(&self, index: MilestoneIndex) -> Result<(), SqlxBackendError> {
+                let mut pool = self.pool();
+
+                sqlx::query(UPSERT_LEDGER_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .execute(&mut pool)
+                    .await?;
+
+                Ok(())
+            }
+
+            async fn load_balances(&self) -> Result<Option<(MilestoneIndex, HashMap<Address, u64>)>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let index: Option<LedgerIndexWrapper> = sqlx::query_as(SELECT_LEDGER_INDEX_STATEMENT)
+                    .fetch_optional(&mut pool)
+                    .await?;
+
+                let index = match index {
+                    Some(index) => index.0,
+                    None => return Ok(None),
+                };
+
+                let balances: Vec<BalanceWrapper> = sqlx::query_as(SELECT_BALANCES_STATEMENT)
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(Some((
+                    index,
+                    balances
+                        .into_iter()
+                        .map(|balance| (balance.0, balance.1))
+                        .collect(),
+                )))
+            }
+
+            async fn insert_spent_addresses(&self, addresses: HashSet<Address>) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                for address in addresses.iter() {
+                    sqlx::query(INSERT_SPENT_ADDRESS_STATEMENT)
+                        .bind(encode_address(address))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn load_spent_addresses(&self) -> Result<HashSet<Address>, SqlxBackendError> {
+                let mut pool = self.pool();
+
+                let spent_addresses: Vec<SpentAddressWrapper> = sqlx::query_as(SELECT_SPENT_ADDRESSES_STATEMENT)
+                    .fetch_all(&mut pool)
+                    .await?;
+
+                Ok(spent_addresses.into_iter().map(|address| address.0).collect())
+            }
+
+            async fn prune_below(
+                &self,
+                index: MilestoneIndex,
+                solid_entry_points: &HashSet<Hash>,
+            ) -> Result<(), SqlxBackendError> {
+                let mut conn_transaction = self.pool().begin().await?;
+
+                let hashes: Vec<HashWrapper> = sqlx::query_as(SELECT_PRUNABLE_HASHES_STATEMENT)
+                    .bind(*index as i32)
+                    .fetch_all(&mut conn_transaction)
+                    .await?;
+
+                for hash in hashes.iter().filter(|hash| !solid_entry_points.contains(&hash.0)) {
+                    sqlx::query(DELETE_TRANSACTION_STATEMENT)
+                        .bind(encode_buffer(hash.0.to_inner().encode::<T5B1Buf>()))
+                        .execute(&mut conn_transaction)
+                        .await?;
+                }
+
+                sqlx::query(DELETE_MILESTONES_BELOW_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                sqlx::query(DELETE_STATE_DELTAS_BELOW_INDEX_STATEMENT)
+                    .bind(*index as i32)
+                    .execute(&mut conn_transaction)
+                    .await?;
+
+                conn_transaction.commit().await?;
+
+                Ok(())
+            }
+
+            async fn backup(&self, path: &str) -> Result<(), SqlxBackendError> {
+                self.backup_to(path).await
+            }
+
+            async fn restore(path: &str, url: &str) -> Result<MilestoneIndex, SqlxBackendError> {
+                Self::restore_from(path, url).await
+            }
+        }
+    };
+}
@@ -19,6 +19,11 @@ pub enum SqlxBackendError {
     EnvError(std::env::VarError),
     SqlxError(String),
     Bincode(String),
+    NegativeBalance,
+    NewerSchemaVersion(u32),
+    InvalidBackup(String),
+    IoError(String),
+    CommandError(String),
     UnknownError,
     //...
 }
@@ -30,6 +35,13 @@ impl fmt::Display for SqlxBackendError {
             SqlxBackendError::EnvError(ref reason) => write!(f, "Connection error: {:?}", reason),
             SqlxBackendError::SqlxError(ref reason) => write!(f, "Sqlx core error: {:?}", reason),
             SqlxBackendError::Bincode(ref reason) => write!(f, "Bincode error: {:?}", reason),
+            SqlxBackendError::NegativeBalance => write!(f, "State delta leads to a negative balance"),
+            SqlxBackendError::NewerSchemaVersion(version) => {
+                write!(f, "Schema version {} is newer than the supported one", version)
+            }
+            SqlxBackendError::InvalidBackup(ref reason) => write!(f, "Invalid backup: {:?}", reason),
+            SqlxBackendError::IoError(ref reason) => write!(f, "I/O error: {:?}", reason),
+            SqlxBackendError::CommandError(ref reason) => write!(f, "Command error: {:?}", reason),
             SqlxBackendError::UnknownError => write!(f, "Unknown error"),
         }
     }
@@ -62,3 +74,10 @@ impl From<std::boxed::Box<bincode::ErrorKind>> for SqlxBackendError {
         SqlxBackendError::Bincode(err.as_ref().to_string())
     }
 }
+
+impl From<std::io::Error> for SqlxBackendError {
+    #[inline]
+    fn from(err: std::io::Error) -> Self {
+        SqlxBackendError::IoError(err.to_string())
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+// Row decoding shared by the Postgres and SQLite flavours, the backends only telling how to read their column types.
+
+use super::statements::{
+    BALANCE_COL_ADDRESS, BALANCE_COL_BALANCE, LEDGER_COL_MILESTONE_INDEX, MILESTONE_COL_HASH, MILESTONE_COL_ID,
+    SCHEMA_VERSION_COL_VERSION, SPENT_ADDRESS_COL_ADDRESS, STATE_DELTA_COL_DELTA, TRANSACTION_COL_ADDRESS,
+    TRANSACTION_COL_ATTACHMENT_TIMESTAMP, TRANSACTION_COL_ATTACHMENT_TIMESTAMP_LOWER,
+    TRANSACTION_COL_ATTACHMENT_TIMESTAMP_UPPER, TRANSACTION_COL_BRANCH, TRANSACTION_COL_BUNDLE,
+    TRANSACTION_COL_CURRENT_INDEX, TRANSACTION_COL_HASH, TRANSACTION_COL_LAST_INDEX, TRANSACTION_COL_NONCE,
+    TRANSACTION_COL_OBSOLETE_TAG, TRANSACTION_COL_PAYLOAD, TRANSACTION_COL_SNAPSHOT_INDEX, TRANSACTION_COL_SOLID,
+    TRANSACTION_COL_TAG, TRANSACTION_COL_TIMESTAMP, TRANSACTION_COL_TRUNK, TRANSACTION_COL_VALUE,
+};
+use crate::storage::{decode_address, AttachmentData, StateDeltaMap};
+
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
+use bee_ternary::{T1B1Buf, T5B1Buf, TritBuf, Trits, T5B1};
+use bee_transaction::bundled::{
+    Address, BundledTransaction as Transaction, BundledTransactionBuilder as TransactionBuilder, Index, Nonce, Payload,
+    Tag, Timestamp, Value, ADDRESS_TRIT_LEN, HASH_TRIT_LEN, NONCE_TRIT_LEN, PAYLOAD_TRIT_LEN, TAG_TRIT_LEN,
+};
+
+use bytemuck::cast_slice;
+use sqlx::{Error as SqlxError, FromRow, Row};
+
+pub(crate) struct TransactionWrapper(pub(crate) Transaction);
+pub(crate) struct HashWrapper(pub(crate) Hash);
+pub(crate) struct MilestoneWrapper(pub(crate) Milestone);
+pub(crate) struct MilestoneIndexWrapper(pub(crate) MilestoneIndex);
+pub(crate) struct StateDeltaWrapper(pub(crate) StateDeltaMap);
+pub(crate) struct SolidStateWrapper(pub(crate) bool);
+pub(crate) struct SnapshotIndexWrapper(pub(crate) u32);
+pub(crate) struct BalanceWrapper(pub(crate) Address, pub(crate) u64);
+pub(crate) struct LedgerIndexWrapper(pub(crate) MilestoneIndex);
+pub(crate) struct SpentAddressWrapper(pub(crate) Address);
+pub(crate) struct SchemaVersionWrapper(pub(crate) u32);
+
+/// Reads the columns of a row, named after their Postgres types; SQLite stores every integer on 64 bits.
+pub(crate) trait SqlxRow<'c>: Row<'c> {
+    fn get_bytes(&self, column: &'static str) -> Result<Vec<u8>, SqlxError>;
+
+    fn get_small_integer(&self, column: &'static str) -> Result<i64, SqlxError>;
+
+    fn get_integer(&self, column: &'static str) -> Result<i64, SqlxError>;
+
+    fn get_big_integer(&self, column: &'static str) -> Result<i64, SqlxError>;
+}
+
+// A corrupt row is reported as a decoding error rather than read out of bounds.
+fn decode_error(column: &str) -> SqlxError {
+    SqlxError::Decode(format!("invalid value in column \"{}\"", column).into())
+}
+
+fn get_trits<'c, R: SqlxRow<'c>>(row: &R, column: &'static str, num_trits: usize) -> Result<TritBuf, SqlxError> {
+    decode_bytes(&row.get_bytes(column)?, num_trits).ok_or_else(|| decode_error(column))
+}
+
+fn get_hash<'c, R: SqlxRow<'c>>(row: &R, column: &'static str) -> Result<Hash, SqlxError> {
+    Ok(Hash::from_inner_unchecked(get_trits(row, column, HASH_TRIT_LEN)?))
+}
+
+fn get_address<'c, R: SqlxRow<'c>>(row: &R, column: &'static str) -> Result<Address, SqlxError> {
+    decode_address(&row.get_bytes(column)?).ok_or_else(|| decode_error(column))
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for TransactionWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        let builder = TransactionBuilder::new()
+            .with_payload(Payload::from_inner_unchecked(get_trits(
+                row,
+                TRANSACTION_COL_PAYLOAD,
+                PAYLOAD_TRIT_LEN,
+            )?))
+            .with_address(Address::from_inner_unchecked(get_trits(
+                row,
+                TRANSACTION_COL_ADDRESS,
+                ADDRESS_TRIT_LEN,
+            )?))
+            .with_value(Value::from_inner_unchecked(row.get_integer(TRANSACTION_COL_VALUE)?))
+            .with_obsolete_tag(Tag::from_inner_unchecked(get_trits(
+                row,
+                TRANSACTION_COL_OBSOLETE_TAG,
+                TAG_TRIT_LEN,
+            )?))
+            .with_timestamp(Timestamp::from_inner_unchecked(
+                row.get_integer(TRANSACTION_COL_TIMESTAMP)? as u64,
+            ))
+            .with_index(Index::from_inner_unchecked(
+                row.get_small_integer(TRANSACTION_COL_CURRENT_INDEX)? as usize,
+            ))
+            .with_last_index(Index::from_inner_unchecked(
+                row.get_small_integer(TRANSACTION_COL_LAST_INDEX)? as usize,
+            ))
+            .with_bundle(get_hash(row, TRANSACTION_COL_BUNDLE)?)
+            .with_trunk(get_hash(row, TRANSACTION_COL_TRUNK)?)
+            .with_branch(get_hash(row, TRANSACTION_COL_BRANCH)?)
+            .with_tag(Tag::from_inner_unchecked(get_trits(
+                row,
+                TRANSACTION_COL_TAG,
+                TAG_TRIT_LEN,
+            )?))
+            .with_attachment_ts(Timestamp::from_inner_unchecked(
+                row.get_integer(TRANSACTION_COL_ATTACHMENT_TIMESTAMP)? as u64,
+            ))
+            .with_attachment_lbts(Timestamp::from_inner_unchecked(
+                row.get_integer(TRANSACTION_COL_ATTACHMENT_TIMESTAMP_LOWER)? as u64,
+            ))
+            .with_attachment_ubts(Timestamp::from_inner_unchecked(
+                row.get_integer(TRANSACTION_COL_ATTACHMENT_TIMESTAMP_UPPER)? as u64,
+            ))
+            .with_nonce(Nonce::from_inner_unchecked(get_trits(
+                row,
+                TRANSACTION_COL_NONCE,
+                NONCE_TRIT_LEN,
+            )?));
+
+        builder
+            .build()
+            .map(Self)
+            .map_err(|e| SqlxError::Decode(format!("invalid transaction: {:?}", e).into()))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for HashWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(get_hash(row, TRANSACTION_COL_HASH)?))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for MilestoneWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(Milestone::new(
+            get_hash(row, MILESTONE_COL_HASH)?,
+            MilestoneIndex(row.get_integer(MILESTONE_COL_ID)? as u32),
+        )))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for MilestoneIndexWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(MilestoneIndex(row.get_integer(MILESTONE_COL_ID)? as u32)))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for StateDeltaWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        StateDeltaMap::decode(&row.get_bytes(STATE_DELTA_COL_DELTA)?)
+            .map(Self)
+            .ok_or_else(|| decode_error(STATE_DELTA_COL_DELTA))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for BalanceWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(
+            get_address(row, BALANCE_COL_ADDRESS)?,
+            row.get_big_integer(BALANCE_COL_BALANCE)? as u64,
+        ))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for LedgerIndexWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(
+            MilestoneIndex(row.get_integer(LEDGER_COL_MILESTONE_INDEX)? as u32),
+        ))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for SpentAddressWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(get_address(row, SPENT_ADDRESS_COL_ADDRESS)?))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for AttachmentData {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(AttachmentData {
+            hash: get_hash(row, TRANSACTION_COL_HASH)?,
+            trunk: get_hash(row, TRANSACTION_COL_TRUNK)?,
+            branch: get_hash(row, TRANSACTION_COL_BRANCH)?,
+        })
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for SolidStateWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(row.get_small_integer(TRANSACTION_COL_SOLID)? != 0))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for SnapshotIndexWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(row.get_integer(TRANSACTION_COL_SNAPSHOT_INDEX)? as u32))
+    }
+}
+
+impl<'c, R: SqlxRow<'c>> FromRow<'c, R> for SchemaVersionWrapper {
+    fn from_row(row: &R) -> Result<Self, SqlxError> {
+        Ok(Self(row.get_integer(SCHEMA_VERSION_COL_VERSION)? as u32))
+    }
+}
+
+// Encoded data is T5B1, decoded to T1B1; `None` if the length does not match.
+pub(crate) fn decode_bytes(u8_slice: &[u8], num_trits: usize) -> Option<TritBuf> {
+    if u8_slice.len() != (num_trits + 4) / 5 {
+        return None;
+    }
+
+    let decoded_column_i8_slice: &[i8] = cast_slice(u8_slice);
+    Some(unsafe { Trits::<T5B1>::from_raw_unchecked(decoded_column_i8_slice, num_trits).encode::<T1B1Buf>() })
+}
+
+pub(crate) fn encode_buffer(buffer: TritBuf<T5B1Buf>) -> Vec<u8> {
+    cast_slice(buffer.as_i8_slice()).to_vec()
+}
@@ -23,6 +23,24 @@ CREATE TABLE IF NOT EXISTS transactions (
 
 CREATE TABLE IF NOT EXISTS  milestones (
   id INTEGER NOT NULL PRIMARY KEY,
-  hash BYTEA NOT NULL UNIQUE,
-  delta BYTEA
+  hash BYTEA NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS state_deltas (
+  milestone_index INTEGER NOT NULL PRIMARY KEY,
+  delta BYTEA NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS balances (
+  address BYTEA NOT NULL PRIMARY KEY,
+  balance BIGINT NOT NULL CHECK (balance > 0)
+);
+
+CREATE TABLE IF NOT EXISTS ledger (
+  id SMALLINT NOT NULL PRIMARY KEY,
+  milestone_index INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS spent_addresses (
+  address BYTEA NOT NULL PRIMARY KEY
 );
@@ -0,0 +1,45 @@
+CREATE TABLE IF NOT EXISTS transactions (
+  payload BLOB NOT NULL,
+  address BLOB NOT NULL,
+  value INTEGER NOT NULL,
+  obsolete_tag BLOB,
+  timestamp INTEGER NOT NULL,
+  current_index INTEGER NOT NULL,
+  last_index INTEGER NOT NULL,
+  bundle BLOB NOT NULL,
+  trunk BLOB NOT NULL,
+  branch BLOB NOT NULL,
+  tag BLOB NOT NULL,
+  attachment_timestamp INTEGER NOT NULL,
+  attachment_timestamp_lower INTEGER NOT NULL,
+  attachment_timestamp_upper INTEGER NOT NULL,
+  nonce BLOB NOT NULL,
+  hash BLOB NOT NULL PRIMARY KEY,
+  snapshot_index INTEGER NOT NULL DEFAULT 0,
+  solid INTEGER NOT NULL DEFAULT 0,
+  validity INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS milestones (
+  id INTEGER NOT NULL PRIMARY KEY,
+  hash BLOB NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS state_deltas (
+  milestone_index INTEGER NOT NULL PRIMARY KEY,
+  delta BLOB NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS balances (
+  address BLOB NOT NULL PRIMARY KEY,
+  balance INTEGER NOT NULL CHECK (balance > 0)
+);
+
+CREATE TABLE IF NOT EXISTS ledger (
+  id INTEGER NOT NULL PRIMARY KEY,
+  milestone_index INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS spent_addresses (
+  address BLOB NOT NULL PRIMARY KEY
+);
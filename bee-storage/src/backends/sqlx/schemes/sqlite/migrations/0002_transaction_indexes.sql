@@ -0,0 +1,9 @@
+CREATE INDEX IF NOT EXISTS transactions_address ON transactions (address);
+
+CREATE INDEX IF NOT EXISTS transactions_bundle ON transactions (bundle);
+
+CREATE INDEX IF NOT EXISTS transactions_tag ON transactions (tag);
+
+CREATE INDEX IF NOT EXISTS transactions_trunk ON transactions (trunk);
+
+CREATE INDEX IF NOT EXISTS transactions_branch ON transactions (branch);
@@ -0,0 +1,194 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+mod statements;
+mod test;
+
+use self::statements::*;
+use super::{
+    rows::{
+        encode_buffer, BalanceWrapper, HashWrapper, LedgerIndexWrapper, MilestoneIndexWrapper, MilestoneWrapper,
+        SchemaVersionWrapper, SnapshotIndexWrapper, SolidStateWrapper, SpentAddressWrapper, SqlxRow, StateDeltaWrapper,
+        TransactionWrapper,
+    },
+    CONNECTION_NOT_INITIALIZED,
+};
+use crate::{
+    backends::sqlx::SqlxBackendError,
+    storage::{
+        encode_address, AttachmentData, Connection, HashesToApprovers, MissingHashesToRCApprovers, StateDeltaMap,
+        Storage, StorageBackend,
+    },
+};
+
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
+use bee_ternary::T5B1Buf;
+use bee_transaction::{
+    bundled::{Address, BundledTransaction as Transaction, BundledTransactionField, Tag},
+    TransactionVertex,
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    rc::Rc,
+};
+
+use async_trait::async_trait;
+use sqlx::{
+    sqlite::{SqlitePool, SqliteQueryAs, SqliteRow},
+    Error as SqlxError, Row,
+};
+
+// Applied in order, the one at position `i` upgrading the schema from version `i` to version `i + 1`.
+const MIGRATIONS: &[&str] = &[
+    include_str!("../schemes/sqlite/migrations/0001_initial.sql"),
+    include_str!("../schemes/sqlite/migrations/0002_transaction_indexes.sql"),
+];
+
+const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+// SQLite integers are all read on 64 bits, whatever the width of the Postgres column.
+impl<'c> SqlxRow<'c> for SqliteRow<'c> {
+    fn get_bytes(&self, column: &'static str) -> Result<Vec<u8>, SqlxError> {
+        self.try_get(column)
+    }
+
+    fn get_small_integer(&self, column: &'static str) -> Result<i64, SqlxError> {
+        self.try_get(column)
+    }
+
+    fn get_integer(&self, column: &'static str) -> Result<i64, SqlxError> {
+        self.try_get(column)
+    }
+
+    fn get_big_integer(&self, column: &'static str) -> Result<i64, SqlxError> {
+        self.try_get(column)
+    }
+}
+
+// Returns the index of the last milestone of a backup, checking that this version can open it and that its ledger
+// does not refer to a later milestone.
+async fn validate_backup(mut pool: &SqlitePool) -> Result<MilestoneIndex, SqlxBackendError> {
+    // Backups predating the version record are at version 0.
+    sqlx::query(CREATE_SCHEMA_VERSION_TABLE_STATEMENT)
+        .execute(&mut pool)
+        .await?;
+    let version: Option<SchemaVersionWrapper> = sqlx::query_as(SELECT_SCHEMA_VERSION_STATEMENT)
+        .fetch_optional(&mut pool)
+        .await?;
+    let version = version.map_or(0, |version| version.0);
+
+    if version > SCHEMA_VERSION {
+        return Err(SqlxBackendError::NewerSchemaVersion(version));
+    }
+
+    let last_index = match sqlx::query_as::<_, MilestoneIndexWrapper>(SELECT_LAST_MILESTONE_INDEX_STATEMENT)
+        .fetch_optional(&mut pool)
+        .await?
+    {
+        Some(last_index) => last_index.0,
+        None => return Err(SqlxBackendError::InvalidBackup("no milestone".to_string())),
+    };
+    let ledger_index: Option<LedgerIndexWrapper> = sqlx::query_as(SELECT_LEDGER_INDEX_STATEMENT)
+        .fetch_optional(&mut pool)
+        .await?;
+
+    if let Some(LedgerIndexWrapper(ledger_index)) = ledger_index {
+        if ledger_index > last_index {
+            return Err(SqlxBackendError::InvalidBackup(format!(
+                "ledger index {} is past the last milestone {}",
+                *ledger_index, *last_index
+            )));
+        }
+    }
+
+    Ok(last_index)
+}
+
+#[derive(Clone, Debug)]
+pub struct SqliteBackendConnection {
+    connection_pool: Option<SqlitePool>,
+}
+
+impl SqliteBackendConnection {
+    pub fn new() -> Self {
+        Self { connection_pool: None }
+    }
+}
+
+#[async_trait]
+impl Connection for SqliteBackendConnection {
+    type StorageError = SqlxBackendError;
+
+    // `url` is of the form "sqlite://path/to/file.db"; the file is created if it does not exist.
+    async fn establish_connection(&mut self, url: &str) -> Result<(), SqlxBackendError> {
+        // SQLite serializes writers anyway, a single connection avoids failing on a locked database.
+        let pool = SqlitePool::builder().max_size(1).build(url).await?;
+        migrate(&pool).await?;
+        self.connection_pool = Some(pool);
+
+        Ok(())
+    }
+
+    async fn destroy_connection(&mut self) -> Result<(), SqlxBackendError> {
+        self.connection_pool.as_ref().unwrap().close().await;
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SqliteBackendStorage(Storage<SqliteBackendConnection>);
+
+impl SqliteBackendStorage {
+    async fn backup_to(&self, path: &str) -> Result<(), SqlxBackendError> {
+        let mut pool = self.pool();
+
+        sqlx::query(BACKUP_STATEMENT).bind(path).execute(&mut pool).await?;
+
+        Ok(())
+    }
+
+    async fn restore_from(path: &str, url: &str) -> Result<MilestoneIndex, SqlxBackendError> {
+        let db_path = url.trim_start_matches("sqlite://");
+
+        // The backup is validated on a copy, next to the database, that only replaces it once known to be usable.
+        let staging = format!("{}.restore", db_path);
+        fs::copy(path, &staging)?;
+
+        let pool = SqlitePool::builder()
+            .max_size(1)
+            .build(&format!("sqlite://{}", staging))
+            .await?;
+        let validation = validate_backup(&pool).await;
+        pool.close().await;
+
+        let last_index = match validation {
+            Ok(last_index) => last_index,
+            Err(e) => {
+                fs::remove_file(&staging)?;
+                return Err(e);
+            }
+        };
+
+        // A leftover journal of the replaced database would otherwise be applied to the restored one.
+        for suffix in &["-journal", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", db_path, suffix));
+        }
+        fs::rename(&staging, db_path)?;
+
+        Ok(last_index)
+    }
+}
+
+// Timestamps are stored as 64-bit integers, which is what SQLite uses for all integers anyway.
+impl_sqlx_backend!(SqliteBackendStorage, SqliteBackendConnection, SqlitePool, i64);
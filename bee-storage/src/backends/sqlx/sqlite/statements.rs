@@ -0,0 +1,222 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+// SQLite flavour of the statements, using `?N` placeholders; the column names are shared with Postgres.
+
+use itertools::Itertools;
+
+pub const INSERT_TRANSACTION_STATEMENT: &str = r#"
+        INSERT INTO transactions (payload, address, value, obsolete_tag, timestamp, current_index, last_index, bundle, trunk, branch, tag
+        ,attachment_timestamp, attachment_timestamp_lower, attachment_timestamp_upper, nonce, hash)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+                "#;
+
+pub const FIND_TRANSACTION_BY_HASH_STATEMENT: &str = r#"
+SELECT payload, address, value, obsolete_tag, timestamp, current_index, last_index, bundle, trunk, branch, tag
+        ,attachment_timestamp, attachment_timestamp_lower, attachment_timestamp_upper, nonce, hash
+FROM transactions
+WHERE hash=?1
+        "#;
+
+// SQLite requires LIMIT before OFFSET; the parameters keep the Postgres binding order.
+pub const SELECT_HASH_BRANCH_TRUNK_LIMIT_STATEMENT: &str = r#"
+SELECT hash, branch, trunk
+FROM transactions
+LIMIT ?2 OFFSET ?1
+        "#;
+
+pub const UPDATE_SNAPSHOT_INDEX_STATEMENT: &str = r#"UPDATE transactions set snapshot_index=?1 WHERE hash=?2"#;
+
+pub const UPDATE_SET_SOLID_STATEMENT: &str = r#"UPDATE transactions set solid=1 WHERE hash=?1"#;
+
+pub const DELETE_TRANSACTION_STATEMENT: &str = r#"DELETE FROM transactions WHERE hash=?1"#;
+
+pub const SELECT_HASHES_BY_ADDRESS_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE address=?1
+        "#;
+
+pub const SELECT_HASHES_BY_BUNDLE_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE bundle=?1
+        "#;
+
+pub const SELECT_HASHES_BY_TAG_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE tag=?1
+        "#;
+
+pub const SELECT_HASHES_BY_APPROVEE_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE trunk=?1 OR branch=?1
+        "#;
+
+pub const INSERT_MILESTONE_STATEMENT: &str = r#"
+        INSERT INTO milestones (id, hash)
+        VALUES (?1, ?2)
+                "#;
+
+pub const FIND_MILESTONE_BY_HASH_STATEMENT: &str = r#"
+SELECT id, hash
+FROM milestones
+WHERE hash=?1
+        "#;
+
+pub const SELECT_MILESTONES_STATEMENT: &str = r#"
+SELECT id, hash
+FROM milestones
+        "#;
+
+pub const DELETE_MILESTONE_BY_HASH_STATEMENT: &str = r#"DELETE FROM milestones WHERE hash=?1"#;
+
+pub const INSERT_STATE_DELTA_STATEMENT: &str = r#"
+        INSERT INTO state_deltas (milestone_index, delta)
+        VALUES (?1, ?2)
+        ON CONFLICT (milestone_index) DO UPDATE SET delta = excluded.delta
+                "#;
+
+pub const LOAD_STATE_DELTA_BY_INDEX_STATEMENT: &str = r#"
+SELECT delta
+FROM state_deltas
+WHERE milestone_index=?1
+        "#;
+
+pub const DELETE_STATE_DELTA_BY_INDEX_STATEMENT: &str = r#"
+DELETE FROM state_deltas
+WHERE milestone_index=?1
+        "#;
+
+pub const SELECT_BALANCE_BY_ADDRESS_STATEMENT: &str = r#"
+SELECT address, balance
+FROM balances
+WHERE address=?1
+        "#;
+
+pub const SELECT_BALANCES_STATEMENT: &str = r#"
+SELECT address, balance
+FROM balances
+        "#;
+
+pub const UPSERT_BALANCE_STATEMENT: &str = r#"
+        INSERT INTO balances (address, balance)
+        VALUES (?1, ?2)
+        ON CONFLICT (address) DO UPDATE SET balance = excluded.balance
+                "#;
+
+pub const DELETE_BALANCE_BY_ADDRESS_STATEMENT: &str = r#"DELETE FROM balances WHERE address=?1"#;
+
+pub const DELETE_BALANCES_STATEMENT: &str = r#"DELETE FROM balances"#;
+
+pub const UPSERT_LEDGER_INDEX_STATEMENT: &str = r#"
+        INSERT INTO ledger (id, milestone_index)
+        VALUES (0, ?1)
+        ON CONFLICT (id) DO UPDATE SET milestone_index = excluded.milestone_index
+                "#;
+
+pub const DELETE_LEDGER_INDEX_STATEMENT: &str = r#"DELETE FROM ledger"#;
+
+pub const SELECT_LEDGER_INDEX_STATEMENT: &str = r#"
+SELECT milestone_index
+FROM ledger
+WHERE id=0
+        "#;
+
+pub const INSERT_SPENT_ADDRESS_STATEMENT: &str = r#"
+        INSERT INTO spent_addresses (address)
+        VALUES (?1)
+        ON CONFLICT (address) DO NOTHING
+                "#;
+
+pub const SELECT_SPENT_ADDRESSES_STATEMENT: &str = r#"
+SELECT address
+FROM spent_addresses
+        "#;
+
+// A snapshot index of 0 marks an unconfirmed transaction, attached at its attachment timestamp, in milliseconds, or
+// else at its timestamp, in seconds.
+pub const SELECT_PRUNABLE_HASHES_STATEMENT: &str = r#"
+SELECT tx.hash
+FROM transactions tx
+WHERE (tx.snapshot_index > 0 AND tx.snapshot_index < ?1)
+OR (
+  tx.snapshot_index = 0
+  AND CASE WHEN tx.attachment_timestamp = 0 THEN tx.timestamp ELSE tx.attachment_timestamp / 1000 END < (
+    SELECT CASE WHEN ms.attachment_timestamp = 0 THEN ms.timestamp ELSE ms.attachment_timestamp / 1000 END
+    FROM milestones m
+    JOIN transactions ms ON ms.hash = m.hash
+    WHERE m.id = ?1
+  )
+)
+        "#;
+
+pub const DELETE_MILESTONES_BELOW_INDEX_STATEMENT: &str = r#"DELETE FROM milestones WHERE id < ?1"#;
+
+pub const DELETE_STATE_DELTAS_BELOW_INDEX_STATEMENT: &str = r#"DELETE FROM state_deltas WHERE milestone_index < ?1"#;
+
+// Created outside of the migrations since it records which of them were applied.
+pub const CREATE_SCHEMA_VERSION_TABLE_STATEMENT: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_version (
+  id INTEGER NOT NULL PRIMARY KEY,
+  version INTEGER NOT NULL
+)
+        "#;
+
+pub const SELECT_SCHEMA_VERSION_STATEMENT: &str = r#"
+SELECT version
+FROM schema_version
+WHERE id=0
+        "#;
+
+pub const UPSERT_SCHEMA_VERSION_STATEMENT: &str = r#"
+        INSERT INTO schema_version (id, version)
+        VALUES (0, ?1)
+        ON CONFLICT (id) DO UPDATE SET version = excluded.version
+                "#;
+
+pub const SELECT_LAST_MILESTONE_INDEX_STATEMENT: &str = r#"
+SELECT id
+FROM milestones
+ORDER BY id DESC
+LIMIT 1
+        "#;
+
+// Unlike copying the file, writes a consistent copy of the database without closing it.
+pub const BACKUP_STATEMENT: &str = r#"VACUUM INTO ?1"#;
+
+pub fn select_solid_states_by_hashes_statement(num_hashes: usize) -> String {
+    format!(
+        r#"
+                SELECT solid
+                FROM transactions
+                WHERE hash in ({})"#,
+        placeholders(num_hashes)
+    )
+}
+
+pub fn select_snapshot_indexes_by_hashes_statement(num_hashes: usize) -> String {
+    format!(
+        r#"
+                SELECT snapshot_index
+                FROM transactions
+                WHERE hash in ({})"#,
+        placeholders(num_hashes)
+    )
+}
+
+fn placeholders(num: usize) -> String {
+    (1..=num)
+        .format_with(",", |i, f| f(&format_args!("?{}", i)))
+        .to_string()
+}
@@ -0,0 +1,151 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::{backends::sqlx::SqliteBackendStorage, tests::test::TestableStorage};
+
+use std::fs;
+
+const BEE_TEST_DB_PATH: &str = "test_db.sqlite3";
+
+impl TestableStorage for SqliteBackendStorage {
+    fn test_name() -> String {
+        String::from("sqlite")
+    }
+
+    // The schema is created by the migrations when connecting.
+    fn setup() {
+        let _ = fs::remove_file(BEE_TEST_DB_PATH);
+    }
+
+    fn teardown() {
+        let _ = fs::remove_file(BEE_TEST_DB_PATH);
+    }
+
+    fn test_db_url() -> String {
+        format!("sqlite://{}", BEE_TEST_DB_PATH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        backends::sqlx::{
+            sqlite::{statements::UPSERT_SCHEMA_VERSION_STATEMENT, SqliteBackendStorage, SCHEMA_VERSION},
+            SqlxBackendError,
+        },
+        storage::StorageBackend,
+        tests::test::StorageTestRunner,
+    };
+
+    use bee_tangle::MilestoneIndex;
+
+    use std::{fs, path::Path};
+
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_all() {
+        StorageTestRunner::<SqliteBackendStorage>::run_all_tests();
+    }
+
+    #[test]
+    fn test_newer_schema_version() {
+        const DB_PATH: &str = "test_db_newer_schema_version.sqlite3";
+        let url = format!("sqlite://{}", DB_PATH);
+
+        let mut storage = SqliteBackendStorage::new();
+        block_on(storage.establish_connection(&url)).unwrap();
+        let mut pool = storage.0.connection.connection_pool.as_ref().unwrap();
+        block_on(
+            sqlx::query(UPSERT_SCHEMA_VERSION_STATEMENT)
+                .bind(SCHEMA_VERSION as i32 + 1)
+                .execute(&mut pool),
+        )
+        .unwrap();
+        block_on(storage.destroy_connection()).unwrap();
+
+        let mut storage = SqliteBackendStorage::new();
+        let res = block_on(storage.establish_connection(&url));
+        let _ = fs::remove_file(DB_PATH);
+
+        match res {
+            Err(SqlxBackendError::NewerSchemaVersion(version)) => assert_eq!(version, SCHEMA_VERSION + 1),
+            _ => panic!("a database with a newer schema version was opened"),
+        }
+    }
+
+    #[test]
+    fn test_backup_restore() {
+        const DB_PATH: &str = "test_db_backup_restore.sqlite3";
+        const BACKUP_PATH: &str = "test_db_backup_restore_backup.sqlite3";
+        const RESTORED_PATH: &str = "test_db_backup_restore_restored.sqlite3";
+        let url = format!("sqlite://{}", DB_PATH);
+        let restored_url = format!("sqlite://{}", RESTORED_PATH);
+
+        let mut storage = SqliteBackendStorage::new();
+        block_on(storage.establish_connection(&url)).unwrap();
+
+        let (backed_up_hash, backed_up_tx) = bee_test::transaction::create_random_tx();
+        let (later_hash, later_tx) = bee_test::transaction::create_random_tx();
+        block_on(storage.insert_transaction(backed_up_hash, backed_up_tx.clone())).unwrap();
+        block_on(storage.insert_milestone(bee_test::milestone::create_random_milestone(MilestoneIndex(7)))).unwrap();
+        block_on(storage.backup(BACKUP_PATH)).unwrap();
+        block_on(storage.insert_transaction(later_hash, later_tx)).unwrap();
+        block_on(storage.destroy_connection()).unwrap();
+
+        assert_eq!(
+            block_on(SqliteBackendStorage::restore(BACKUP_PATH, &restored_url)).unwrap(),
+            MilestoneIndex(7)
+        );
+
+        let mut storage = SqliteBackendStorage::new();
+        block_on(storage.establish_connection(&restored_url)).unwrap();
+        assert_eq!(
+            block_on(storage.find_transaction(backed_up_hash)).unwrap(),
+            backed_up_tx
+        );
+        assert!(block_on(storage.find_transaction(later_hash)).is_err());
+        block_on(storage.destroy_connection()).unwrap();
+
+        for path in &[DB_PATH, BACKUP_PATH, RESTORED_PATH] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_restore_without_milestone() {
+        const DB_PATH: &str = "test_db_restore_without_milestone.sqlite3";
+        const BACKUP_PATH: &str = "test_db_restore_without_milestone_backup.sqlite3";
+        const RESTORED_PATH: &str = "test_db_restore_without_milestone_restored.sqlite3";
+        let url = format!("sqlite://{}", DB_PATH);
+
+        let mut storage = SqliteBackendStorage::new();
+        block_on(storage.establish_connection(&url)).unwrap();
+        block_on(storage.backup(BACKUP_PATH)).unwrap();
+        block_on(storage.destroy_connection()).unwrap();
+
+        let res = block_on(SqliteBackendStorage::restore(
+            BACKUP_PATH,
+            &format!("sqlite://{}", RESTORED_PATH),
+        ));
+        let restored = Path::new(RESTORED_PATH).exists();
+
+        for path in &[DB_PATH, BACKUP_PATH] {
+            let _ = fs::remove_file(path);
+        }
+
+        match res {
+            Err(SqlxBackendError::InvalidBackup(_)) => assert!(!restored),
+            _ => panic!("a backup without milestone was restored"),
+        }
+    }
+}
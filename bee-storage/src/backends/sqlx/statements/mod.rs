@@ -32,7 +32,17 @@ pub const TRANSACTION_COL_SNAPSHOT_INDEX: &str = "snapshot_index";
 
 pub const MILESTONE_COL_ID: &str = "id";
 pub const MILESTONE_COL_HASH: &str = "hash";
-pub const MILESTONE_COL_DELTA: &str = "delta";
+
+pub const STATE_DELTA_COL_DELTA: &str = "delta";
+
+pub const BALANCE_COL_ADDRESS: &str = "address";
+pub const BALANCE_COL_BALANCE: &str = "balance";
+
+pub const LEDGER_COL_MILESTONE_INDEX: &str = "milestone_index";
+
+pub const SPENT_ADDRESS_COL_ADDRESS: &str = "address";
+
+pub const SCHEMA_VERSION_COL_VERSION: &str = "version";
 
 pub const INSERT_TRANSACTION_STATEMENT: &str = r#"
         INSERT INTO transactions (payload, address, value, obsolete_tag, timestamp, current_index, last_index, bundle, trunk, branch, tag
@@ -67,6 +77,30 @@ pub const UPDATE_SET_SOLID_STATEMENT: &str = r#"UPDATE transactions set solid=1
 
 pub const DELETE_TRANSACTION_STATEMENT: &str = r#"DELETE FROM transactions WHERE hash =$1"#;
 
+pub const SELECT_HASHES_BY_ADDRESS_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE address=$1
+        "#;
+
+pub const SELECT_HASHES_BY_BUNDLE_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE bundle=$1
+        "#;
+
+pub const SELECT_HASHES_BY_TAG_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE tag=$1
+        "#;
+
+pub const SELECT_HASHES_BY_APPROVEE_STATEMENT: &str = r#"
+SELECT hash
+FROM transactions
+WHERE trunk=$1 OR branch=$1
+        "#;
+
 pub const INSERT_MILESTONE_STATEMENT: &str = r#"
         INSERT INTO milestones (id, hash)
         VALUES ($1, $2)
@@ -78,16 +112,148 @@ FROM milestones
 WHERE hash=$1
         "#;
 
+pub const SELECT_MILESTONES_STATEMENT: &str = r#"
+SELECT id, hash
+FROM milestones
+        "#;
+
 pub const DELETE_MILESTONE_BY_HASH_STATEMENT: &str = r#"DELETE FROM milestones WHERE hash =$1"#;
 
-pub const STORE_DELTA_STATEMENT: &str = r#"UPDATE milestones SET delta =$1 WHERE id =$2"#;
+pub const INSERT_STATE_DELTA_STATEMENT: &str = r#"
+        INSERT INTO state_deltas (milestone_index, delta)
+        VALUES ($1, $2)
+        ON CONFLICT (milestone_index) DO UPDATE SET delta = EXCLUDED.delta
+                "#;
 
-pub const LOAD_DELTA_STATEMENT_BY_INDEX: &str = r#"
+pub const LOAD_STATE_DELTA_BY_INDEX_STATEMENT: &str = r#"
 SELECT delta
+FROM state_deltas
+WHERE milestone_index=$1
+        "#;
+
+pub const DELETE_STATE_DELTA_BY_INDEX_STATEMENT: &str = r#"
+DELETE FROM state_deltas
+WHERE milestone_index=$1
+        "#;
+
+pub const SELECT_BALANCE_BY_ADDRESS_STATEMENT: &str = r#"
+SELECT address, balance
+FROM balances
+WHERE address=$1
+        "#;
+
+pub const SELECT_BALANCES_STATEMENT: &str = r#"
+SELECT address, balance
+FROM balances
+        "#;
+
+pub const UPSERT_BALANCE_STATEMENT: &str = r#"
+        INSERT INTO balances (address, balance)
+        VALUES ($1, $2)
+        ON CONFLICT (address) DO UPDATE SET balance = EXCLUDED.balance
+                "#;
+
+pub const DELETE_BALANCE_BY_ADDRESS_STATEMENT: &str = r#"DELETE FROM balances WHERE address =$1"#;
+
+pub const DELETE_BALANCES_STATEMENT: &str = r#"DELETE FROM balances"#;
+
+pub const UPSERT_LEDGER_INDEX_STATEMENT: &str = r#"
+        INSERT INTO ledger (id, milestone_index)
+        VALUES (0, $1)
+        ON CONFLICT (id) DO UPDATE SET milestone_index = EXCLUDED.milestone_index
+                "#;
+
+pub const DELETE_LEDGER_INDEX_STATEMENT: &str = r#"DELETE FROM ledger"#;
+
+pub const SELECT_LEDGER_INDEX_STATEMENT: &str = r#"
+SELECT milestone_index
+FROM ledger
+WHERE id=0
+        "#;
+
+pub const INSERT_SPENT_ADDRESS_STATEMENT: &str = r#"
+        INSERT INTO spent_addresses (address)
+        VALUES ($1)
+        ON CONFLICT (address) DO NOTHING
+                "#;
+
+pub const SELECT_SPENT_ADDRESSES_STATEMENT: &str = r#"
+SELECT address
+FROM spent_addresses
+        "#;
+
+// A snapshot index of 0 marks an unconfirmed transaction, attached at its attachment timestamp, in milliseconds, or
+// else at its timestamp, in seconds.
+pub const SELECT_PRUNABLE_HASHES_STATEMENT: &str = r#"
+SELECT tx.hash
+FROM transactions tx
+WHERE (tx.snapshot_index > 0 AND tx.snapshot_index < $1)
+OR (
+  tx.snapshot_index = 0
+  AND CASE WHEN tx.attachment_timestamp = 0 THEN tx.timestamp ELSE tx.attachment_timestamp / 1000 END < (
+    SELECT CASE WHEN ms.attachment_timestamp = 0 THEN ms.timestamp ELSE ms.attachment_timestamp / 1000 END
+    FROM milestones m
+    JOIN transactions ms ON ms.hash = m.hash
+    WHERE m.id = $1
+  )
+)
+        "#;
+
+pub const DELETE_MILESTONES_BELOW_INDEX_STATEMENT: &str = r#"DELETE FROM milestones WHERE id < $1"#;
+
+pub const DELETE_STATE_DELTAS_BELOW_INDEX_STATEMENT: &str = r#"DELETE FROM state_deltas WHERE milestone_index < $1"#;
+
+// Created outside of the migrations since it records which of them were applied.
+pub const CREATE_SCHEMA_VERSION_TABLE_STATEMENT: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_version (
+  id SMALLINT NOT NULL PRIMARY KEY,
+  version INTEGER NOT NULL
+)
+        "#;
+
+pub const SELECT_SCHEMA_VERSION_STATEMENT: &str = r#"
+SELECT version
+FROM schema_version
+WHERE id=0
+        "#;
+
+pub const UPSERT_SCHEMA_VERSION_STATEMENT: &str = r#"
+        INSERT INTO schema_version (id, version)
+        VALUES (0, $1)
+        ON CONFLICT (id) DO UPDATE SET version = EXCLUDED.version
+                "#;
+
+pub const SELECT_LAST_MILESTONE_INDEX_STATEMENT: &str = r#"
+SELECT id
 FROM milestones
-WHERE id=$1
+ORDER BY id DESC
+LIMIT 1
         "#;
 
+// Run along with the restore of a dump, in the same transaction, so that an unusable backup is rolled back.
+pub fn check_backup_statement(schema_version: u32) -> String {
+    format!(
+        r#"
+DO $$
+DECLARE
+  last_index INTEGER := (SELECT MAX(id) FROM milestones);
+BEGIN
+  IF (SELECT version FROM schema_version WHERE id=0) > {} THEN
+    RAISE EXCEPTION 'schema version is newer than the supported one';
+  END IF;
+  IF last_index IS NULL THEN
+    RAISE EXCEPTION 'no milestone';
+  END IF;
+  IF (SELECT milestone_index FROM ledger WHERE id=0) > last_index THEN
+    RAISE EXCEPTION 'ledger index is past the last milestone';
+  END IF;
+END
+$$
+        "#,
+        schema_version
+    )
+}
+
 pub fn select_solid_states_by_hashes_statement(num_hashes: usize) -> String {
     format!(
         r#"
@@ -26,7 +26,7 @@ impl TestableStorage for SqlxBackendStorage {
 
     fn setup() {
         let output = Command::new("src/backends/sqlx/schemes/postgres/setup.sh")
-            .arg("src/backends/sqlx/schemes/postgres/schema.sql")
+            .arg("src/backends/sqlx/schemes/postgres/migrations/0001_initial.sql")
             .arg(BEE_TEST_DB_USER)
             .arg("dummy_password")
             .arg(BEE_TEST_DB_NAME)
@@ -0,0 +1,110 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use crate::storage::StorageBackend;
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_std::task;
+use futures::{channel::oneshot, future::FutureExt, select};
+use log::{error, info, warn};
+
+const BACKUP_PREFIX: &str = "backup-";
+
+/// Periodically backs up the storage, while it is in use, into a directory keeping the `count` most recent backups.
+pub struct BackupWorker<S: StorageBackend> {
+    storage: Arc<S>,
+    path: PathBuf,
+    interval: Duration,
+    count: usize,
+}
+
+impl<S: StorageBackend + Send + Sync> BackupWorker<S> {
+    pub fn new(storage: Arc<S>, path: String, interval: Duration, count: usize) -> Self {
+        Self {
+            storage,
+            path: PathBuf::from(path),
+            interval,
+            count,
+        }
+    }
+
+    async fn backup(&self) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        // Padded so that the names sort by age.
+        let backup_path = self.path.join(format!("{}{:020}", BACKUP_PREFIX, timestamp));
+
+        if let Err(e) = fs::create_dir_all(&self.path) {
+            error!("Creating backup directory {} failed: {:?}.", self.path.display(), e);
+            return;
+        }
+
+        match self.storage.backup(&backup_path.to_string_lossy()).await {
+            Ok(()) => info!("Backed up storage to {}.", backup_path.display()),
+            Err(e) => {
+                error!("Backing up storage to {} failed: {:?}.", backup_path.display(), e);
+                return;
+            }
+        }
+
+        if let Err(e) = remove_old_backups(&self.path, self.count) {
+            warn!("Removing old backups failed: {:?}.", e);
+        }
+    }
+
+    pub async fn run(self, shutdown: oneshot::Receiver<()>) {
+        info!("Running.");
+
+        let mut shutdown_fused = shutdown.fuse();
+
+        loop {
+            let mut backup_timeout = Box::pin(task::sleep(self.interval)).fuse();
+
+            select! {
+                _ = backup_timeout => self.backup().await,
+                _ = shutdown_fused => break
+            }
+        }
+
+        info!("Stopped.");
+    }
+}
+
+// Backups are directories or files depending on the backend.
+fn remove_old_backups(path: &Path, count: usize) -> io::Result<()> {
+    let mut backups = Vec::new();
+
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with(BACKUP_PREFIX) {
+            backups.push(entry.path());
+        }
+    }
+
+    backups.sort();
+
+    for backup in backups.iter().rev().skip(count) {
+        if backup.is_dir() {
+            fs::remove_dir_all(backup)?;
+        } else {
+            fs::remove_file(backup)?;
+        }
+    }
+
+    Ok(())
+}
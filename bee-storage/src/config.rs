@@ -0,0 +1,183 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use serde::Deserialize;
+
+use std::{num::NonZeroUsize, time::Duration};
+
+const DEFAULT_PATH: &str = "./storage";
+const DEFAULT_CHANNEL_SIZE: usize = 10000;
+const DEFAULT_BATCH_SIZE: usize = 1000;
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 500;
+const DEFAULT_SYNC: bool = false;
+const DEFAULT_BACKUP_INTERVAL: u64 = 3600;
+const DEFAULT_BACKUP_COUNT: usize = 24;
+
+#[derive(Default, Deserialize)]
+pub struct StorageConfigBuilder {
+    path: Option<String>,
+    channel_size: Option<usize>,
+    batch_size: Option<usize>,
+    flush_interval_ms: Option<u64>,
+    sync: Option<bool>,
+    backup_path: Option<String>,
+    backup_interval: Option<u64>,
+    // Keeping no backup would remove the one just taken, so it is rejected.
+    backup_count: Option<NonZeroUsize>,
+    cache_capacity: Option<usize>,
+    // Only set from the command line, a restore being a one-off operation.
+    #[serde(skip)]
+    restore_path: Option<String>,
+}
+
+impl StorageConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn path(mut self, path: String) -> Self {
+        self.path.replace(path);
+        self
+    }
+
+    pub fn channel_size(mut self, channel_size: usize) -> Self {
+        self.channel_size.replace(channel_size);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size.replace(batch_size);
+        self
+    }
+
+    pub fn flush_interval_ms(mut self, flush_interval_ms: u64) -> Self {
+        self.flush_interval_ms.replace(flush_interval_ms);
+        self
+    }
+
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync.replace(sync);
+        self
+    }
+
+    pub fn backup_path(mut self, backup_path: String) -> Self {
+        self.backup_path.replace(backup_path);
+        self
+    }
+
+    pub fn backup_interval(mut self, backup_interval: u64) -> Self {
+        self.backup_interval.replace(backup_interval);
+        self
+    }
+
+    pub fn backup_count(mut self, backup_count: NonZeroUsize) -> Self {
+        self.backup_count.replace(backup_count);
+        self
+    }
+
+    pub fn cache_capacity(mut self, cache_capacity: usize) -> Self {
+        self.cache_capacity.replace(cache_capacity);
+        self
+    }
+
+    pub fn restore_path(mut self, restore_path: String) -> Self {
+        self.restore_path.replace(restore_path);
+        self
+    }
+
+    pub fn finish(self) -> StorageConfig {
+        StorageConfig {
+            path: self.path.unwrap_or_else(|| DEFAULT_PATH.to_string()),
+            channel_size: self.channel_size.unwrap_or(DEFAULT_CHANNEL_SIZE),
+            batch_size: self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+            flush_interval: Duration::from_millis(self.flush_interval_ms.unwrap_or(DEFAULT_FLUSH_INTERVAL_MS)),
+            sync: self.sync.unwrap_or(DEFAULT_SYNC),
+            backup_path: self.backup_path,
+            backup_interval: Duration::from_secs(self.backup_interval.unwrap_or(DEFAULT_BACKUP_INTERVAL)),
+            backup_count: self.backup_count.map_or(DEFAULT_BACKUP_COUNT, NonZeroUsize::get),
+            cache_capacity: self.cache_capacity,
+            restore_path: self.restore_path,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StorageConfig {
+    path: String,
+    channel_size: usize,
+    batch_size: usize,
+    flush_interval: Duration,
+    sync: bool,
+    backup_path: Option<String>,
+    backup_interval: Duration,
+    backup_count: usize,
+    cache_capacity: Option<usize>,
+    restore_path: Option<String>,
+}
+
+impl StorageConfig {
+    pub fn build() -> StorageConfigBuilder {
+        StorageConfigBuilder::new()
+    }
+
+    /// Location of the storage, i.e. a directory for RocksDB or a connection URL for the sqlx backend, although the
+    /// node only runs on RocksDB for now.
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    /// Number of writes queued for the storage worker before new ones are written to the storage directly.
+    pub fn channel_size(&self) -> usize {
+        self.channel_size
+    }
+
+    /// Number of writes after which a write batch is flushed.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Maximum time a write waits in a batch before being flushed.
+    pub fn flush_interval(&self) -> Duration {
+        self.flush_interval
+    }
+
+    /// Whether flushing a batch waits for it to reach the disk; otherwise a machine crash may lose the last batches,
+    /// but never part of one.
+    pub fn sync(&self) -> bool {
+        self.sync
+    }
+
+    /// Directory in which the storage is periodically backed up, if it should be.
+    pub fn backup_path(&self) -> Option<&str> {
+        self.backup_path.as_deref()
+    }
+
+    /// Time between two backups.
+    pub fn backup_interval(&self) -> Duration {
+        self.backup_interval
+    }
+
+    /// Number of backups kept, at least one, the oldest ones being removed.
+    pub fn backup_count(&self) -> usize {
+        self.backup_count
+    }
+
+    /// Maximum number of transactions of the tangle held in memory, the others being reloaded from the storage when
+    /// accessed; unbounded if unset.
+    pub fn cache_capacity(&self) -> Option<usize> {
+        self.cache_capacity
+    }
+
+    /// Backup replacing the storage before it is opened, if any.
+    pub fn restore_path(&self) -> Option<&str> {
+        self.restore_path.as_deref()
+    }
+}
@@ -10,6 +10,18 @@
 // See the License for the specific language governing permissions and limitations under the License.
 
 mod backends;
+mod backup;
+mod config;
 mod models;
 mod storage;
 mod tests;
+mod worker;
+
+pub use backends::{
+    MemoryBackendError, MemoryBackendStorage, RocksDbBackendError, RocksDbBackendStorage, RocksDbBatchWriter,
+    SqliteBackendStorage, SqlxBackendError, SqlxBackendStorage,
+};
+pub use backup::BackupWorker;
+pub use config::{StorageConfig, StorageConfigBuilder};
+pub use storage::{HashesToApprovers, MissingHashesToRCApprovers, StateDeltaMap, StorageBackend};
+pub use worker::StorageWorkerEvent;
@@ -9,8 +9,8 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use bee_protocol::Milestone;
-use bee_transaction::Hash;
+use bee_crypto::ternary::Hash;
+use bee_tangle::Milestone;
 
 use async_trait::async_trait;
 
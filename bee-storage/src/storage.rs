@@ -9,10 +9,15 @@
 // an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and limitations under the License.
 
-use bee_protocol::{Milestone, MilestoneIndex};
-use bee_transaction::{Address, BundledTransaction as Transaction, Hash};
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
+use bee_ternary::{T1B1Buf, T5B1Buf, Trits, T5B1};
+use bee_transaction::bundled::{
+    Address, BundledTransaction as Transaction, BundledTransactionField, Tag, ADDRESS_TRIT_LEN,
+};
 
 use async_trait::async_trait;
+use bytemuck::cast_slice;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -29,11 +34,64 @@ pub type MissingHashesToRCApprovers = HashMap<Hash, HashSet<Rc<Hash>>>;
 // practically, a map for total balance change over an addresses will be collected
 // per milestone (snapshot_index), when we no longer have milestones, we will have to find
 // another way to decide on a check point where to store an address's delta if we want to snapshot
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct StateDeltaMap {
     pub address_to_delta: HashMap<Address, i64>,
 }
 
+// T5B1 encoded address.
+pub(crate) const ADDRESS_BYTE_LEN: usize = (ADDRESS_TRIT_LEN + 4) / 5;
+
+pub(crate) fn encode_address(address: &Address) -> Vec<u8> {
+    cast_slice(address.to_inner().encode::<T5B1Buf>().as_i8_slice()).to_vec()
+}
+
+// `None` if the length or the trits are invalid, e.g. for corrupted data.
+pub(crate) fn decode_address(buf: &[u8]) -> Option<Address> {
+    if buf.len() != ADDRESS_BYTE_LEN {
+        return None;
+    }
+
+    Trits::<T5B1>::try_from_raw(cast_slice(buf), ADDRESS_TRIT_LEN)
+        .ok()
+        .map(|trits| Address::from_inner_unchecked(trits.encode::<T1B1Buf>()))
+}
+
+impl StateDeltaMap {
+    // Entries are T5B1 encoded addresses followed by their little-endian deltas.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.address_to_delta.len() * (ADDRESS_BYTE_LEN + 8));
+
+        for (address, delta) in self.address_to_delta.iter() {
+            buf.extend_from_slice(&encode_address(address));
+            buf.extend_from_slice(&delta.to_le_bytes());
+        }
+
+        buf
+    }
+
+    // `None` if an entry is truncated or holds an invalid address.
+    pub(crate) fn decode(buf: &[u8]) -> Option<Self> {
+        let mut address_to_delta = HashMap::new();
+        let mut delta_buf = [0u8; 8];
+        let entries = buf.chunks_exact(ADDRESS_BYTE_LEN + 8);
+
+        if !entries.remainder().is_empty() {
+            return None;
+        }
+
+        for entry in entries {
+            delta_buf.copy_from_slice(&entry[ADDRESS_BYTE_LEN..]);
+            address_to_delta.insert(
+                decode_address(&entry[..ADDRESS_BYTE_LEN])?,
+                i64::from_le_bytes(delta_buf),
+            );
+        }
+
+        Some(Self { address_to_delta })
+    }
+}
+
 pub struct AttachmentData {
     pub hash: Hash,
     pub trunk: Hash,
@@ -56,10 +114,10 @@ pub trait StorageBackend {
     async fn destroy_connection(&mut self) -> Result<(), Self::StorageError>;
     // This method is heavy weighted and will be used to populate Tangle struct on initialization
     //**Operations over transaction's schema**//
-    fn map_existing_transaction_hashes_to_approvers(&self) -> Result<HashesToApprovers, Self::StorageError>;
+    async fn map_existing_transaction_hashes_to_approvers(&self) -> Result<HashesToApprovers, Self::StorageError>;
 
     // This method is heavy weighted and will be used to populate Tangle struct on initialization
-    fn map_missing_transaction_hashes_to_approvers(
+    async fn map_missing_transaction_hashes_to_approvers(
         &self,
         all_hashes: HashSet<Hash>,
     ) -> Result<MissingHashesToRCApprovers, Self::StorageError>;
@@ -86,16 +144,31 @@ pub trait StorageBackend {
 
     async fn delete_transactions(&self, transaction_hashes: &HashSet<Hash>) -> Result<(), Self::StorageError>;
 
+    // Indexed lookups of transaction hashes, an unknown key resulting in an empty set.
+    async fn find_transaction_hashes_by_address(&self, address: &Address) -> Result<HashSet<Hash>, Self::StorageError>;
+
+    async fn find_transaction_hashes_by_bundle(&self, bundle: Hash) -> Result<HashSet<Hash>, Self::StorageError>;
+
+    async fn find_transaction_hashes_by_tag(&self, tag: &Tag) -> Result<HashSet<Hash>, Self::StorageError>;
+
+    // Returns the hashes of the transactions having `approvee` as trunk or branch.
+    async fn find_transaction_hashes_by_approvee(&self, approvee: Hash) -> Result<HashSet<Hash>, Self::StorageError>;
+
     //**Operations over milestone's schema**//
 
     async fn insert_milestone(&self, milestone: Milestone) -> Result<(), Self::StorageError>;
 
     async fn find_milestone(&self, milestone_hash: Hash) -> Result<Milestone, Self::StorageError>;
 
+    // Returns all the stored milestones, in no particular order.
+    async fn load_milestones(&self) -> Result<Vec<Milestone>, Self::StorageError>;
+
     async fn delete_milestones(&self, milestone_hashes: &HashSet<Hash>) -> Result<(), Self::StorageError>;
 
     //**Operations over state_delta's schema**//
 
+    // Stores the delta of the milestone `index`, applies it to the balances and sets the ledger index to `index`, as a
+    // single atomic operation.
     async fn insert_state_delta(
         &self,
         state_delta: StateDeltaMap,
@@ -103,6 +176,58 @@ pub trait StorageBackend {
     ) -> Result<(), Self::StorageError>;
 
     async fn load_state_delta(&self, index: MilestoneIndex) -> Result<StateDeltaMap, Self::StorageError>;
+
+    // Reverts the delta of the milestone at the ledger index from the balances, removes it and decrements the ledger
+    // index, as a single atomic operation. Returns the reverted milestone index and delta.
+    async fn revert_state_delta(&self) -> Result<(MilestoneIndex, StateDeltaMap), Self::StorageError>;
+
+    //**Operations over ledger's schema**//
+
+    // Replaces all the balances and sets the ledger index to `index`, e.g. when bootstrapping from a snapshot.
+    async fn insert_balances(
+        &self,
+        balances: HashMap<Address, u64>,
+        index: MilestoneIndex,
+    ) -> Result<(), Self::StorageError>;
+
+    // Removes all the balances along with the ledger index, so that no ledger is stored until the index is set again.
+    async fn clear_balances(&self) -> Result<(), Self::StorageError>;
+
+    // Adds or replaces the given balances, leaving the ledger index untouched. A ledger bootstrapped in chunks is only
+    // stored once `insert_ledger_index` is called, after the last chunk, so that an interrupted bootstrap starts over.
+    async fn append_balances(&self, balances: HashMap<Address, u64>) -> Result<(), Self::StorageError>;
+
+    async fn insert_ledger_index(&self, index: MilestoneIndex) -> Result<(), Self::StorageError>;
+
+    // Returns the ledger index and the balances, if a ledger has been stored.
+    async fn load_balances(&self) -> Result<Option<(MilestoneIndex, HashMap<Address, u64>)>, Self::StorageError>;
+
+    // Adds `addresses` to the spent ones; an address is never removed from them, even when rolling back the ledger.
+    async fn insert_spent_addresses(&self, addresses: HashSet<Address>) -> Result<(), Self::StorageError>;
+
+    async fn load_spent_addresses(&self) -> Result<HashSet<Address>, Self::StorageError>;
+
+    //**Pruning**//
+
+    // Removes the transactions confirmed below `index` and the unconfirmed ones attached before the milestone `index`,
+    // except `solid_entry_points`, and the milestones and state deltas below `index`. A transaction is attached at its
+    // attachment timestamp or, lacking one, at its timestamp. Unconfirmed transactions are kept if the milestone
+    // `index` is not stored.
+    async fn prune_below(
+        &self,
+        index: MilestoneIndex,
+        solid_entry_points: &HashSet<Hash>,
+    ) -> Result<(), Self::StorageError>;
+
+    //**Backup**//
+
+    // Writes a consistent copy of the database to `path`, which must not exist, while the database keeps being used.
+    async fn backup(&self, path: &str) -> Result<(), Self::StorageError>;
+
+    // Replaces the database at `url`, which must not be open, with the backup at `path`. The backup is rejected, and
+    // the database left untouched, if its schema version is newer than the supported one or if it has no milestone.
+    // Returns the index of the last milestone of the backup.
+    async fn restore(path: &str, url: &str) -> Result<MilestoneIndex, Self::StorageError>;
 }
 
 #[derive(Clone, Debug)]
@@ -28,8 +28,10 @@ pub struct StorageTestRunner<T: TestableStorage + StorageBackend> {
 pub mod tests {
     use crate::storage::{HashesToApprovers, MissingHashesToRCApprovers, StateDeltaMap, StorageBackend};
 
+    use bee_crypto::ternary::Hash;
+    use bee_tangle::{Milestone, MilestoneIndex};
     use bee_test::field::rand_trits_field;
-    use bee_transaction::{Address, Hash};
+    use bee_transaction::{bundled::Address, TransactionVertex};
 
     use std::{
         collections::{HashMap, HashSet},
@@ -87,7 +89,10 @@ pub mod tests {
             assert_eq!(tx, found_tx);
             let snapshot_index_res = block_on(storage.get_transactions_snapshot_index(vec![tx_hash]));
             assert_eq!(snapshot_index_res.unwrap()[0], 0);
-            block_on(storage.update_transactions_set_snapshot_index(vec![tx_hash].into_iter().collect(), 1)).unwrap();
+            block_on(
+                storage.update_transactions_set_snapshot_index(vec![tx_hash].into_iter().collect(), MilestoneIndex(1)),
+            )
+            .unwrap();
 
             let snapshot_index_res = block_on(storage.get_transactions_snapshot_index(vec![tx_hash]));
             assert_eq!(snapshot_index_res.unwrap()[0], 1);
@@ -98,7 +103,7 @@ pub mod tests {
             let mut storage = T::new();
 
             block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
-            let milestone = bee_test::milestone::create_random_milestone(1);
+            let milestone = bee_test::milestone::create_random_milestone(MilestoneIndex(1));
             block_on(storage.insert_milestone(bee_test::milestone::clone_ms(&milestone))).unwrap();
             let res = block_on(storage.find_milestone(*milestone.hash()));
             let found_milestone = res.unwrap();
@@ -107,6 +112,20 @@ pub mod tests {
             assert_eq!(milestone.hash(), found_milestone.hash());
         }
 
+        fn test_load_milestones() {
+            let mut storage = T::new();
+
+            block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
+            let milestone = bee_test::milestone::create_random_milestone(MilestoneIndex(3));
+            block_on(storage.insert_milestone(bee_test::milestone::clone_ms(&milestone))).unwrap();
+            let milestones = block_on(storage.load_milestones()).unwrap();
+            block_on(storage.destroy_connection()).unwrap();
+
+            assert!(milestones
+                .iter()
+                .any(|m| m.hash() == milestone.hash() && m.index() == milestone.index()));
+        }
+
         fn test_delete_one_transaction() {
             let mut storage = T::new();
 
@@ -125,11 +144,65 @@ pub mod tests {
             assert!(res.is_err());
         }
 
+        fn test_find_transaction_hashes_by_indexes() {
+            let mut storage = T::new();
+
+            block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
+            let (tx_hash, tx) = bee_test::transaction::create_random_tx();
+            let (batch_tx_hash, batch_tx) = bee_test::transaction::create_random_tx();
+            block_on(storage.insert_transaction(tx_hash, bee_test::transaction::clone_tx(&tx))).unwrap();
+            let mut transactions = HashMap::new();
+            transactions.insert(batch_tx_hash, bee_test::transaction::clone_tx(&batch_tx));
+            block_on(storage.insert_transactions(transactions)).unwrap();
+
+            for (hash, tx) in vec![(tx_hash, &tx), (batch_tx_hash, &batch_tx)] {
+                let expected: HashSet<Hash> = vec![hash].into_iter().collect();
+                assert_eq!(
+                    block_on(storage.find_transaction_hashes_by_address(tx.address())).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    block_on(storage.find_transaction_hashes_by_bundle(*tx.bundle())).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    block_on(storage.find_transaction_hashes_by_tag(tx.tag())).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    block_on(storage.find_transaction_hashes_by_approvee(*tx.trunk())).unwrap(),
+                    expected
+                );
+                assert_eq!(
+                    block_on(storage.find_transaction_hashes_by_approvee(*tx.branch())).unwrap(),
+                    expected
+                );
+            }
+
+            let transactions_to_delete: HashSet<Hash> = vec![tx_hash, batch_tx_hash].into_iter().collect();
+            block_on(storage.delete_transactions(&transactions_to_delete)).unwrap();
+
+            assert!(block_on(storage.find_transaction_hashes_by_address(tx.address()))
+                .unwrap()
+                .is_empty());
+            assert!(block_on(storage.find_transaction_hashes_by_bundle(*tx.bundle()))
+                .unwrap()
+                .is_empty());
+            assert!(block_on(storage.find_transaction_hashes_by_tag(tx.tag()))
+                .unwrap()
+                .is_empty());
+            assert!(block_on(storage.find_transaction_hashes_by_approvee(*tx.trunk()))
+                .unwrap()
+                .is_empty());
+
+            block_on(storage.destroy_connection()).unwrap();
+        }
+
         fn test_delete_one_milestone() {
             let mut storage = T::new();
 
             block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
-            let milestone = bee_test::milestone::create_random_milestone(2);
+            let milestone = bee_test::milestone::create_random_milestone(MilestoneIndex(2));
             block_on(storage.insert_milestone(bee_test::milestone::clone_ms(&milestone))).unwrap();
             let res = block_on(storage.find_milestone(*milestone.hash()));
             let found_milestone = res.unwrap();
@@ -197,7 +270,7 @@ pub mod tests {
             }
 
             let now = Instant::now();
-            let hash_to_approvers_observed = storage.map_existing_transaction_hashes_to_approvers().unwrap();
+            let hash_to_approvers_observed = block_on(storage.map_existing_transaction_hashes_to_approvers()).unwrap();
             let message = format!(
                 "\n{}: test_map_hashes_to_approvers milliseconds elapsed: {}\n",
                 T::test_name(),
@@ -271,9 +344,8 @@ pub mod tests {
             }
 
             let now = Instant::now();
-            let missing_hash_to_approvers_observed = storage
-                .map_missing_transaction_hashes_to_approvers(all_transactions_hashes)
-                .unwrap();
+            let missing_hash_to_approvers_observed =
+                block_on(storage.map_missing_transaction_hashes_to_approvers(all_transactions_hashes)).unwrap();
             let message = format!(
                 "\n{}: test_map_missing_transaction_hashes_to_approvers milliseconds elapsed: {}\n",
                 T::test_name(),
@@ -406,29 +478,35 @@ pub mod tests {
             let mut storage = T::new();
             block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
 
-            let milestone = bee_test::milestone::create_random_milestone(100_000);
-            block_on(storage.insert_milestone(bee_test::milestone::clone_ms(&milestone))).unwrap();
-            block_on(storage.find_milestone(*milestone.hash())).unwrap();
+            assert!(block_on(storage.load_balances()).unwrap().is_none());
 
+            let mut balances = HashMap::new();
             let mut state_delta = StateDeltaMap {
                 address_to_delta: HashMap::new(),
             };
-            let mut addresses = HashSet::new();
             const NUM_BALANCES: usize = 1000;
-            for _i in 0..NUM_BALANCES {
+
+            for i in 0..NUM_BALANCES {
                 let address = rand_trits_field::<Address>();
-                addresses.insert(address.clone());
-                state_delta.address_to_delta.insert(address.clone(), _i as i64);
+                balances.insert(address.clone(), i as u64 + 1);
+                state_delta.address_to_delta.insert(address.clone(), -(i as i64));
             }
 
             for i in 0..NUM_BALANCES {
                 let address = rand_trits_field::<Address>();
-                addresses.insert(address.clone());
-                state_delta.address_to_delta.insert(address.clone(), -(i as i64));
+                state_delta.address_to_delta.insert(address.clone(), i as i64);
             }
 
+            block_on(storage.insert_balances(balances.clone(), MilestoneIndex(99_999))).unwrap();
+
             let now = Instant::now();
-            block_on(storage.insert_state_delta(state_delta, milestone.index())).unwrap();
+            block_on(storage.insert_state_delta(
+                StateDeltaMap {
+                    address_to_delta: state_delta.address_to_delta.clone(),
+                },
+                MilestoneIndex(100_000),
+            ))
+            .unwrap();
             let message = format!(
                 "\n{}: test_store_and_load_state_delta milliseconds elapsed: {}\n",
                 T::test_name(),
@@ -436,8 +514,121 @@ pub mod tests {
             );
             io::stdout().write_all(message.as_bytes()).unwrap();
 
-            let res = block_on(storage.load_state_delta(milestone.index()));
-            assert!(res.is_ok());
+            let res = block_on(storage.load_state_delta(MilestoneIndex(100_000)));
+            assert_eq!(res.unwrap(), state_delta);
+
+            for (address, delta) in state_delta.address_to_delta.iter() {
+                let balance = *balances.get(address).unwrap_or(&0) as i64 + delta;
+                if balance == 0 {
+                    balances.remove(address);
+                } else {
+                    balances.insert(address.clone(), balance as u64);
+                }
+            }
+
+            let (index, stored_balances) = block_on(storage.load_balances()).unwrap().unwrap();
+            assert_eq!(index, MilestoneIndex(100_000));
+            assert_eq!(stored_balances, balances);
+
+            let mut overspending_delta = StateDeltaMap {
+                address_to_delta: HashMap::new(),
+            };
+            overspending_delta
+                .address_to_delta
+                .insert(rand_trits_field::<Address>(), -1);
+            assert!(block_on(storage.insert_state_delta(overspending_delta, MilestoneIndex(100_001))).is_err());
+
+            let (index, stored_balances) = block_on(storage.load_balances()).unwrap().unwrap();
+            assert_eq!(index, MilestoneIndex(100_000));
+            assert_eq!(stored_balances, balances);
+
+            block_on(storage.destroy_connection()).unwrap();
+        }
+
+        fn test_revert_state_delta() {
+            let mut storage = T::new();
+            block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
+
+            let from = rand_trits_field::<Address>();
+            let to = rand_trits_field::<Address>();
+            let mut balances = HashMap::new();
+            balances.insert(from.clone(), 10);
+
+            block_on(storage.insert_balances(balances.clone(), MilestoneIndex(1))).unwrap();
+
+            for index in 2..4 {
+                let mut state_delta = StateDeltaMap {
+                    address_to_delta: HashMap::new(),
+                };
+                state_delta.address_to_delta.insert(from.clone(), -5);
+                state_delta.address_to_delta.insert(to.clone(), 5);
+                block_on(storage.insert_state_delta(state_delta, MilestoneIndex(index))).unwrap();
+            }
+
+            let (index, state_delta) = block_on(storage.revert_state_delta()).unwrap();
+            assert_eq!(index, MilestoneIndex(3));
+            assert_eq!(*state_delta.address_to_delta.get(&to).unwrap(), 5);
+            assert!(block_on(storage.load_state_delta(MilestoneIndex(3))).is_err());
+
+            let (index, _) = block_on(storage.revert_state_delta()).unwrap();
+            assert_eq!(index, MilestoneIndex(2));
+
+            let (index, stored_balances) = block_on(storage.load_balances()).unwrap().unwrap();
+            assert_eq!(index, MilestoneIndex(1));
+            assert_eq!(stored_balances, balances);
+
+            // The snapshot balances have no delta to revert.
+            assert!(block_on(storage.revert_state_delta()).is_err());
+
+            block_on(storage.destroy_connection()).unwrap();
+        }
+
+        fn test_append_balances() {
+            let mut storage = T::new();
+            block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
+
+            let mut first_chunk = HashMap::new();
+            let mut last_chunk = HashMap::new();
+            for i in 0..5 {
+                first_chunk.insert(rand_trits_field::<Address>(), i as u64 + 1);
+                last_chunk.insert(rand_trits_field::<Address>(), i as u64 + 1);
+            }
+            let mut balances = first_chunk.clone();
+            balances.extend(last_chunk.clone());
+
+            block_on(storage.clear_balances()).unwrap();
+            block_on(storage.append_balances(first_chunk)).unwrap();
+            // No ledger is stored until its index is.
+            assert!(block_on(storage.load_balances()).unwrap().is_none());
+
+            block_on(storage.append_balances(last_chunk)).unwrap();
+            block_on(storage.insert_ledger_index(MilestoneIndex(42))).unwrap();
+
+            let (index, stored_balances) = block_on(storage.load_balances()).unwrap().unwrap();
+            assert_eq!(index, MilestoneIndex(42));
+            assert_eq!(stored_balances, balances);
+
+            block_on(storage.clear_balances()).unwrap();
+            assert!(block_on(storage.load_balances()).unwrap().is_none());
+
+            block_on(storage.destroy_connection()).unwrap();
+        }
+
+        fn test_insert_and_load_spent_addresses() {
+            let mut storage = T::new();
+            block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
+
+            let mut spent_addresses = HashSet::new();
+            for _ in 0..10 {
+                spent_addresses.insert(rand_trits_field::<Address>());
+            }
+
+            block_on(storage.insert_spent_addresses(spent_addresses.clone())).unwrap();
+            // Inserting an already spent address is not an error.
+            let more_spent_addresses = spent_addresses.iter().take(5).cloned().collect::<HashSet<Address>>();
+            block_on(storage.insert_spent_addresses(more_spent_addresses)).unwrap();
+
+            assert_eq!(block_on(storage.load_spent_addresses()).unwrap(), spent_addresses);
 
             block_on(storage.destroy_connection()).unwrap();
         }
@@ -457,21 +648,92 @@ pub mod tests {
             assert!(result.is_ok())
         }
 
+        // Runs last since it prunes what the other tests stored below its milestones.
+        fn test_prune_below() {
+            let mut storage = T::new();
+            block_on(storage.establish_connection(T::test_db_url().as_str())).unwrap();
+
+            const OLD_INDEX: MilestoneIndex = MilestoneIndex(1_000_001);
+            const PRUNING_INDEX: MilestoneIndex = MilestoneIndex(1_000_003);
+            const RECENT_INDEX: MilestoneIndex = MilestoneIndex(1_000_005);
+
+            let (old_hash, old_tx) = bee_test::transaction::create_random_tx();
+            let (sep_hash, sep_tx) = bee_test::transaction::create_random_tx();
+            let (recent_hash, recent_tx) = bee_test::transaction::create_random_tx();
+            // Attachment timestamps are in milliseconds.
+            let (stale_hash, stale_tx) = bee_test::transaction::create_random_tx_attached_at(1_000_000);
+            let (pruning_hash, pruning_tx) = bee_test::transaction::create_random_tx_attached_at(2_000_000);
+            let (pending_hash, pending_tx) = bee_test::transaction::create_random_tx_attached_at(3_000_000);
+            let mut transactions = HashMap::new();
+            transactions.insert(old_hash, old_tx);
+            transactions.insert(sep_hash, sep_tx);
+            transactions.insert(recent_hash, recent_tx);
+            transactions.insert(stale_hash, stale_tx);
+            transactions.insert(pending_hash, pending_tx);
+            block_on(storage.insert_transactions(transactions)).unwrap();
+            block_on(
+                storage
+                    .update_transactions_set_snapshot_index(vec![old_hash, sep_hash].into_iter().collect(), OLD_INDEX),
+            )
+            .unwrap();
+            block_on(
+                storage.update_transactions_set_snapshot_index(vec![recent_hash].into_iter().collect(), RECENT_INDEX),
+            )
+            .unwrap();
+
+            let old_milestone = bee_test::milestone::create_random_milestone(OLD_INDEX);
+            let recent_milestone = bee_test::milestone::create_random_milestone(RECENT_INDEX);
+            block_on(storage.insert_milestone(bee_test::milestone::clone_ms(&old_milestone))).unwrap();
+            block_on(storage.insert_milestone(bee_test::milestone::clone_ms(&recent_milestone))).unwrap();
+            block_on(storage.insert_state_delta(StateDeltaMap::default(), OLD_INDEX)).unwrap();
+            block_on(storage.insert_state_delta(StateDeltaMap::default(), RECENT_INDEX)).unwrap();
+
+            let solid_entry_points = vec![sep_hash].into_iter().collect();
+
+            // Unconfirmed transactions are kept as long as the pruning milestone is not stored.
+            block_on(storage.prune_below(PRUNING_INDEX, &solid_entry_points)).unwrap();
+            assert!(block_on(storage.find_transaction(old_hash)).is_err());
+            assert!(block_on(storage.find_transaction(stale_hash)).is_ok());
+
+            block_on(storage.insert_transaction(pruning_hash, pruning_tx)).unwrap();
+            block_on(storage.insert_milestone(Milestone::new(pruning_hash, PRUNING_INDEX))).unwrap();
+            block_on(storage.prune_below(PRUNING_INDEX, &solid_entry_points)).unwrap();
+
+            assert!(block_on(storage.find_transaction(old_hash)).is_err());
+            assert!(block_on(storage.find_transaction(sep_hash)).is_ok());
+            assert!(block_on(storage.find_transaction(recent_hash)).is_ok());
+            assert!(block_on(storage.find_transaction(stale_hash)).is_err());
+            assert!(block_on(storage.find_transaction(pruning_hash)).is_ok());
+            assert!(block_on(storage.find_transaction(pending_hash)).is_ok());
+            assert!(block_on(storage.find_milestone(*old_milestone.hash())).is_err());
+            assert!(block_on(storage.find_milestone(*recent_milestone.hash())).is_ok());
+            assert!(block_on(storage.load_state_delta(OLD_INDEX)).is_err());
+            assert!(block_on(storage.load_state_delta(RECENT_INDEX)).is_ok());
+
+            block_on(storage.destroy_connection()).unwrap();
+        }
+
         pub fn run_all_tests() {
             StorageTestRunner::<T>::run_test(|| {
                 Self::test_insert_one_transaction();
                 Self::test_delete_one_transaction();
                 Self::test_transaction_multiple_delete();
+                Self::test_find_transaction_hashes_by_indexes();
                 Self::test_map_hashes_to_approvers();
                 Self::test_map_missing_transaction_hashes_to_approvers();
                 Self::test_insert_one_milestone();
                 Self::test_delete_one_milestone();
+                Self::test_load_milestones();
                 Self::test_insert_transactions_concurrent();
                 Self::test_insert_transactions_batch();
                 Self::test_insert_transactions_batch_concurrent();
                 Self::test_store_and_load_state_delta();
+                Self::test_revert_state_delta();
+                Self::test_append_balances();
+                Self::test_insert_and_load_spent_addresses();
                 Self::test_transaction_update_solid();
                 Self::test_transaction_snapshot_index();
+                Self::test_prune_below();
             })
         }
     }
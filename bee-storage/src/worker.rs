@@ -0,0 +1,22 @@
+// Copyright 2020 IOTA Stiftung
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except in compliance with
+// the License. You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License is distributed on
+// an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and limitations under the License.
+
+use bee_crypto::ternary::Hash;
+use bee_tangle::{Milestone, MilestoneIndex};
+use bee_transaction::bundled::BundledTransaction as Transaction;
+
+pub enum StorageWorkerEvent {
+    Transaction(Hash, Transaction),
+    Milestone(Milestone),
+    Solid(Vec<Hash>),
+    // A snapshot index of 0 marks the transactions as unconfirmed.
+    SnapshotIndex(Vec<Hash>, MilestoneIndex),
+}
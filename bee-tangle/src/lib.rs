@@ -13,10 +13,12 @@
 
 #![warn(missing_docs)]
 
-pub use tangle::Tangle;
+pub use milestone::{Milestone, MilestoneIndex};
+pub use tangle::{Tangle, TransactionLoader};
 
 pub mod traversal;
 
+mod milestone;
 mod tangle;
 mod vertex;
 
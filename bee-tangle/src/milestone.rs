@@ -39,20 +39,25 @@ impl Add for MilestoneIndex {
     }
 }
 
+/// A milestone, identified by the hash of its tail transaction.
+#[derive(Clone)]
 pub struct Milestone {
-    pub(crate) hash: Hash,
-    pub(crate) index: MilestoneIndex,
+    hash: Hash,
+    index: MilestoneIndex,
 }
 
 impl Milestone {
+    /// Creates a new `Milestone`.
     pub fn new(hash: Hash, index: MilestoneIndex) -> Self {
         Self { hash, index }
     }
 
+    /// Returns the hash of the tail transaction of the milestone.
     pub fn hash(&self) -> &Hash {
         &self.hash
     }
 
+    /// Returns the index of the milestone.
     pub fn index(&self) -> MilestoneIndex {
         self.index
     }
@@ -14,9 +14,64 @@ use crate::{vertex::Vertex, TransactionRef as TxRef};
 use bee_crypto::ternary::Hash as TxHash;
 use bee_transaction::{bundled::BundledTransaction as Tx, TransactionVertex};
 
+use async_std::sync::Arc;
+use async_trait::async_trait;
 use dashmap::{mapref::entry::Entry, DashMap, DashSet};
 
-use std::collections::HashSet;
+use std::{
+    cmp,
+    collections::{BinaryHeap, HashSet},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex, RwLock,
+    },
+};
+
+/// Loads the transactions evicted from a bounded Tangle back into memory.
+#[async_trait]
+pub trait TransactionLoader: Send + Sync {
+    /// Returns the transaction associated with the given `hash`, if it is known.
+    async fn load(&self, hash: &TxHash) -> Option<Tx>;
+}
+
+// Entry of the eviction queue, the lowest priority coming out first.
+struct Evictable {
+    priority: u64,
+    hash: TxHash,
+}
+
+impl PartialEq for Evictable {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.hash == other.hash
+    }
+}
+
+impl Eq for Evictable {}
+
+impl PartialOrd for Evictable {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Evictable {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| other.hash.as_bytes().cmp(self.hash.as_bytes()))
+    }
+}
+
+struct Cache<T> {
+    capacity: usize,
+    loader: Arc<dyn TransactionLoader>,
+    // Transactions with the lowest priority are evicted first.
+    priority: fn(&T, u64) -> u64,
+    // Loaded and persisted vertices, by their priority when queued. They are queued again when their priority drops,
+    // while raised priorities are only noticed when coming out of the queue, so entries may be outdated.
+    queue: Mutex<BinaryHeap<Evictable>>,
+}
 
 /// A foundational, thread-safe graph datastructure to represent the IOTA Tangle.
 pub struct Tangle<T>
@@ -26,7 +81,10 @@ where
     pub(crate) vertices: DashMap<TxHash, Vertex<T>>,
     pub(crate) children: DashMap<TxHash, HashSet<TxHash>>,
     pub(crate) tips: DashSet<TxHash>,
-    // TODO: PriorityQueue with customizable priority for implementing cache eviction strategy
+    cache: RwLock<Option<Cache<T>>>,
+    // Number of vertices holding their transaction.
+    loaded: AtomicUsize,
+    clock: AtomicU64,
 }
 
 impl<T> Default for Tangle<T>
@@ -38,6 +96,9 @@ where
             vertices: DashMap::new(),
             children: DashMap::new(),
             tips: DashSet::new(),
+            cache: RwLock::new(None),
+            loaded: AtomicUsize::new(0),
+            clock: AtomicU64::new(0),
         }
     }
 }
@@ -51,10 +112,39 @@ where
         Self::default()
     }
 
+    /// Bounds the number of transactions held in memory to `capacity`. Beyond it, the persisted transactions with
+    /// the lowest `priority`, computed from their metadata and their last access time, are evicted and later reloaded
+    /// through the `loader` by `fetch`. Evicted vertices keep their metadata and edges, so that the children and the
+    /// tips are unaffected, until they are removed or the `loader` can't find their transaction anymore.
+    pub fn set_cache(&self, capacity: usize, loader: Box<dyn TransactionLoader>, priority: fn(&T, u64) -> u64) {
+        let cache = Cache {
+            capacity,
+            loader: Arc::from(loader),
+            priority,
+            queue: Mutex::new(BinaryHeap::new()),
+        };
+        *cache.queue.lock().unwrap() = self.evictables(&cache);
+        *self.cache.write().unwrap() = Some(cache);
+        self.evict_if_full();
+    }
+
+    /// Marks a transaction as persisted, so that it can be evicted and reloaded.
+    pub fn set_persisted(&self, hash: &TxHash) {
+        if let Some(mut vtx) = self.vertices.get_mut(hash) {
+            vtx.value_mut().set_persisted();
+        } else {
+            return;
+        }
+
+        self.enqueue(hash);
+        self.compact_if_outdated();
+        self.evict_if_full();
+    }
+
     /// Inserts a transaction, and returns a thread-safe reference to it in case it didn't already exist.
     pub fn insert(&self, transaction: Tx, hash: TxHash, metadata: T) -> Option<TxRef> {
-        match self.vertices.entry(hash) {
-            Entry::Occupied(_) => None,
+        let tx = match self.vertices.entry(hash) {
+            Entry::Occupied(_) => return None,
             Entry::Vacant(entry) => {
                 self.add_child(*transaction.trunk(), hash);
                 self.add_child(*transaction.branch(), hash);
@@ -71,11 +161,16 @@ where
                 }
 
                 let vtx = Vertex::new(transaction, metadata);
-                let tx = vtx.transaction().clone();
+                vtx.touch(self.tick());
+                let tx = vtx.transaction().cloned();
                 entry.insert(vtx);
-                Some(tx)
+                self.loaded.fetch_add(1, Ordering::Relaxed);
+                tx
             }
-        }
+        };
+
+        // New transactions are not persisted yet, so nothing can be evicted before `set_persisted`.
+        tx
     }
 
     #[inline]
@@ -94,12 +189,204 @@ where
         }
     }
 
-    /// Get the data of a vertex associated with the given `hash`.
+    /// Removes a transaction, evicted or not, and returns whether it existed. Its parents that are left without
+    /// children become tips again.
+    pub fn remove(&self, hash: &TxHash) -> bool {
+        self.vertices
+            .remove(hash)
+            .map(|(_, vtx)| {
+                if vtx.transaction().is_some() {
+                    self.loaded.fetch_sub(1, Ordering::Relaxed);
+                }
+
+                self.remove_child(vtx.trunk(), hash);
+                self.remove_child(vtx.branch(), hash);
+
+                self.tips.remove(hash);
+            })
+            .is_some()
+    }
+
+    #[inline]
+    fn remove_child(&self, parent: &TxHash, child: &TxHash) {
+        if let Some(mut children) = self.children.get_mut(parent) {
+            children.remove(child);
+        }
+        if self
+            .children
+            .remove_if(parent, |_, children| children.is_empty())
+            .is_some()
+            && self.vertices.contains_key(parent)
+        {
+            self.tips.insert(*parent);
+        }
+    }
+
+    /// Get the data of a vertex associated with the given `hash`, if it is held in memory. Evicted transactions are
+    /// only reloaded by `fetch`.
     pub fn get(&self, hash: &TxHash) -> Option<TxRef> {
-        self.vertices.get(hash).map(|vtx| vtx.value().transaction().clone())
+        let tick = self.tick();
+        let vtx = self.vertices.get(hash)?;
+        vtx.value().touch(tick);
+        vtx.value().transaction().cloned()
+    }
+
+    /// Same as `get`, reloading the transaction through the loader if it was evicted.
+    pub async fn fetch(&self, hash: &TxHash) -> Option<TxRef> {
+        if let Some(tx) = self.get(hash) {
+            return Some(tx);
+        }
+        if !self.contains(hash) {
+            return None;
+        }
+
+        // The loader is taken out of the lock, which can't be held across the load.
+        let loader = self.cache.read().unwrap().as_ref()?.loader.clone();
+        let tx = match loader.load(hash).await {
+            Some(tx) => TxRef(Arc::new(tx)),
+            None => {
+                // Only persisted transactions are evicted, this one has been pruned from storage since.
+                self.remove(hash);
+                return None;
+            }
+        };
+
+        if self.vertices.get_mut(hash)?.value_mut().reload(tx.clone()) {
+            self.loaded.fetch_add(1, Ordering::Relaxed);
+            self.enqueue(hash);
+            self.evict_if_full();
+        }
+
+        Some(tx)
+    }
+
+    // Same as `get`, also returning the metadata.
+    pub(crate) fn get_with_metadata(&self, hash: &TxHash) -> Option<(TxRef, T)> {
+        let tx = self.get(hash)?;
+        let metadata = self.get_metadata(hash)?;
+
+        Some((tx, metadata))
+    }
+
+    /// Returns the trunk and the branch of a vertex, which are kept when its transaction is evicted.
+    pub fn get_parents(&self, hash: &TxHash) -> Option<(TxHash, TxHash)> {
+        self.vertices
+            .get(hash)
+            .map(|vtx| (*vtx.value().trunk(), *vtx.value().branch()))
+    }
+
+    /// Reloads the evicted transactions of the past cone of `root`, so that the traversals that follow find them in
+    /// memory. The walk goes through the parents of the vertices whose metadata `follows`, the last ones visited
+    /// being reloaded as well.
+    pub async fn fetch_cone<Follow>(&self, root: &TxHash, follows: Follow)
+    where
+        Follow: Fn(&T) -> bool,
+    {
+        let mut hashes = vec![*root];
+        let mut visited = HashSet::new();
+
+        while let Some(hash) = hashes.pop() {
+            if !visited.insert(hash) {
+                continue;
+            }
+
+            let (trunk, branch, follow) = match self.vertices.get(&hash) {
+                Some(vtx) => (*vtx.trunk(), *vtx.branch(), follows(vtx.metadata())),
+                None => continue,
+            };
+
+            self.fetch(&hash).await;
+
+            if follow {
+                hashes.push(trunk);
+                hashes.push(branch);
+            }
+        }
+    }
+
+    // Queues a vertex for eviction with its current priority, if it is loaded and persisted.
+    fn enqueue(&self, hash: &TxHash) {
+        let cache = self.cache.read().unwrap();
+        let cache = match cache.as_ref() {
+            Some(cache) => cache,
+            None => return,
+        };
+        let priority = match self.vertices.get(hash) {
+            Some(vtx) if vtx.is_persisted() && vtx.transaction().is_some() => {
+                (cache.priority)(vtx.metadata(), vtx.last_access())
+            }
+            _ => return,
+        };
+
+        cache.queue.lock().unwrap().push(Evictable { priority, hash: *hash });
+    }
+
+    // Drops the outdated entries of the queue once they outnumber the loaded vertices.
+    fn compact_if_outdated(&self) {
+        let cache = self.cache.read().unwrap();
+        let cache = match cache.as_ref() {
+            Some(cache) => cache,
+            None => return,
+        };
+
+        if cache.queue.lock().unwrap().len() <= 2 * self.loaded.load(Ordering::Relaxed) + cache.capacity {
+            return;
+        }
+
+        // Vertices are visited without holding the queue, for the other updates not to wait on it.
+        let evictables = self.evictables(cache);
+        *cache.queue.lock().unwrap() = evictables;
     }
 
-    /// Returns whether the transaction is stored in the Tangle.
+    fn evictables(&self, cache: &Cache<T>) -> BinaryHeap<Evictable> {
+        self.vertices
+            .iter()
+            .filter(|vtx| vtx.value().is_persisted() && vtx.value().transaction().is_some())
+            .map(|vtx| Evictable {
+                priority: (cache.priority)(vtx.value().metadata(), vtx.value().last_access()),
+                hash: *vtx.key(),
+            })
+            .collect()
+    }
+
+    // Evicts the transactions with the lowest priority until the capacity is no longer exceeded.
+    fn evict_if_full(&self) {
+        let cache = self.cache.read().unwrap();
+        let cache = match cache.as_ref() {
+            Some(cache) => cache,
+            None => return,
+        };
+        let mut queue = cache.queue.lock().unwrap();
+
+        while self.loaded.load(Ordering::Relaxed) > cache.capacity {
+            let entry = match queue.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            let mut vtx = match self.vertices.get_mut(&entry.hash) {
+                Some(vtx) => vtx,
+                None => continue,
+            };
+            let vtx = vtx.value_mut();
+            let priority = (cache.priority)(vtx.metadata(), vtx.last_access());
+
+            if priority > entry.priority {
+                // Accessed or updated since it was queued.
+                queue.push(Evictable {
+                    priority,
+                    hash: entry.hash,
+                });
+            } else if vtx.evict() {
+                self.loaded.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Returns whether the transaction is stored in the Tangle, evicted or not.
     pub fn contains(&self, hash: &TxHash) -> bool {
         self.vertices.contains_key(hash)
     }
@@ -111,9 +398,7 @@ where
 
     /// Updates the metadata of a particular vertex.
     pub fn set_metadata(&self, hash: &TxHash, metadata: T) {
-        self.vertices.get_mut(hash).map(|mut vtx| {
-            *vtx.value_mut().metadata_mut() = metadata;
-        });
+        self.update_metadata(hash, |m| *m = metadata);
     }
 
     /// Updates the metadata of a vertex.
@@ -121,9 +406,22 @@ where
     where
         Update: Fn(&mut T),
     {
-        self.vertices
-            .get_mut(hash)
-            .map(|mut vtx| update(vtx.value_mut().metadata_mut()));
+        let priority = self.cache.read().unwrap().as_ref().map(|cache| cache.priority);
+        let dropped = match self.vertices.get_mut(hash) {
+            Some(mut vtx) => {
+                let vtx = vtx.value_mut();
+                let before = priority.map(|priority| priority(vtx.metadata(), vtx.last_access()));
+                update(vtx.metadata_mut());
+                let after = priority.map(|priority| priority(vtx.metadata(), vtx.last_access()));
+                after < before
+            }
+            None => false,
+        };
+
+        // A raised priority is noticed when the vertex comes out of the queue, a dropped one has to be queued again.
+        if dropped {
+            self.enqueue(hash);
+        }
     }
 
     /// Returns the number of transactions in the Tangle.
@@ -131,6 +429,11 @@ where
         self.vertices.len()
     }
 
+    /// Returns the number of transactions held in memory, i.e. not evicted.
+    pub fn num_loaded(&self) -> usize {
+        self.loaded.load(Ordering::Relaxed)
+    }
+
     /// Returns the children of a vertex.
     pub fn get_children(&self, hash: &TxHash) -> HashSet<TxHash> {
         let num_children = self.num_children(hash);
@@ -160,13 +463,20 @@ where
         self.vertices.clear();
         self.children.clear();
         self.tips.clear();
+        *self.cache.write().unwrap() = None;
+        self.loaded.store(0, Ordering::Relaxed);
+        self.clock.store(0, Ordering::Relaxed);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bee_test::transaction::create_random_tx;
+    use bee_test::transaction::{create_random_attached_tx, create_random_tx};
+
+    use async_std::task;
+
+    use std::collections::HashMap;
 
     #[test]
     fn new_tangle() {
@@ -193,4 +503,139 @@ mod tests {
         assert!(tangle.contains(&hash));
         assert_eq!(1, tangle.num_tips());
     }
+
+    #[test]
+    fn insert_and_remove() {
+        let tangle = Tangle::new();
+
+        let (a_hash, a) = create_random_tx();
+        let (b_hash, b) = create_random_attached_tx(a_hash, a_hash);
+
+        tangle.insert(a, a_hash, ());
+        tangle.insert(b, b_hash, ());
+
+        assert!(tangle.remove(&b_hash));
+        assert!(!tangle.remove(&b_hash));
+
+        assert_eq!(1, tangle.len());
+        assert!(!tangle.contains(&b_hash));
+        assert_eq!(0, tangle.num_children(&a_hash));
+        assert_eq!(1, tangle.num_tips());
+        assert!(tangle.tips.contains(&a_hash));
+    }
+
+    struct MapLoader(HashMap<TxHash, Tx>);
+
+    #[async_trait]
+    impl TransactionLoader for MapLoader {
+        async fn load(&self, hash: &TxHash) -> Option<Tx> {
+            self.0.get(hash).cloned()
+        }
+    }
+
+    #[test]
+    fn evict_and_reload() {
+        let tangle = Tangle::new();
+
+        let (a_hash, a) = create_random_tx();
+        let (b_hash, b) = create_random_attached_tx(a_hash, a_hash);
+        let (c_hash, c) = create_random_attached_tx(b_hash, a_hash);
+
+        let mut stored = HashMap::new();
+        stored.insert(a_hash, a.clone());
+        stored.insert(b_hash, b.clone());
+        stored.insert(c_hash, c.clone());
+        // Least recently used first.
+        tangle.set_cache(2, Box::new(MapLoader(stored)), |_: &(), last_access| last_access);
+
+        tangle.insert(a.clone(), a_hash, ());
+        tangle.insert(b, b_hash, ());
+        tangle.insert(c, c_hash, ());
+
+        // Transactions are only evicted once persisted.
+        assert_eq!(3, tangle.num_loaded());
+
+        tangle.set_persisted(&a_hash);
+        tangle.set_persisted(&b_hash);
+        tangle.set_persisted(&c_hash);
+
+        assert_eq!(3, tangle.len());
+        assert_eq!(2, tangle.num_loaded());
+        assert!(tangle.vertices.get(&a_hash).unwrap().transaction().is_none());
+        assert!(tangle.contains(&a_hash));
+        assert_eq!(2, tangle.num_children(&a_hash));
+        assert_eq!(1, tangle.num_tips());
+
+        assert!(tangle.get(&a_hash).is_none());
+        assert_eq!(a, *task::block_on(tangle.fetch(&a_hash)).unwrap());
+        assert_eq!(2, tangle.num_loaded());
+        assert!(tangle.vertices.get(&b_hash).unwrap().transaction().is_none());
+
+        assert!(tangle.remove(&b_hash));
+        assert_eq!(2, tangle.num_loaded());
+        assert_eq!(1, tangle.num_children(&a_hash));
+        assert_eq!(1, tangle.num_tips());
+    }
+
+    #[test]
+    fn reload_missing() {
+        let tangle = Tangle::new();
+
+        let (a_hash, a) = create_random_tx();
+
+        tangle.set_cache(0, Box::new(MapLoader(HashMap::new())), |_: &(), last_access| {
+            last_access
+        });
+        tangle.insert(a, a_hash, ());
+        tangle.set_persisted(&a_hash);
+
+        assert_eq!(0, tangle.num_loaded());
+        assert!(tangle.contains(&a_hash));
+
+        // The vertex goes away along with its transaction.
+        assert!(task::block_on(tangle.fetch(&a_hash)).is_none());
+        assert!(!tangle.contains(&a_hash));
+        assert_eq!(0, tangle.len());
+        assert_eq!(0, tangle.num_tips());
+    }
+
+    #[test]
+    fn evict_on_priority_drop() {
+        let tangle = Tangle::new();
+
+        let (a_hash, a) = create_random_tx();
+        let (b_hash, b) = create_random_attached_tx(a_hash, a_hash);
+        let (c_hash, c) = create_random_attached_tx(b_hash, a_hash);
+
+        let mut stored = HashMap::new();
+        stored.insert(a_hash, a.clone());
+        stored.insert(b_hash, b.clone());
+        stored.insert(c_hash, c.clone());
+        // Flagged transactions first, then the least recently used ones.
+        tangle.set_cache(2, Box::new(MapLoader(stored)), |flagged: &bool, last_access| {
+            if *flagged {
+                last_access
+            } else {
+                u64::MAX / 2 + last_access
+            }
+        });
+
+        tangle.insert(a, a_hash, false);
+        tangle.insert(b, b_hash, false);
+        tangle.insert(c, c_hash, false);
+        tangle.set_persisted(&a_hash);
+        tangle.set_persisted(&b_hash);
+        tangle.set_persisted(&c_hash);
+
+        assert!(tangle.vertices.get(&a_hash).unwrap().transaction().is_none());
+
+        tangle.get(&c_hash);
+        tangle.update_metadata(&c_hash, |flagged| *flagged = true);
+        task::block_on(tangle.fetch(&a_hash));
+
+        // The least recently used one is kept as the priority of the other dropped.
+        assert_eq!(2, tangle.num_loaded());
+        assert!(tangle.vertices.get(&b_hash).unwrap().transaction().is_some());
+        assert!(tangle.vertices.get(&c_hash).unwrap().transaction().is_none());
+    }
 }
@@ -14,6 +14,7 @@
 use crate::{tangle::Tangle, TransactionRef as TxRef};
 
 use bee_crypto::ternary::Hash as TxHash;
+use bee_transaction::TransactionVertex;
 
 use std::collections::HashSet;
 
@@ -35,14 +36,12 @@ pub fn visit_parents_follow_trunk<'a, Metadata, Match, Apply>(
     let mut parents = vec![initial];
 
     while let Some(ref hash) = parents.pop() {
-        if let Some(vtx) = tangle.vertices.get(&hash) {
-            let vtx = vtx.value();
-
-            if !matches(vtx.transaction(), vtx.metadata()) {
+        if let Some((tx, metadata)) = tangle.get_with_metadata(&hash) {
+            if !matches(&tx, &metadata) {
                 break;
             } else {
-                apply(&hash, vtx.transaction(), vtx.metadata());
-                parents.push(*vtx.trunk());
+                apply(&hash, &tx, &metadata);
+                parents.push(*tx.trunk());
             }
         }
     }
@@ -65,9 +64,9 @@ pub fn visit_children_follow_trunk<'a, Metadata, Match, Apply>(
     let mut children = vec![initial];
 
     while let Some(ref parent_hash) = children.pop() {
-        if let Some(parent) = tangle.vertices.get(parent_hash) {
-            if matches(parent.value().transaction(), parent.value().metadata()) {
-                apply(parent_hash, parent.value().transaction(), parent.value().metadata());
+        if let Some((parent, metadata)) = tangle.get_with_metadata(parent_hash) {
+            if matches(&parent, &metadata) {
+                apply(parent_hash, &parent, &metadata);
 
                 if let Some(parent_children) = tangle.children.get(parent_hash) {
                     for child_hash in parent_children.value() {
@@ -106,15 +105,13 @@ pub fn visit_parents_depth_first<'a, Metadata, Match, Apply, ElseApply>(
 
     while let Some(hash) = parents.pop() {
         if !visited.contains(&hash) {
-            match tangle.vertices.get(&hash) {
-                Some(vtx) => {
-                    let vtx = vtx.value();
-
-                    apply(&hash, vtx.transaction(), vtx.metadata());
+            match tangle.get_with_metadata(&hash) {
+                Some((tx, metadata)) => {
+                    apply(&hash, &tx, &metadata);
 
-                    if matches(vtx.transaction(), vtx.metadata()) {
-                        parents.push(*vtx.trunk());
-                        parents.push(*vtx.branch());
+                    if matches(&tx, &metadata) {
+                        parents.push(*tx.trunk());
+                        parents.push(*tx.branch());
                     }
                 }
                 None => {
@@ -147,21 +144,19 @@ pub fn visit_children_depth_first<'a, Metadata, Match, Apply, ElseApply>(
     let mut visited = HashSet::new();
 
     while let Some(hash) = children.last() {
-        match tangle.vertices.get(hash) {
-            Some(r) => {
-                let vtx = r.value();
-
-                if visited.contains(vtx.trunk()) && visited.contains(vtx.branch()) {
-                    apply(hash, vtx.transaction(), vtx.metadata());
+        match tangle.get_with_metadata(hash) {
+            Some((tx, metadata)) => {
+                if visited.contains(tx.trunk()) && visited.contains(tx.branch()) {
+                    apply(hash, &tx, &metadata);
                     visited.insert(hash.clone());
                     children.pop();
-                } else if !visited.contains(vtx.trunk()) {
-                    if matches(vtx.transaction(), vtx.metadata()) {
-                        children.push(*vtx.trunk());
+                } else if !visited.contains(tx.trunk()) {
+                    if matches(&tx, &metadata) {
+                        children.push(*tx.trunk());
                     }
-                } else if !visited.contains(vtx.branch()) {
-                    if matches(vtx.transaction(), vtx.metadata()) {
-                        children.push(*vtx.branch());
+                } else if !visited.contains(tx.branch()) {
+                    if matches(&tx, &metadata) {
+                        children.push(*tx.branch());
                     }
                 }
             }
@@ -16,13 +16,20 @@ use bee_transaction::{bundled::BundledTransaction as Tx, TransactionVertex};
 
 use async_std::sync::Arc;
 
-#[derive(Clone)]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// The edges of a vertex are kept apart from its transaction so that the latter can be evicted from memory.
 pub(crate) struct Vertex<T>
 where
     T: Clone + Copy,
 {
-    transaction: TxRef,
+    trunk: TxHash,
+    branch: TxHash,
+    transaction: Option<TxRef>,
     metadata: T,
+    last_access: AtomicU64,
+    // Whether the transaction is in storage, and can then be evicted.
+    persisted: bool,
 }
 
 impl<T> Vertex<T>
@@ -31,21 +38,55 @@ where
 {
     pub fn new(transaction: Tx, metadata: T) -> Self {
         Self {
-            transaction: TxRef(Arc::new(transaction)),
+            trunk: *transaction.trunk(),
+            branch: *transaction.branch(),
+            transaction: Some(TxRef(Arc::new(transaction))),
             metadata,
+            last_access: AtomicU64::new(0),
+            persisted: false,
         }
     }
 
     pub fn trunk(&self) -> &TxHash {
-        self.transaction.trunk()
+        &self.trunk
     }
 
     pub fn branch(&self) -> &TxHash {
-        self.transaction.branch()
+        &self.branch
+    }
+
+    pub fn transaction(&self) -> Option<&TxRef> {
+        self.transaction.as_ref()
+    }
+
+    // Drops the transaction, and returns whether it was held. Transactions that are not persisted are kept.
+    pub fn evict(&mut self) -> bool {
+        self.persisted && self.transaction.take().is_some()
+    }
+
+    pub fn set_persisted(&mut self) {
+        self.persisted = true;
+    }
+
+    pub fn is_persisted(&self) -> bool {
+        self.persisted
+    }
+
+    // Sets back an evicted transaction, and returns whether it was missing.
+    pub fn reload(&mut self, transaction: TxRef) -> bool {
+        if self.transaction.is_some() {
+            return false;
+        }
+        self.transaction = Some(transaction);
+        true
     }
 
-    pub fn transaction(&self) -> &TxRef {
-        &self.transaction
+    pub fn touch(&self, tick: u64) {
+        self.last_access.store(tick, Ordering::Relaxed);
+    }
+
+    pub fn last_access(&self) -> u64 {
+        self.last_access.load(Ordering::Relaxed)
     }
 
     pub fn metadata(&self) -> &T {
@@ -71,10 +112,29 @@ mod tests {
 
         assert_eq!(tx.trunk(), vtx.trunk());
         assert_eq!(tx.branch(), vtx.branch());
-        assert_eq!(tx, **vtx.transaction());
+        assert_eq!(tx, **vtx.transaction().unwrap());
         assert_eq!(metadata, *vtx.metadata());
     }
 
+    #[test]
+    fn evict_and_reload_vertex() {
+        let (_, tx) = create_random_tx();
+
+        let mut vtx = Vertex::new(tx.clone(), ());
+
+        assert!(!vtx.evict());
+        vtx.set_persisted();
+        assert!(vtx.evict());
+        assert!(!vtx.evict());
+        assert!(vtx.transaction().is_none());
+        assert_eq!(tx.trunk(), vtx.trunk());
+        assert_eq!(tx.branch(), vtx.branch());
+
+        assert!(vtx.reload(TxRef(Arc::new(tx.clone()))));
+        assert!(!vtx.reload(TxRef(Arc::new(tx.clone()))));
+        assert_eq!(tx, **vtx.transaction().unwrap());
+    }
+
     #[test]
     fn update_vertex_meta() {
         let (_, tx) = create_random_tx();
@@ -62,6 +62,28 @@ pub fn create_random_tx() -> (Hash, Transaction) {
     (rand_trits_field::<Hash>(), builder.build().unwrap())
 }
 
+pub fn create_random_tx_attached_at(attachment_ts: u64) -> (Hash, Transaction) {
+    let (hash, tx) = create_random_tx();
+    let builder = TransactionBuilder::new()
+        .with_payload(tx.payload().clone())
+        .with_address(tx.address().clone())
+        .with_value(tx.value().clone())
+        .with_obsolete_tag(tx.obsolete_tag().clone())
+        .with_timestamp(tx.timestamp().clone())
+        .with_index(tx.index().clone())
+        .with_last_index(tx.last_index().clone())
+        .with_tag(tx.tag().clone())
+        .with_attachment_ts(Timestamp::from_inner_unchecked(attachment_ts))
+        .with_bundle(tx.bundle().clone())
+        .with_trunk(tx.trunk().clone())
+        .with_branch(tx.branch().clone())
+        .with_attachment_lbts(tx.attachment_lbts().clone())
+        .with_attachment_ubts(tx.attachment_ubts().clone())
+        .with_nonce(tx.nonce().clone());
+
+    (hash, builder.build().unwrap())
+}
+
 pub fn create_random_attached_tx(branch: Hash, trunk: Hash) -> (Hash, Transaction) {
     let builder = TransactionBuilder::new()
         .with_payload(rand_trits_field::<Payload>())
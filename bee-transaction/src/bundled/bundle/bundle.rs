@@ -73,6 +73,13 @@ impl Bundle {
     }
 }
 
+// NOTE: no validation is performed, the transactions are expected to have been validated upon reception.
+impl From<BundledTransactions> for Bundle {
+    fn from(transactions: BundledTransactions) -> Self {
+        Self(transactions)
+    }
+}
+
 impl IntoIterator for Bundle {
     type Item = BundledTransaction;
     type IntoIter = std::vec::IntoIter<BundledTransaction>;
@@ -104,4 +111,59 @@ impl std::ops::Index<usize> for Bundle {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+
+    use super::*;
+
+    use crate::bundled::{BundledTransactionBuilder, Index, Nonce, Payload, Tag, Timestamp, Value};
+
+    fn default_transaction_builder(index: usize, last_index: usize) -> BundledTransactionBuilder {
+        BundledTransactionBuilder::new()
+            .with_payload(Payload::zeros())
+            .with_address(Address::zeros())
+            .with_value(Value::from_inner_unchecked(0))
+            .with_obsolete_tag(Tag::zeros())
+            .with_timestamp(Timestamp::from_inner_unchecked(0))
+            .with_index(Index::from_inner_unchecked(index))
+            .with_last_index(Index::from_inner_unchecked(last_index))
+            .with_tag(Tag::zeros())
+            .with_attachment_ts(Timestamp::from_inner_unchecked(0))
+            .with_bundle(Hash::zeros())
+            .with_trunk(Hash::zeros())
+            .with_branch(Hash::zeros())
+            .with_attachment_lbts(Timestamp::from_inner_unchecked(0))
+            .with_attachment_ubts(Timestamp::from_inner_unchecked(0))
+            .with_nonce(Nonce::zeros())
+    }
+
+    #[test]
+    fn from_transactions_keeps_order() {
+        let bundle_size = 3;
+        let mut transactions = BundledTransactions::new();
+
+        for i in 0..bundle_size {
+            transactions.push(default_transaction_builder(i, bundle_size - 1).build().unwrap());
+        }
+
+        let bundle = Bundle::from(transactions);
+
+        assert_eq!(bundle.len(), bundle_size);
+        for (i, transaction) in bundle.into_iter().enumerate() {
+            assert_eq!(*transaction.index().to_inner(), i);
+        }
+    }
+
+    #[test]
+    fn from_transactions_tail_and_head() {
+        let mut transactions = BundledTransactions::new();
+
+        transactions.push(default_transaction_builder(0, 1).build().unwrap());
+        transactions.push(default_transaction_builder(1, 1).build().unwrap());
+
+        let bundle = Bundle::from(transactions);
+
+        assert_eq!(*bundle.tail().index().to_inner(), 0);
+        assert_eq!(*bundle.head().index().to_inner(), 1);
+        assert!(bundle.get(2).is_none());
+    }
+}
@@ -17,7 +17,7 @@ pub use bundle::{
     Bundle, IncomingBundleBuilder, IncomingBundleBuilderError, OutgoingBundleBuilder, OutgoingBundleBuilderError,
 };
 pub use constants::{
-    ADDRESS_TRIT_LEN, HASH_TRIT_LEN, NONCE_TRIT_LEN, PAYLOAD_TRIT_LEN, TAG_TRIT_LEN, TRANSACTION_BYTE_LEN,
+    ADDRESS_TRIT_LEN, HASH_TRIT_LEN, IOTA_SUPPLY, NONCE_TRIT_LEN, PAYLOAD_TRIT_LEN, TAG_TRIT_LEN, TRANSACTION_BYTE_LEN,
     TRANSACTION_TRIT_LEN, TRANSACTION_TRYT_LEN,
 };
 pub use transaction::{